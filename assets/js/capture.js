@@ -0,0 +1,210 @@
+// 离线截图：把元素连同计算后的样式克隆进 SVG foreignObject，再画到 canvas 上导出 PNG。
+// 图片、背景图和 @font-face 字体都先转成 data URL，整个过程不需要访问网络。
+(function () {
+    "use strict";
+
+    const XHTML_NS = "http://www.w3.org/1999/xhtml";
+    const dataUrlCache = new Map();
+
+    function readBlob(blob) {
+        return new Promise((resolve, reject) => {
+            const reader = new FileReader();
+            reader.onload = () => resolve(reader.result);
+            reader.onerror = () => reject(reader.error);
+            reader.readAsDataURL(blob);
+        });
+    }
+
+    // 失败时返回 null，由调用方保留原来的地址
+    function toDataUrl(url) {
+        if (!url || url.startsWith("data:")) return Promise.resolve(url);
+        const absolute = new URL(url, document.baseURI).href;
+        if (!dataUrlCache.has(absolute)) {
+            dataUrlCache.set(
+                absolute,
+                fetch(absolute)
+                    .then((response) => {
+                        if (!response.ok) throw new Error(`HTTP ${response.status}`);
+                        return response.blob();
+                    })
+                    .then(readBlob)
+                    .catch((err) => {
+                        console.warn("capture: 无法读取资源", absolute, err);
+                        return null;
+                    }),
+            );
+        }
+        return dataUrlCache.get(absolute);
+    }
+
+    const URL_PATTERN = /url\((['"]?)([^'")]+)\1\)/g;
+
+    async function inlineUrls(text) {
+        const urls = new Set();
+        for (const match of text.matchAll(URL_PATTERN)) urls.add(match[2]);
+        for (const url of urls) {
+            const data = await toDataUrl(url);
+            if (data && data !== url) text = text.split(url).join(data);
+        }
+        return text;
+    }
+
+    function copyStyle(source, target) {
+        const style = target.style;
+        for (let i = 0; i < source.length; i++) {
+            const name = source[i];
+            style.setProperty(name, source.getPropertyValue(name), source.getPropertyPriority(name));
+        }
+        // 克隆出来的图像只有一帧，保留当前的动画状态即可
+        style.setProperty("animation", "none");
+        style.setProperty("transition", "none");
+    }
+
+    let pseudoCounter = 0;
+
+    function copyPseudo(original, clone, pseudo, rules) {
+        const computed = getComputedStyle(original, pseudo);
+        const content = computed.getPropertyValue("content");
+        if (!content || content === "none" || content === "normal") return;
+        const className = `baker-capture-${pseudoCounter++}`;
+        clone.classList.add(className);
+        let body = "";
+        for (let i = 0; i < computed.length; i++) {
+            const name = computed[i];
+            body += `${name}: ${computed.getPropertyValue(name)};`;
+        }
+        rules.push(`.${className}${pseudo} { ${body} animation: none; transition: none; }`);
+    }
+
+    function cloneTree(original, rules, jobs) {
+        if (original.nodeType === Node.TEXT_NODE) return original.cloneNode(false);
+        if (original.nodeType !== Node.ELEMENT_NODE) return null;
+        const tag = original.tagName.toLowerCase();
+        if (tag === "script" || tag === "noscript") return null;
+
+        let clone;
+        if (tag === "canvas") {
+            clone = document.createElement("img");
+            try {
+                clone.src = original.toDataURL();
+            } catch (_) {
+                // 被污染的 canvas 无法读取，留空
+            }
+        } else {
+            clone = original.cloneNode(false);
+        }
+
+        const computed = getComputedStyle(original);
+        copyStyle(computed, clone);
+        copyPseudo(original, clone, "::before", rules);
+        copyPseudo(original, clone, "::after", rules);
+
+        const background = computed.getPropertyValue("background-image");
+        if (background && background.includes("url(")) {
+            jobs.push(inlineUrls(background).then((value) => clone.style.setProperty("background-image", value)));
+        }
+
+        if (tag === "img" && original.currentSrc) {
+            jobs.push(
+                toDataUrl(original.currentSrc).then((data) => {
+                    clone.removeAttribute("srcset");
+                    if (data) clone.setAttribute("src", data);
+                }),
+            );
+        } else if (tag === "image") {
+            const href = original.getAttribute("href") || original.getAttribute("xlink:href");
+            if (href) jobs.push(toDataUrl(href).then((data) => data && clone.setAttribute("href", data)));
+        } else if (tag === "input" || tag === "textarea" || tag === "select") {
+            if (tag === "textarea") clone.textContent = original.value;
+            else clone.setAttribute("value", original.value);
+            if (original.checked) clone.setAttribute("checked", "");
+        }
+
+        for (const child of original.childNodes) {
+            const childClone = cloneTree(child, rules, jobs);
+            if (childClone) clone.appendChild(childClone);
+        }
+
+        // 滚动位置不会被克隆，用位移模拟，叠加在子元素原有的变换之前
+        if (original.scrollTop || original.scrollLeft) {
+            const offset = `translate(${-original.scrollLeft}px, ${-original.scrollTop}px)`;
+            for (const child of clone.children) {
+                const transform = child.style.getPropertyValue("transform");
+                child.style.setProperty("transform", transform && transform !== "none" ? `${offset} ${transform}` : offset);
+            }
+        }
+        return clone;
+    }
+
+    async function fontFaces() {
+        const rules = [];
+        for (const sheet of document.styleSheets) {
+            let cssRules;
+            try {
+                cssRules = sheet.cssRules;
+            } catch (_) {
+                // 跨域的样式表无法读取
+                continue;
+            }
+            for (const rule of cssRules) {
+                if (rule.type === CSSRule.FONT_FACE_RULE) {
+                    let text = rule.cssText;
+                    if (sheet.href) {
+                        text = text.replace(URL_PATTERN, (_, quote, url) => `url(${quote}${new URL(url, sheet.href).href}${quote})`);
+                    }
+                    rules.push(inlineUrls(text));
+                }
+            }
+        }
+        return (await Promise.all(rules)).join("\n");
+    }
+
+    function loadImage(src) {
+        return new Promise((resolve, reject) => {
+            const image = new Image();
+            image.onload = () => resolve(image);
+            image.onerror = () => reject(new Error("渲染截图失败"));
+            image.src = src;
+        });
+    }
+
+    // 截取元素并返回 PNG data URL。options.scale 为截图的缩放倍率，默认 1。
+    async function bakerCapture(el, options) {
+        const scaleRaw = Number(options && options.scale);
+        const scale = Number.isFinite(scaleRaw) && scaleRaw > 0 ? scaleRaw : 1;
+        const width = Math.ceil(el.offsetWidth || el.getBoundingClientRect().width);
+        const height = Math.ceil(el.offsetHeight || el.getBoundingClientRect().height);
+        if (!width || !height) throw new Error("截图区域的大小为 0");
+
+        const rules = [];
+        const jobs = [];
+        const clone = cloneTree(el, rules, jobs);
+        // 截图区域本身通常放在屏幕外, 去掉它在页面中的位置
+        for (const name of ["margin", "transform", "left", "top", "right", "bottom"]) {
+            clone.style.removeProperty(name);
+        }
+        clone.style.setProperty("position", "relative");
+        clone.setAttribute("xmlns", XHTML_NS);
+        await Promise.all(jobs);
+
+        const style = document.createElement("style");
+        style.textContent = `${await fontFaces()}\n${rules.join("\n")}`;
+        clone.insertBefore(style, clone.firstChild);
+
+        const markup = new XMLSerializer().serializeToString(clone);
+        const svg =
+            `<svg xmlns="http://www.w3.org/2000/svg" width="${width}" height="${height}">` +
+            `<foreignObject x="0" y="0" width="100%" height="100%">${markup}</foreignObject></svg>`;
+        const image = await loadImage(`data:image/svg+xml;charset=utf-8,${encodeURIComponent(svg)}`);
+
+        const canvas = document.createElement("canvas");
+        canvas.width = Math.ceil(width * scale);
+        canvas.height = Math.ceil(height * scale);
+        const context = canvas.getContext("2d");
+        context.scale(scale, scale);
+        context.drawImage(image, 0, 0, width, height);
+        return canvas.toDataURL("image/png");
+    }
+
+    window.bakerCapture = bakerCapture;
+})();
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+
+/// 每个分块最多携带的字节数
+pub const BLOB_CHUNK_BYTES: usize = 64 * 1024;
+/// 单个二进制数据允许的最大字节数
+pub const MAX_BLOB_BYTES: u64 = 32 * 1024 * 1024;
+
+/// 二进制数据的一个分块
+///
+/// 每个分块都带有完整的元信息, 接收方不需要额外的开始/结束消息;
+/// `offset + data.len() == total_bytes` 的分块即为最后一块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlobChunk {
+    /// 发送方分配的编号, 同一发送方的不同数据之间不能重复
+    pub blob_id: u64,
+    /// MIME 类型, 例如 `image/png`
+    pub mime: String,
+    pub total_bytes: u64,
+    /// 这一块在完整数据中的起始位置
+    pub offset: u64,
+    #[serde(with = "serde_bytes")]
+    pub data: Vec<u8>,
+}
+
+/// 将数据切分为按顺序发送的分块
+pub fn split_blob(blob_id: u64, mime: &str, bytes: &[u8]) -> Vec<BlobChunk> {
+    let chunk = |offset: usize, data: &[u8]| BlobChunk {
+        blob_id,
+        mime: mime.to_string(),
+        total_bytes: bytes.len() as u64,
+        offset: offset as u64,
+        data: data.to_vec(),
+    };
+    if bytes.is_empty() {
+        return vec![chunk(0, &[])];
+    }
+    bytes
+        .chunks(BLOB_CHUNK_BYTES)
+        .enumerate()
+        .map(|(index, data)| chunk(index * BLOB_CHUNK_BYTES, data))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlobError {
+    /// 声明的总大小超过 `MAX_BLOB_BYTES`
+    TooLarge { blob_id: u64, total_bytes: u64 },
+    /// 分块没有按顺序到达
+    OutOfOrder {
+        blob_id: u64,
+        expected: u64,
+        got: u64,
+    },
+    /// 分块的元信息与第一块不一致, 或数据超出了声明的总大小
+    Inconsistent { blob_id: u64 },
+}
+
+impl fmt::Display for BlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLarge {
+                blob_id,
+                total_bytes,
+            } => write!(f, "blob {blob_id} of {total_bytes} bytes exceeds the limit"),
+            Self::OutOfOrder {
+                blob_id,
+                expected,
+                got,
+            } => write!(
+                f,
+                "blob {blob_id}: expected chunk at offset {expected}, got {got}"
+            ),
+            Self::Inconsistent { blob_id } => write!(f, "blob {blob_id}: inconsistent chunk"),
+        }
+    }
+}
+
+impl std::error::Error for BlobError {}
+
+/// 拼装完成的二进制数据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembledBlob {
+    pub blob_id: u64,
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+struct PartialBlob {
+    mime: String,
+    total_bytes: u64,
+    data: Vec<u8>,
+}
+
+/// 将同一发送方的分块拼回完整数据
+///
+/// 不同发送方的 `blob_id` 可能重复, 接收方应为每个发送方各用一个
+#[derive(Default)]
+pub struct BlobAssembler {
+    partial: HashMap<u64, PartialBlob>,
+}
+
+impl BlobAssembler {
+    /// 收下一个分块, 数据完整时返回拼装结果
+    ///
+    /// 出错时丢弃这份数据已收到的部分
+    pub fn push(&mut self, chunk: BlobChunk) -> Result<Option<AssembledBlob>, BlobError> {
+        let blob_id = chunk.blob_id;
+        match self.accept(chunk) {
+            Ok(false) => Ok(None),
+            Ok(true) => Ok(self.partial.remove(&blob_id).map(|partial| AssembledBlob {
+                blob_id,
+                mime: partial.mime,
+                data: partial.data,
+            })),
+            Err(err) => {
+                self.partial.remove(&blob_id);
+                Err(err)
+            }
+        }
+    }
+
+    /// 返回这份数据是否已经完整
+    fn accept(&mut self, chunk: BlobChunk) -> Result<bool, BlobError> {
+        let blob_id = chunk.blob_id;
+        if chunk.total_bytes > MAX_BLOB_BYTES {
+            return Err(BlobError::TooLarge {
+                blob_id,
+                total_bytes: chunk.total_bytes,
+            });
+        }
+        let partial = self.partial.entry(blob_id).or_insert_with(|| PartialBlob {
+            mime: chunk.mime.clone(),
+            total_bytes: chunk.total_bytes,
+            data: Vec::new(),
+        });
+        let received = partial.data.len() as u64;
+        if chunk.offset != received {
+            return Err(BlobError::OutOfOrder {
+                blob_id,
+                expected: received,
+                got: chunk.offset,
+            });
+        }
+        if chunk.mime != partial.mime
+            || chunk.total_bytes != partial.total_bytes
+            || received + chunk.data.len() as u64 > partial.total_bytes
+        {
+            return Err(BlobError::Inconsistent { blob_id });
+        }
+        partial.data.extend_from_slice(&chunk.data);
+        Ok(partial.data.len() as u64 == partial.total_bytes)
+    }
+
+    /// 丢弃尚未拼装完成的数据, 例如发送方断开连接时
+    pub fn clear(&mut self) {
+        self.partial.clear();
+    }
+}
@@ -0,0 +1,66 @@
+use crate::PREFIX_LENGTH_BYTES;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::fmt;
+
+/// 帧内容的编码方式
+///
+/// 连接建立 (`Welcome`) 之前的帧总是 JSON, 之后使用协商出的编码
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Encoding {
+    /// 便于调试, 但二进制数据会膨胀数倍
+    #[default]
+    Json,
+    /// MessagePack, 二进制数据按原样传输
+    MessagePack,
+}
+
+#[derive(Debug)]
+pub enum CodecError {
+    Json(serde_json::Error),
+    MessagePackEncode(rmp_serde::encode::Error),
+    MessagePackDecode(rmp_serde::decode::Error),
+    /// 编码后的内容超出了长度前缀能表示的范围
+    TooLarge(usize),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "json: {err}"),
+            Self::MessagePackEncode(err) => write!(f, "messagepack encode: {err}"),
+            Self::MessagePackDecode(err) => write!(f, "messagepack decode: {err}"),
+            Self::TooLarge(len) => write!(f, "frame body of {len} bytes is too large"),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+impl From<CodecError> for std::io::Error {
+    fn from(err: CodecError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// 将消息编码为带长度前缀的帧
+pub fn encode_frame<T: Serialize>(message: &T, encoding: Encoding) -> Result<Vec<u8>, CodecError> {
+    let body = match encoding {
+        Encoding::Json => serde_json::to_vec(message).map_err(CodecError::Json)?,
+        Encoding::MessagePack => {
+            rmp_serde::to_vec_named(message).map_err(CodecError::MessagePackEncode)?
+        }
+    };
+    let len = u32::try_from(body.len()).map_err(|_| CodecError::TooLarge(body.len()))?;
+    let mut frame = Vec::with_capacity(PREFIX_LENGTH_BYTES + body.len());
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(&body);
+    Ok(frame)
+}
+
+/// 解码一个帧的内容 (不含长度前缀)
+pub fn decode_body<T: DeserializeOwned>(body: &[u8], encoding: Encoding) -> Result<T, CodecError> {
+    match encoding {
+        Encoding::Json => serde_json::from_slice(body).map_err(CodecError::Json),
+        Encoding::MessagePack => rmp_serde::from_slice(body).map_err(CodecError::MessagePackDecode),
+    }
+}
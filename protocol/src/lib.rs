@@ -1,12 +1,56 @@
 use serde::{Deserialize, Serialize};
 
-pub const PROTOCOL_VERSION: u64 = 1;
+mod blob;
+mod codec;
+#[cfg(test)]
+mod tests;
+
+pub use blob::{
+    AssembledBlob, BLOB_CHUNK_BYTES, BlobAssembler, BlobChunk, BlobError, MAX_BLOB_BYTES,
+    split_blob,
+};
+pub use codec::{CodecError, Encoding, decode_body, encode_frame};
+
+pub const PROTOCOL_VERSION: u64 = 3;
 pub const PREFIX_LENGTH_BYTES: usize = 4;
+/// 单个帧 (不含长度前缀) 允许的最大字节数
+pub const MAX_FRAME_BYTES: usize = 4 * 1024 * 1024;
+
+/// 服务器在这么久没有收到客户端的任何帧后发送 `Ping`
+pub const HEARTBEAT_INTERVAL_SECS: u64 = 15;
+/// 服务器在这么久没有收到客户端的任何帧后断开连接
+pub const IDLE_TIMEOUT_SECS: u64 = 45;
+
+/// 在线的其他客户端
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Peer {
+    /// 服务器分配的客户端编号
+    pub client_id: u64,
+    /// 客户端在连接请求中给出的显示名
+    pub name: String,
+}
+
+/// 输入状态, 对应客户端回放中的 `ReplayTypingPhase`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TypingPhase {
+    /// 正在输入
+    Typing,
+    /// 停止输入 (消息已发出或被放弃)
+    Idle,
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum MessageKind {
     /// 客户端: 连接请求
-    ConnectionRequest,
+    ///
+    /// `name`: 显示给其他客户端的名字
+    ///
+    /// `encodings`: 客户端支持的帧编码, 按偏好排列; 为空时只使用 JSON
+    ConnectionRequest {
+        name: String,
+        #[serde(default)]
+        encodings: Vec<Encoding>,
+    },
 
     /// 服务器: 请求密码
     PasswordRequest,
@@ -24,13 +68,77 @@ pub enum MessageKind {
     /// 服务器: 建立连接
     ///
     /// 这个连接不是 Tcp 意义上的连接, 请注意
-    Welcome,
+    ///
+    /// `client_id`: 服务器为这个客户端分配的编号
+    ///
+    /// `encoding`: 协商出的帧编码, 双方从下一帧起使用; 此前的帧一律为 JSON
+    Welcome {
+        client_id: u64,
+        #[serde(default)]
+        encoding: Encoding,
+    },
 
     /// 服务器: 确认, 无误
     Ok,
+
+    /// 双方: 心跳
+    ///
+    /// 收到后应尽快以相同的 `nonce` 回复 `Pong`
+    Ping { nonce: u64 },
+
+    /// 双方: 心跳回复
+    Pong { nonce: u64 },
+
+    /// 客户端: 更新自己的输入状态
+    Typing { phase: TypingPhase },
+
+    /// 服务器: 建立连接后发送, 当前在线的其他客户端
+    PresenceList { peers: Vec<Peer> },
+
+    /// 服务器: 有客户端建立了连接
+    PeerJoined { peer: Peer },
+
+    /// 服务器: 有客户端断开了连接
+    PeerLeft { client_id: u64 },
+
+    /// 服务器: 其他客户端的输入状态变化
+    PeerTyping { client_id: u64, phase: TypingPhase },
+
+    /// 服务器: 即将断开连接
+    ///
+    /// `reason`: 理由, 例如空闲超时
+    Disconnect { reason: String },
+
+    /// 服务器: 客户端违反了协议 (帧无法解析、帧过大、发送过于频繁等), 随后断开连接
+    ///
+    /// `reason`: 理由
+    ProtocolError { reason: String },
+
+    /// 客户端: 二进制数据 (例如图片) 的一个分块, 由服务器转发给其他客户端
+    Blob { chunk: BlobChunk },
+
+    /// 服务器: 其他客户端发送的二进制数据分块
+    PeerBlob { client_id: u64, chunk: BlobChunk },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ClientRequest {
-    kind: MessageKind,
+    pub kind: MessageKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerMessage {
+    pub kind: MessageKind,
+}
+
+impl From<MessageKind> for ClientRequest {
+    fn from(kind: MessageKind) -> Self {
+        Self { kind }
+    }
+}
+
+impl From<MessageKind> for ServerMessage {
+    fn from(kind: MessageKind) -> Self {
+        Self { kind }
+    }
 }
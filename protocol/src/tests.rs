@@ -0,0 +1,209 @@
+use crate::{
+    BLOB_CHUNK_BYTES, BlobAssembler, BlobChunk, BlobError, ClientRequest, Encoding, MessageKind,
+    PREFIX_LENGTH_BYTES, Peer, ServerMessage, TypingPhase, decode_body, encode_frame, split_blob,
+};
+
+/// 每种 `MessageKind` 各一个样例
+///
+/// 新增变体时 `variant_name` 会无法编译, 提醒在这里补上样例
+fn sample_kinds() -> Vec<MessageKind> {
+    let peer = Peer {
+        client_id: 7,
+        name: "佩丽卡".to_string(),
+    };
+    let chunk = BlobChunk {
+        blob_id: 3,
+        mime: "image/png".to_string(),
+        total_bytes: 5,
+        offset: 0,
+        data: vec![0x89, b'P', b'N', b'G', 0xff],
+    };
+    vec![
+        MessageKind::ConnectionRequest {
+            name: "管理员".to_string(),
+            encodings: vec![Encoding::MessagePack, Encoding::Json],
+        },
+        MessageKind::PasswordRequest,
+        MessageKind::GiveYouPassword {
+            password: "hunter2".to_string(),
+        },
+        MessageKind::ConnectRefuse {
+            reason: "密码错误".to_string(),
+        },
+        MessageKind::Welcome {
+            client_id: 1,
+            encoding: Encoding::MessagePack,
+        },
+        MessageKind::Ok,
+        MessageKind::Ping { nonce: u64::MAX },
+        MessageKind::Pong { nonce: 0 },
+        MessageKind::Typing {
+            phase: TypingPhase::Typing,
+        },
+        MessageKind::PresenceList {
+            peers: vec![peer.clone()],
+        },
+        MessageKind::PeerJoined { peer },
+        MessageKind::PeerLeft { client_id: 7 },
+        MessageKind::PeerTyping {
+            client_id: 7,
+            phase: TypingPhase::Idle,
+        },
+        MessageKind::Disconnect {
+            reason: "空闲超时".to_string(),
+        },
+        MessageKind::ProtocolError {
+            reason: "帧过大".to_string(),
+        },
+        MessageKind::Blob {
+            chunk: chunk.clone(),
+        },
+        MessageKind::PeerBlob {
+            client_id: 7,
+            chunk,
+        },
+    ]
+}
+
+fn variant_name(kind: &MessageKind) -> &'static str {
+    match kind {
+        MessageKind::ConnectionRequest { .. } => "ConnectionRequest",
+        MessageKind::PasswordRequest => "PasswordRequest",
+        MessageKind::GiveYouPassword { .. } => "GiveYouPassword",
+        MessageKind::ConnectRefuse { .. } => "ConnectRefuse",
+        MessageKind::Welcome { .. } => "Welcome",
+        MessageKind::Ok => "Ok",
+        MessageKind::Ping { .. } => "Ping",
+        MessageKind::Pong { .. } => "Pong",
+        MessageKind::Typing { .. } => "Typing",
+        MessageKind::PresenceList { .. } => "PresenceList",
+        MessageKind::PeerJoined { .. } => "PeerJoined",
+        MessageKind::PeerLeft { .. } => "PeerLeft",
+        MessageKind::PeerTyping { .. } => "PeerTyping",
+        MessageKind::Disconnect { .. } => "Disconnect",
+        MessageKind::ProtocolError { .. } => "ProtocolError",
+        MessageKind::Blob { .. } => "Blob",
+        MessageKind::PeerBlob { .. } => "PeerBlob",
+    }
+}
+
+fn decode_frame<T: serde::de::DeserializeOwned>(frame: &[u8], encoding: Encoding) -> T {
+    let (prefix, body) = frame.split_at(PREFIX_LENGTH_BYTES);
+    let len = u32::from_be_bytes(prefix.try_into().unwrap()) as usize;
+    assert_eq!(len, body.len());
+    decode_body(body, encoding).unwrap()
+}
+
+#[test]
+fn test_sample_kinds_cover_every_variant() {
+    let mut names: Vec<_> = sample_kinds().iter().map(variant_name).collect();
+    let total = names.len();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), total);
+    assert_eq!(total, 17);
+}
+
+#[test]
+fn test_round_trip_every_kind() {
+    for encoding in [Encoding::Json, Encoding::MessagePack] {
+        for kind in sample_kinds() {
+            let request = ClientRequest::from(kind.clone());
+            let frame = encode_frame(&request, encoding).unwrap();
+            assert_eq!(decode_frame::<ClientRequest>(&frame, encoding), request);
+
+            let message = ServerMessage::from(kind);
+            let frame = encode_frame(&message, encoding).unwrap();
+            assert_eq!(decode_frame::<ServerMessage>(&frame, encoding), message);
+        }
+    }
+}
+
+#[test]
+fn test_connection_request_without_encodings() {
+    // 旧客户端不发送 `encodings`, 视为只支持 JSON
+    let request: ClientRequest =
+        serde_json::from_str(r#"{"kind":{"ConnectionRequest":{"name":"a"}}}"#).unwrap();
+    assert_eq!(
+        request.kind,
+        MessageKind::ConnectionRequest {
+            name: "a".to_string(),
+            encodings: vec![],
+        }
+    );
+}
+
+#[test]
+fn test_message_pack_keeps_blob_bytes_compact() {
+    let data = vec![0xffu8; 4096];
+    let message = ServerMessage::from(MessageKind::Blob {
+        chunk: split_blob(1, "image/png", &data).remove(0),
+    });
+    let json = encode_frame(&message, Encoding::Json).unwrap();
+    let packed = encode_frame(&message, Encoding::MessagePack).unwrap();
+    assert!(packed.len() < data.len() + 128);
+    assert!(json.len() > data.len() * 3);
+}
+
+#[test]
+fn test_split_and_assemble_blob() {
+    let data: Vec<u8> = (0..BLOB_CHUNK_BYTES * 2 + 10).map(|i| i as u8).collect();
+    let chunks = split_blob(9, "image/webp", &data);
+    assert_eq!(chunks.len(), 3);
+    assert!(
+        chunks
+            .iter()
+            .all(|chunk| chunk.data.len() <= BLOB_CHUNK_BYTES)
+    );
+
+    let mut assembler = BlobAssembler::default();
+    let (last, rest) = chunks.split_last().unwrap();
+    for chunk in rest {
+        assert_eq!(assembler.push(chunk.clone()), Ok(None));
+    }
+    let blob = assembler.push(last.clone()).unwrap().unwrap();
+    assert_eq!(blob.blob_id, 9);
+    assert_eq!(blob.mime, "image/webp");
+    assert_eq!(blob.data, data);
+
+    let empty = split_blob(10, "image/png", &[]);
+    assert_eq!(empty.len(), 1);
+    let blob = assembler.push(empty[0].clone()).unwrap().unwrap();
+    assert!(blob.data.is_empty());
+}
+
+#[test]
+fn test_assembler_rejects_bad_chunks() {
+    let data = vec![1u8; BLOB_CHUNK_BYTES + 1];
+    let chunks = split_blob(1, "image/png", &data);
+
+    let mut assembler = BlobAssembler::default();
+    assert_eq!(
+        assembler.push(chunks[1].clone()),
+        Err(BlobError::OutOfOrder {
+            blob_id: 1,
+            expected: 0,
+            got: BLOB_CHUNK_BYTES as u64,
+        })
+    );
+
+    assert_eq!(assembler.push(chunks[0].clone()), Ok(None));
+    let mut changed = chunks[1].clone();
+    changed.mime = "image/gif".to_string();
+    assert_eq!(
+        assembler.push(changed),
+        Err(BlobError::Inconsistent { blob_id: 1 })
+    );
+    // 出错后已收到的部分被丢弃, 需要从头发送
+    assert!(matches!(
+        assembler.push(chunks[1].clone()),
+        Err(BlobError::OutOfOrder { .. })
+    ));
+
+    let mut huge = chunks[0].clone();
+    huge.total_bytes = u64::MAX;
+    assert!(matches!(
+        assembler.push(huge),
+        Err(BlobError::TooLarge { .. })
+    ));
+}
@@ -0,0 +1,165 @@
+use crate::{connections::AuthState, state::ServerState};
+use log::{error, info, warn};
+use std::{fmt::Write as _, net::SocketAddr, sync::Arc};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+const HELP: &str = "\
+commands:
+  status                 uptime, connection count and throughput
+  clients                list all connections
+  kick <id> [reason]     disconnect a client
+  help                   show this message
+  quit                   close the admin session
+";
+
+/// 管理接口: 只监听本机地址的文本控制台, 每行一条命令
+///
+/// 可以用 `nc 127.0.0.1 7301` 之类的工具连接
+pub async fn serve(listener: TcpListener, state: Arc<ServerState>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer_addr)) => {
+                if !peer_addr.ip().is_loopback() {
+                    warn!("Rejected admin connection from {}", peer_addr);
+                    continue;
+                }
+                info!("Admin connected from {}", peer_addr);
+                tokio::spawn(handle_admin(stream, peer_addr, state.clone()));
+            }
+            Err(err) => error!("Failed to accept admin connection: {}", err),
+        }
+    }
+}
+
+async fn handle_admin(stream: TcpStream, peer_addr: SocketAddr, state: Arc<ServerState>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    if writer
+        .write_all(b"Baker-Dx admin console, type `help` for commands\n")
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            continue;
+        };
+        let reply = match command {
+            "status" => status(&state),
+            "clients" => clients(&state),
+            "kick" => {
+                let id = words.next().and_then(|id| id.parse::<u64>().ok());
+                let reason = words.collect::<Vec<_>>().join(" ");
+                kick(&state, id, reason)
+            }
+            "help" => HELP.to_string(),
+            "quit" | "exit" => break,
+            other => format!("unknown command `{other}`, type `help` for commands\n"),
+        };
+        if writer.write_all(reply.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+    info!("Admin disconnected from {}", peer_addr);
+}
+
+fn format_duration(secs: u64) -> String {
+    let (days, rest) = (secs / 86400, secs % 86400);
+    let clock = format!(
+        "{:02}:{:02}:{:02}",
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    );
+    if days > 0 {
+        format!("{days}d {clock}")
+    } else {
+        clock
+    }
+}
+
+fn status(state: &ServerState) -> String {
+    let connections = state.connections.snapshot();
+    let established = connections
+        .iter()
+        .filter(|info| info.auth == AuthState::Established)
+        .count();
+    let throughput = state.connections.throughput();
+
+    let mut reply = String::new();
+    let _ = writeln!(
+        reply,
+        "uptime       {}",
+        format_duration(state.connections.uptime_secs())
+    );
+    let _ = writeln!(
+        reply,
+        "connections  {} ({} established)",
+        connections.len(),
+        established
+    );
+    let _ = writeln!(
+        reply,
+        "frames       {} in, {} out",
+        throughput.frames_in, throughput.frames_out
+    );
+    let _ = writeln!(
+        reply,
+        "bytes        {} in, {} out",
+        throughput.bytes_in, throughput.bytes_out
+    );
+    let _ = writeln!(
+        reply,
+        "throughput   {:.2} frames/s over the last {}s",
+        throughput.recent_frames_per_sec, throughput.recent_window_secs
+    );
+    reply
+}
+
+fn clients(state: &ServerState) -> String {
+    let connections = state.connections.snapshot();
+    if connections.is_empty() {
+        return "no clients\n".to_string();
+    }
+    let mut reply = format!(
+        "{:>6}  {:<22}  {:<17}  {:>9}  {:>8}  {:>8}  name\n",
+        "id", "address", "state", "online", "in", "out"
+    );
+    for info in connections {
+        let _ = writeln!(
+            reply,
+            "{:>6}  {:<22}  {:<17}  {:>9}  {:>8}  {:>8}  {}",
+            info.client_id,
+            info.peer_addr.to_string(),
+            info.auth.label(),
+            format_duration(info.connected_at.elapsed().as_secs()),
+            info.frames_in,
+            info.frames_out,
+            info.name.as_deref().unwrap_or("-"),
+        );
+    }
+    reply
+}
+
+fn kick(state: &ServerState, client_id: Option<u64>, reason: String) -> String {
+    let Some(client_id) = client_id else {
+        return "usage: kick <id> [reason]\n".to_string();
+    };
+    let reason = if reason.is_empty() {
+        "被管理员断开".to_string()
+    } else {
+        reason
+    };
+    if state.connections.kick(client_id, reason) {
+        info!("Admin kicked client {}", client_id);
+        format!("kicked client {client_id}\n")
+    } else {
+        format!("no client with id {client_id}\n")
+    }
+}
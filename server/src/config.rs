@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+/// 服务器配置, 从环境变量读取
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// 监听地址, `BAKER_SERVER_ADDR`
+    pub bind_addr: String,
+    /// 管理接口的监听地址, `BAKER_ADMIN_ADDR`; 为空时不启用, 只允许本机地址
+    pub admin_addr: Option<String>,
+    /// 连接密码, `BAKER_SERVER_PASSWORD`; 为空时不要求密码
+    pub password: Option<String>,
+    /// 没有收到任何帧多久之后发送 `Ping`
+    pub heartbeat_interval: Duration,
+    /// 没有收到任何帧多久之后断开连接
+    pub idle_timeout: Duration,
+    /// 单个连接每秒允许的帧数
+    pub frames_per_sec: f64,
+    /// 单个连接允许的突发帧数
+    pub frame_burst: u32,
+    /// 单个连接每秒允许转发的二进制数据字节数; 二进制分块不占用帧数的限制
+    pub blob_bytes_per_sec: f64,
+    /// 单个连接允许突发转发的二进制数据字节数, 至少能一次发完一个最大的二进制数据
+    pub blob_burst_bytes: u32,
+    /// 同一 IP 同时允许的连接数
+    pub max_connections_per_ip: usize,
+    /// 同一 IP 每分钟允许发起的连接数
+    pub connects_per_minute_per_ip: u32,
+    /// 同一 IP 密码错误多少次之后封禁
+    pub max_failed_passwords: u32,
+    /// 封禁时长
+    pub ban_duration: Duration,
+    /// 单个连接允许出现多少个无法解析的帧
+    pub max_malformed_frames: u32,
+    /// 每个客户端最多积压多少条待发送的消息, 超出时断开这个客户端
+    pub outbox_capacity: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: "127.0.0.1:7300".to_string(),
+            admin_addr: Some("127.0.0.1:7301".to_string()),
+            password: None,
+            heartbeat_interval: Duration::from_secs(protocol::HEARTBEAT_INTERVAL_SECS),
+            idle_timeout: Duration::from_secs(protocol::IDLE_TIMEOUT_SECS),
+            frames_per_sec: 20.0,
+            frame_burst: 40,
+            blob_bytes_per_sec: 4.0 * 1024.0 * 1024.0,
+            blob_burst_bytes: protocol::MAX_BLOB_BYTES as u32,
+            max_connections_per_ip: 8,
+            connects_per_minute_per_ip: 30,
+            max_failed_passwords: 5,
+            ban_duration: Duration::from_secs(10 * 60),
+            max_malformed_frames: 3,
+            outbox_capacity: 256,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(addr) = std::env::var("BAKER_SERVER_ADDR") {
+            config.bind_addr = addr;
+        }
+        if let Ok(addr) = std::env::var("BAKER_ADMIN_ADDR") {
+            config.admin_addr = Some(addr).filter(|addr| !addr.is_empty());
+        }
+        config.password = std::env::var("BAKER_SERVER_PASSWORD")
+            .ok()
+            .filter(|password| !password.is_empty());
+        config
+    }
+}
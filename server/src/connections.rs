@@ -0,0 +1,209 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tokio::{sync::mpsc::UnboundedSender, time::Instant};
+
+/// 吞吐量统计的滑动窗口长度 (秒)
+const THROUGHPUT_WINDOW_SECS: u64 = 60;
+
+/// 连接的认证状态, 供管理接口展示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthState {
+    /// 尚未发送连接请求
+    Connecting,
+    /// 已请求密码
+    AwaitingPassword,
+    /// 已建立连接
+    Established,
+}
+
+impl AuthState {
+    pub fn label(self) -> &'static str {
+        match self {
+            AuthState::Connecting => "connecting",
+            AuthState::AwaitingPassword => "awaiting-password",
+            AuthState::Established => "established",
+        }
+    }
+}
+
+/// 某个连接在某一时刻的快照
+#[derive(Debug, Clone)]
+pub struct ConnectionInfo {
+    pub client_id: u64,
+    pub peer_addr: SocketAddr,
+    pub name: Option<String>,
+    pub auth: AuthState,
+    pub connected_at: Instant,
+    pub frames_in: u64,
+    pub frames_out: u64,
+}
+
+struct ConnectionEntry {
+    info: ConnectionInfo,
+    /// 管理接口踢出连接时发送理由
+    kick: UnboundedSender<String>,
+}
+
+/// 每秒一个计数, 用于计算最近一段时间的吞吐量
+#[derive(Default)]
+struct Window {
+    buckets: VecDeque<(u64, u64)>,
+}
+
+impl Window {
+    fn add(&mut self, second: u64, count: u64) {
+        match self.buckets.back_mut() {
+            Some((last, total)) if *last == second => *total += count,
+            _ => self.buckets.push_back((second, count)),
+        }
+        self.prune(second);
+    }
+
+    fn prune(&mut self, now: u64) {
+        while self
+            .buckets
+            .front()
+            .is_some_and(|(second, _)| second + THROUGHPUT_WINDOW_SECS <= now)
+        {
+            self.buckets.pop_front();
+        }
+    }
+
+    fn sum(&mut self, now: u64) -> u64 {
+        self.prune(now);
+        self.buckets.iter().map(|(_, count)| count).sum()
+    }
+}
+
+/// 服务器整体的吞吐量
+#[derive(Debug, Clone, Copy)]
+pub struct Throughput {
+    pub frames_in: u64,
+    pub frames_out: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    /// 最近 `THROUGHPUT_WINDOW_SECS` 秒内每秒的平均帧数
+    pub recent_frames_per_sec: f64,
+    pub recent_window_secs: u64,
+}
+
+/// 所有 TCP 连接 (包括尚未认证的) 及吞吐量统计, 供管理接口使用
+pub struct Connections {
+    started_at: Instant,
+    entries: Mutex<HashMap<u64, ConnectionEntry>>,
+    frames_in: AtomicU64,
+    frames_out: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    window: Mutex<Window>,
+}
+
+impl Default for Connections {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            entries: Mutex::default(),
+            frames_in: AtomicU64::default(),
+            frames_out: AtomicU64::default(),
+            bytes_in: AtomicU64::default(),
+            bytes_out: AtomicU64::default(),
+            window: Mutex::default(),
+        }
+    }
+}
+
+impl Connections {
+    pub fn register(&self, client_id: u64, peer_addr: SocketAddr, kick: UnboundedSender<String>) {
+        let info = ConnectionInfo {
+            client_id,
+            peer_addr,
+            name: None,
+            auth: AuthState::Connecting,
+            connected_at: Instant::now(),
+            frames_in: 0,
+            frames_out: 0,
+        };
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(client_id, ConnectionEntry { info, kick });
+    }
+
+    pub fn unregister(&self, client_id: u64) {
+        self.entries.lock().unwrap().remove(&client_id);
+    }
+
+    pub fn set_auth(&self, client_id: u64, auth: AuthState, name: Option<&str>) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&client_id) {
+            entry.info.auth = auth;
+            if let Some(name) = name {
+                entry.info.name = Some(name.to_string());
+            }
+        }
+    }
+
+    pub fn record_in(&self, client_id: u64, bytes: usize) {
+        self.frames_in.fetch_add(1, Ordering::Relaxed);
+        self.bytes_in.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.window.lock().unwrap().add(self.uptime_secs(), 1);
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&client_id) {
+            entry.info.frames_in += 1;
+        }
+    }
+
+    pub fn record_out(&self, client_id: u64, bytes: usize) {
+        self.frames_out.fetch_add(1, Ordering::Relaxed);
+        self.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.window.lock().unwrap().add(self.uptime_secs(), 1);
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&client_id) {
+            entry.info.frames_out += 1;
+        }
+    }
+
+    /// 按客户端编号排序的所有连接
+    pub fn snapshot(&self) -> Vec<ConnectionInfo> {
+        let mut infos = self
+            .entries
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect::<Vec<_>>();
+        infos.sort_by_key(|info| info.client_id);
+        infos
+    }
+
+    /// 要求连接断开, 连接不存在时返回 `false`
+    pub fn kick(&self, client_id: u64, reason: String) -> bool {
+        self.entries
+            .lock()
+            .unwrap()
+            .get(&client_id)
+            .is_some_and(|entry| entry.kick.send(reason).is_ok())
+    }
+
+    pub fn uptime_secs(&self) -> u64 {
+        self.started_at.elapsed().as_secs()
+    }
+
+    pub fn throughput(&self) -> Throughput {
+        let uptime = self.uptime_secs();
+        let recent = self.window.lock().unwrap().sum(uptime);
+        // 刚启动时窗口还没有填满
+        let window_secs = (uptime + 1).min(THROUGHPUT_WINDOW_SECS);
+        Throughput {
+            frames_in: self.frames_in.load(Ordering::Relaxed),
+            frames_out: self.frames_out.load(Ordering::Relaxed),
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            recent_frames_per_sec: recent as f64 / window_secs as f64,
+            recent_window_secs: window_secs,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use protocol::Encoding;
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// 日志中最多展示的帧内容字节数
+const PAYLOAD_PREVIEW_BYTES: usize = 256;
+
+/// 读取一个带长度前缀的帧
+///
+/// 长度超过 `protocol::MAX_FRAME_BYTES` 时返回 `InvalidData`, 此时流已无法继续使用
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    let mut len_buf = [0u8; protocol::PREFIX_LENGTH_BYTES];
+    reader.read_exact(&mut len_buf).await?;
+    let pack_len = u32::from_be_bytes(len_buf) as usize;
+    if pack_len > protocol::MAX_FRAME_BYTES {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("frame of {pack_len} bytes exceeds the limit"),
+        ));
+    }
+
+    let mut content = vec![0u8; pack_len];
+    reader.read_exact(&mut content).await?;
+    Ok(content)
+}
+
+/// 以指定编码写入一个带长度前缀的帧, 返回写入的字节数
+pub async fn write_frame<W: AsyncWrite + Unpin, T: Serialize>(
+    writer: &mut W,
+    message: &T,
+    encoding: Encoding,
+) -> std::io::Result<usize> {
+    let frame = protocol::encode_frame(message, encoding)?;
+    writer.write_all(&frame).await?;
+    writer.flush().await?;
+    Ok(frame.len())
+}
+
+/// 用于日志的帧内容预览: 截断到固定长度, 非 UTF-8 和控制字符都会被转义
+pub fn payload_preview(content: &[u8]) -> String {
+    let shown = &content[..content.len().min(PAYLOAD_PREVIEW_BYTES)];
+    let mut preview = String::from_utf8_lossy(shown).escape_debug().to_string();
+    if content.len() > shown.len() {
+        preview.push_str(&format!("... ({} bytes total)", content.len()));
+    }
+    preview
+}
@@ -0,0 +1,147 @@
+use crate::config::ServerConfig;
+use std::{collections::HashMap, net::IpAddr, sync::Mutex, time::Duration};
+use tokio::time::Instant;
+
+/// 令牌桶, 用于限制单位时间内的操作次数
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        Self {
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            refill_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+    }
+
+    /// 取出一个令牌, 没有令牌时返回 `false`
+    pub fn try_take(&mut self) -> bool {
+        self.try_take_many(1)
+    }
+
+    /// 一次取出 `count` 个令牌, 不够时不取并返回 `false`
+    pub fn try_take_many(&mut self, count: u32) -> bool {
+        self.refill();
+        if self.tokens >= count as f64 {
+            self.tokens -= count as f64;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_full(&mut self) -> bool {
+        self.refill();
+        self.tokens >= self.capacity
+    }
+}
+
+struct IpRecord {
+    connections: usize,
+    connect_bucket: TokenBucket,
+    failed_passwords: u32,
+    last_failure: Option<Instant>,
+    banned_until: Option<Instant>,
+}
+
+impl IpRecord {
+    /// 没有连接、没有封禁、最近也没有密码错误的记录可以丢弃
+    fn is_stale(&mut self, now: Instant, config: &ServerConfig) -> bool {
+        self.connections == 0
+            && self.banned_until.is_none_or(|until| until <= now)
+            && self
+                .last_failure
+                .is_none_or(|at| now.duration_since(at) >= config.ban_duration)
+            && self.connect_bucket.is_full()
+    }
+}
+
+/// 按 IP 记录连接数、连接频率和密码错误次数
+#[derive(Default)]
+pub struct Guard {
+    records: Mutex<HashMap<IpAddr, IpRecord>>,
+}
+
+impl Guard {
+    /// 检查是否允许来自 `ip` 的新连接, 拒绝时返回给客户端的理由
+    pub fn admit(&self, ip: IpAddr, config: &ServerConfig) -> Result<(), String> {
+        let now = Instant::now();
+        let mut records = self.records.lock().unwrap();
+        records.retain(|_, record| !record.is_stale(now, config));
+
+        let record = records.entry(ip).or_insert_with(|| IpRecord {
+            connections: 0,
+            connect_bucket: TokenBucket::new(
+                config.connects_per_minute_per_ip,
+                config.connects_per_minute_per_ip as f64 / 60.0,
+            ),
+            failed_passwords: 0,
+            last_failure: None,
+            banned_until: None,
+        });
+
+        if let Some(until) = record.banned_until {
+            if until > now {
+                return Err(format!(
+                    "密码错误次数过多, 请在 {} 秒后重试",
+                    until.duration_since(now).as_secs().max(1)
+                ));
+            }
+            record.banned_until = None;
+            record.failed_passwords = 0;
+            record.last_failure = None;
+        }
+        if record.connections >= config.max_connections_per_ip {
+            return Err("来自同一地址的连接过多".to_string());
+        }
+        if !record.connect_bucket.try_take() {
+            return Err("连接过于频繁, 请稍后再试".to_string());
+        }
+
+        record.connections += 1;
+        Ok(())
+    }
+
+    /// 连接关闭时调用, 与成功的 `admit` 一一对应
+    pub fn release(&self, ip: IpAddr) {
+        let mut records = self.records.lock().unwrap();
+        if let Some(record) = records.get_mut(&ip) {
+            record.connections = record.connections.saturating_sub(1);
+        }
+    }
+
+    /// 记录一次密码错误, 返回封禁时长 (如果这次错误导致了封禁)
+    pub fn record_failed_password(&self, ip: IpAddr, config: &ServerConfig) -> Option<Duration> {
+        let mut records = self.records.lock().unwrap();
+        let record = records.get_mut(&ip)?;
+        record.failed_passwords += 1;
+        record.last_failure = Some(Instant::now());
+        if record.failed_passwords >= config.max_failed_passwords {
+            record.banned_until = Some(Instant::now() + config.ban_duration);
+            return Some(config.ban_duration);
+        }
+        None
+    }
+
+    pub fn record_successful_login(&self, ip: IpAddr) {
+        let mut records = self.records.lock().unwrap();
+        if let Some(record) = records.get_mut(&ip) {
+            record.failed_passwords = 0;
+            record.last_failure = None;
+        }
+    }
+}
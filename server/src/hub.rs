@@ -0,0 +1,82 @@
+use protocol::{MessageKind, Peer};
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tokio::sync::mpsc::Sender;
+
+struct PeerEntry {
+    name: String,
+    outbox: Sender<MessageKind>,
+}
+
+/// 所有已建立连接的客户端, 用于在线状态的广播
+#[derive(Default)]
+pub struct Hub {
+    next_client_id: AtomicU64,
+    peers: Mutex<HashMap<u64, PeerEntry>>,
+}
+
+/// 向除 `except` 以外的所有客户端发送消息
+///
+/// 积压已满的客户端会被移除, 丢弃它的 `outbox` 后会话随即断开, 其他客户端收到 `PeerLeft`
+fn deliver(peers: &mut HashMap<u64, PeerEntry>, except: Option<u64>, kind: MessageKind) {
+    let mut pending = vec![(except, kind)];
+    while let Some((except, kind)) = pending.pop() {
+        let lagging = peers
+            .iter()
+            .filter(|(id, _)| Some(**id) != except)
+            .filter(|(_, entry)| entry.outbox.try_send(kind.clone()).is_err())
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for client_id in lagging {
+            peers.remove(&client_id);
+            pending.push((None, MessageKind::PeerLeft { client_id }));
+        }
+    }
+}
+
+impl Hub {
+    pub fn allocate_client_id(&self) -> u64 {
+        self.next_client_id.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// 登记一个刚建立连接的客户端, 返回此前已在线的其他客户端
+    pub fn join(&self, client_id: u64, name: String, outbox: Sender<MessageKind>) -> Vec<Peer> {
+        let peer = Peer {
+            client_id,
+            name: name.clone(),
+        };
+        let mut peers = self.peers.lock().unwrap();
+        deliver(&mut peers, None, MessageKind::PeerJoined { peer });
+        let mut others = peers
+            .iter()
+            .map(|(id, entry)| Peer {
+                client_id: *id,
+                name: entry.name.clone(),
+            })
+            .collect::<Vec<_>>();
+        others.sort_by_key(|peer| peer.client_id);
+
+        peers.insert(client_id, PeerEntry { name, outbox });
+        others
+    }
+
+    /// 移除一个客户端; 对没有建立连接或已经被移除的客户端不做任何事
+    pub fn leave(&self, client_id: u64) {
+        let mut peers = self.peers.lock().unwrap();
+        if peers.remove(&client_id).is_none() {
+            return;
+        }
+        deliver(&mut peers, None, MessageKind::PeerLeft { client_id });
+    }
+
+    /// 向除 `from` 以外的所有客户端发送消息
+    pub fn broadcast_from(&self, from: u64, kind: MessageKind) {
+        let mut peers = self.peers.lock().unwrap();
+        deliver(&mut peers, Some(from), kind);
+    }
+}
@@ -0,0 +1,35 @@
+use log::{error, info};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+mod admin;
+mod config;
+mod connections;
+mod frame;
+mod guard;
+mod hub;
+mod session;
+mod state;
+
+pub use config::ServerConfig;
+pub use state::ServerState;
+
+/// 在给定的监听器上接受客户端连接, 直到进程退出
+///
+/// 监听器由调用方创建, 测试可以绑定到 `127.0.0.1:0` 上的临时端口
+pub async fn run(listener: TcpListener, state: Arc<ServerState>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer_addr)) => {
+                info!("Accepted connection from {}", peer_addr);
+                tokio::spawn(session::handle_client(stream, peer_addr, state.clone()));
+            }
+            Err(err) => error!("Failed to accept connection: {}", err),
+        }
+    }
+}
+
+/// 在给定的监听器上运行管理控制台, 只接受本机地址的连接
+pub async fn run_admin(listener: TcpListener, state: Arc<ServerState>) {
+    admin::serve(listener, state).await
+}
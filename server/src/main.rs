@@ -1,57 +1,8 @@
 use colored::Colorize;
-use log::{error, info};
-use std::{
-    io::Read,
-    net::{TcpListener, TcpStream},
-};
-
-async fn handle_client(mut stream: TcpStream) {
-    'main_loop: loop {
-        let mut len_buf = [0u8; protocol::PREFIX_LENGTH_BYTES];
-        match stream.read_exact(&mut len_buf) {
-            Ok(()) => {
-                let pack_len = u32::from_be_bytes(len_buf) as usize;
-
-                let mut content = vec![0u8; pack_len];
-                if let Err(err) = stream.read_exact(&mut content) {
-                    match err.kind() {
-                        std::io::ErrorKind::UnexpectedEof => {
-                            // 对方关闭连接, 退出循环
-                            error!("Read UnexpectedEof, disconnecting...");
-                            break 'main_loop;
-                        }
-                        _ => {
-                            error!("Failed to read from client: {}, disconnecting...", err);
-                            break 'main_loop;
-                        }
-                    }
-                }
-
-                match serde_json::from_slice::<protocol::ClientRequest>(&content) {
-                    Ok(data) => {
-                        info!("Received: {:?}", data);
-                    }
-                    Err(err) => {
-                        error!(
-                            "Failed to parse request from client: {}\nContent: {}",
-                            err,
-                            unsafe { String::from_utf8_unchecked(content.clone()) }
-                        );
-                    }
-                }
-            }
-            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
-                // 对方关闭连接, 退出循环
-                error!("Read UnexpectedEof, disconnecting...");
-                break 'main_loop;
-            }
-            Err(err) => {
-                error!("Failed to read from client: {}, disconnecting...", err);
-                break 'main_loop;
-            }
-        }
-    }
-}
+use log::info;
+use server::{ServerConfig, ServerState};
+use std::sync::Arc;
+use tokio::net::TcpListener;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -73,11 +24,20 @@ async fn main() -> anyhow::Result<()> {
 
     info!("Starting Server");
 
-    let listener = TcpListener::bind("127.0.0.1:7300")?;
-
-    for stream in listener.incoming() {
-        tokio::spawn(handle_client(stream?));
+    let state = Arc::new(ServerState::new(ServerConfig::from_env()));
+    let listener = TcpListener::bind(&state.config.bind_addr).await?;
+
+    if let Some(admin_addr) = &state.config.admin_addr {
+        let admin_listener = TcpListener::bind(admin_addr).await?;
+        let local_addr = admin_listener.local_addr()?;
+        anyhow::ensure!(
+            local_addr.ip().is_loopback(),
+            "admin console must listen on a loopback address, got {local_addr}"
+        );
+        info!("Admin console listening on {}", local_addr);
+        tokio::spawn(server::run_admin(admin_listener, state.clone()));
     }
 
+    server::run(listener, state).await;
     Ok(())
 }
@@ -0,0 +1,337 @@
+use crate::{
+    connections::AuthState,
+    frame::{payload_preview, read_frame, write_frame},
+    guard::TokenBucket,
+    state::ServerState,
+};
+use log::{error, info, warn};
+use protocol::{ClientRequest, Encoding, MessageKind, ServerMessage, decode_body};
+use std::{net::SocketAddr, sync::Arc};
+use tokio::{
+    net::{TcpStream, tcp::OwnedWriteHalf},
+    sync::mpsc::{self, Sender},
+    time::{Instant, sleep_until},
+};
+
+enum SessionState {
+    /// 等待 `ConnectionRequest`
+    Connecting,
+    /// 已请求密码, 等待 `GiveYouPassword`
+    AwaitingPassword { name: String, encoding: Encoding },
+    /// 已建立连接, 在线状态对其他客户端可见
+    Established,
+}
+
+struct Session {
+    client_id: u64,
+    peer_addr: SocketAddr,
+    state: SessionState,
+    /// 当前使用的帧编码, 收发双方一致
+    encoding: Encoding,
+    writer: OwnedWriteHalf,
+    server: Arc<ServerState>,
+    /// 建立连接时交给 `Hub`, 之后 `Hub` 丢弃它就表示这个客户端接收得太慢
+    outbox: Option<Sender<MessageKind>>,
+}
+
+impl Session {
+    async fn send(&mut self, kind: MessageKind) -> std::io::Result<()> {
+        let bytes =
+            write_frame(&mut self.writer, &ServerMessage::from(kind), self.encoding).await?;
+        self.server.connections.record_out(self.client_id, bytes);
+        Ok(())
+    }
+
+    /// 告知客户端违反了协议; 调用方随后应断开连接
+    async fn protocol_error(&mut self, reason: &str) {
+        warn!(
+            "Protocol error from client {} ({}): {}",
+            self.client_id, self.peer_addr, reason
+        );
+        let _ = self
+            .send(MessageKind::ProtocolError {
+                reason: reason.to_string(),
+            })
+            .await;
+    }
+
+    async fn establish(&mut self, name: String, encoding: Encoding) -> std::io::Result<()> {
+        self.send(MessageKind::Welcome {
+            client_id: self.client_id,
+            encoding,
+        })
+        .await?;
+        self.encoding = encoding;
+        self.server
+            .connections
+            .set_auth(self.client_id, AuthState::Established, Some(&name));
+        let outbox = self.outbox.take().expect("session established twice");
+        let peers = self.server.hub.join(self.client_id, name, outbox);
+        self.state = SessionState::Established;
+        info!("Client {} ({}) joined", self.client_id, self.peer_addr);
+        self.send(MessageKind::PresenceList { peers }).await
+    }
+
+    /// 处理一条客户端消息, 返回 `false` 表示应当断开连接
+    async fn handle(&mut self, kind: MessageKind) -> std::io::Result<bool> {
+        match (&self.state, kind) {
+            (_, MessageKind::Ping { nonce }) => self.send(MessageKind::Pong { nonce }).await?,
+            (_, MessageKind::Pong { .. }) => {}
+            (SessionState::Connecting, MessageKind::ConnectionRequest { name, encodings }) => {
+                // 服务器支持所有编码, 直接采用客户端最偏好的一个
+                let encoding = encodings.first().copied().unwrap_or_default();
+                if self.server.config.password.is_some() {
+                    self.server.connections.set_auth(
+                        self.client_id,
+                        AuthState::AwaitingPassword,
+                        Some(&name),
+                    );
+                    self.state = SessionState::AwaitingPassword { name, encoding };
+                    self.send(MessageKind::PasswordRequest).await?;
+                } else {
+                    self.establish(name, encoding).await?;
+                }
+            }
+            (
+                SessionState::AwaitingPassword { name, encoding },
+                MessageKind::GiveYouPassword { password },
+            ) => {
+                if self.server.config.password.as_deref() == Some(password.as_str()) {
+                    let (name, encoding) = (name.clone(), *encoding);
+                    self.server
+                        .guard
+                        .record_successful_login(self.peer_addr.ip());
+                    self.establish(name, encoding).await?;
+                } else {
+                    let reason = match self
+                        .server
+                        .guard
+                        .record_failed_password(self.peer_addr.ip(), &self.server.config)
+                    {
+                        Some(ban) => {
+                            warn!(
+                                "Banning {} for {} seconds after repeated wrong passwords",
+                                self.peer_addr.ip(),
+                                ban.as_secs()
+                            );
+                            format!("密码错误次数过多, 请在 {} 秒后重试", ban.as_secs())
+                        }
+                        None => "密码错误".to_string(),
+                    };
+                    self.send(MessageKind::ConnectRefuse { reason }).await?;
+                    return Ok(false);
+                }
+            }
+            (SessionState::Established, MessageKind::Typing { phase }) => {
+                self.server.hub.broadcast_from(
+                    self.client_id,
+                    MessageKind::PeerTyping {
+                        client_id: self.client_id,
+                        phase,
+                    },
+                );
+            }
+            (SessionState::Established, MessageKind::Blob { chunk }) => {
+                self.server.hub.broadcast_from(
+                    self.client_id,
+                    MessageKind::PeerBlob {
+                        client_id: self.client_id,
+                        chunk,
+                    },
+                );
+            }
+            (_, kind) => {
+                warn!(
+                    "Unexpected message from client {}: {:?}",
+                    self.client_id, kind
+                );
+            }
+        }
+        Ok(true)
+    }
+}
+
+pub async fn handle_client(mut stream: TcpStream, peer_addr: SocketAddr, server: Arc<ServerState>) {
+    if let Err(reason) = server.guard.admit(peer_addr.ip(), &server.config) {
+        info!("Refusing connection from {}: {}", peer_addr, reason);
+        let _ = write_frame(
+            &mut stream,
+            &ServerMessage::from(MessageKind::ConnectRefuse { reason }),
+            Encoding::Json,
+        )
+        .await;
+        return;
+    }
+
+    let (mut reader, writer) = stream.into_split();
+
+    // 读取放在单独的任务里, 这样等待心跳超时的时候不会丢掉读了一半的帧
+    let (frame_tx, mut frames) = mpsc::channel::<std::io::Result<Vec<u8>>>(16);
+    let reader_task = tokio::spawn(async move {
+        loop {
+            match read_frame(&mut reader).await {
+                Ok(content) => {
+                    if frame_tx.send(Ok(content)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    // 对方关闭连接, 退出循环
+                    error!("Read UnexpectedEof, disconnecting...");
+                    break;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                    // 帧过大, 交给会话告知客户端
+                    let _ = frame_tx.send(Err(e)).await;
+                    break;
+                }
+                Err(err) => {
+                    error!("Failed to read from client: {}, disconnecting...", err);
+                    break;
+                }
+            }
+        }
+    });
+
+    let client_id = server.hub.allocate_client_id();
+    let (kick_tx, mut kicks) = mpsc::unbounded_channel::<String>();
+    server.connections.register(client_id, peer_addr, kick_tx);
+
+    let (outbox_tx, mut outbox) = mpsc::channel(server.config.outbox_capacity);
+    let mut session = Session {
+        client_id,
+        peer_addr,
+        state: SessionState::Connecting,
+        encoding: Encoding::Json,
+        writer,
+        server: server.clone(),
+        outbox: Some(outbox_tx),
+    };
+    let config = &server.config;
+
+    let mut last_seen = Instant::now();
+    let mut awaiting_pong = false;
+    let mut next_nonce = 0u64;
+    let mut frame_bucket = TokenBucket::new(config.frame_burst, config.frames_per_sec);
+    let mut blob_bucket = TokenBucket::new(config.blob_burst_bytes, config.blob_bytes_per_sec);
+    let mut malformed_frames = 0u32;
+
+    'main_loop: loop {
+        let deadline = if awaiting_pong {
+            last_seen + config.idle_timeout
+        } else {
+            last_seen + config.heartbeat_interval
+        };
+
+        let result = tokio::select! {
+            frame = frames.recv() => {
+                let content = match frame {
+                    None => break 'main_loop,
+                    Some(Ok(content)) => content,
+                    Some(Err(err)) => {
+                        session.protocol_error(&format!("帧过大: {err}")).await;
+                        break 'main_loop;
+                    }
+                };
+                last_seen = Instant::now();
+                awaiting_pong = false;
+                server
+                    .connections
+                    .record_in(client_id, protocol::PREFIX_LENGTH_BYTES + content.len());
+
+                match decode_body::<ClientRequest>(&content, session.encoding) {
+                    Ok(data) => {
+                        // 图片按字节数限速, 一张大图的分块不会用光消息的帧数
+                        let allowed = match &data.kind {
+                            MessageKind::Blob { chunk } => {
+                                blob_bucket.try_take_many(chunk.data.len() as u32)
+                            }
+                            _ => frame_bucket.try_take(),
+                        };
+                        if !allowed {
+                            session.protocol_error("发送过于频繁").await;
+                            break 'main_loop;
+                        }
+                        if let MessageKind::Blob { chunk } = &data.kind {
+                            // 分块内容太长, 不写进日志
+                            info!(
+                                "Received blob {} chunk at offset {} ({} bytes)",
+                                chunk.blob_id,
+                                chunk.offset,
+                                chunk.data.len()
+                            );
+                        } else {
+                            info!("Received: {:?}", data);
+                        }
+                        match session.handle(data.kind).await {
+                            Ok(true) => Ok(()),
+                            Ok(false) => break 'main_loop,
+                            Err(err) => Err(err),
+                        }
+                    }
+                    Err(err) => {
+                        if !frame_bucket.try_take() {
+                            session.protocol_error("发送过于频繁").await;
+                            break 'main_loop;
+                        }
+                        error!(
+                            "Failed to parse request from client: {}\nContent: {}",
+                            err,
+                            payload_preview(&content)
+                        );
+                        malformed_frames += 1;
+                        if malformed_frames >= config.max_malformed_frames {
+                            session.protocol_error("无法解析的帧过多").await;
+                            break 'main_loop;
+                        }
+                        Ok(())
+                    }
+                }
+            }
+            kind = outbox.recv() => match kind {
+                Some(kind) => session.send(kind).await,
+                None => {
+                    info!("Client {} fell behind, disconnecting...", client_id);
+                    let _ = session
+                        .send(MessageKind::Disconnect {
+                            reason: "接收消息过慢".to_string(),
+                        })
+                        .await;
+                    break 'main_loop;
+                }
+            },
+            Some(reason) = kicks.recv() => {
+                info!("Client {} kicked by admin: {}", client_id, reason);
+                let _ = session.send(MessageKind::Disconnect { reason }).await;
+                break 'main_loop;
+            }
+            _ = sleep_until(deadline) => {
+                if awaiting_pong {
+                    info!("Client {} idle for too long, disconnecting...", session.client_id);
+                    let _ = session
+                        .send(MessageKind::Disconnect {
+                            reason: "空闲超时".to_string(),
+                        })
+                        .await;
+                    break 'main_loop;
+                }
+                awaiting_pong = true;
+                next_nonce += 1;
+                session.send(MessageKind::Ping { nonce: next_nonce }).await
+            }
+        };
+
+        if let Err(err) = result {
+            error!("Failed to write to client: {}, disconnecting...", err);
+            break 'main_loop;
+        }
+    }
+
+    if matches!(session.state, SessionState::Established) {
+        info!("Client {} ({}) left", session.client_id, peer_addr);
+    }
+    server.hub.leave(session.client_id);
+    server.connections.unregister(client_id);
+    server.guard.release(peer_addr.ip());
+    reader_task.abort();
+}
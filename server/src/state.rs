@@ -0,0 +1,20 @@
+use crate::{config::ServerConfig, connections::Connections, guard::Guard, hub::Hub};
+
+/// 所有连接共享的服务器状态
+pub struct ServerState {
+    pub config: ServerConfig,
+    pub hub: Hub,
+    pub guard: Guard,
+    pub connections: Connections,
+}
+
+impl ServerState {
+    pub fn new(config: ServerConfig) -> Self {
+        Self {
+            config,
+            hub: Hub::default(),
+            guard: Guard::default(),
+            connections: Connections::default(),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! 集成测试共用的工具: 在临时端口上启动服务器, 以及按脚本收发消息的客户端
+
+#![allow(dead_code)]
+
+use protocol::{
+    ClientRequest, Encoding, MessageKind, PREFIX_LENGTH_BYTES, Peer, ServerMessage, decode_body,
+    encode_frame,
+};
+use server::{ServerConfig, ServerState};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// 等待一条消息的最长时间, 超时视为测试失败
+const RECV_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct TestServer {
+    pub addr: SocketAddr,
+    pub state: Arc<ServerState>,
+}
+
+/// 在 `127.0.0.1` 的临时端口上启动服务器, 服务器随测试的运行时一起结束
+pub async fn start_server(config: ServerConfig) -> TestServer {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let state = Arc::new(ServerState::new(config));
+    tokio::spawn(server::run(listener, state.clone()));
+    TestServer { addr, state }
+}
+
+/// 在临时端口上启动管理控制台, 返回其地址
+pub async fn start_admin(server: &TestServer) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(server::run_admin(listener, server.state.clone()));
+    addr
+}
+
+pub struct TestClient {
+    stream: TcpStream,
+    /// 当前使用的帧编码, 收到 `Welcome` 后切换
+    pub encoding: Encoding,
+}
+
+impl TestClient {
+    pub async fn connect(server: &TestServer) -> Self {
+        Self {
+            stream: TcpStream::connect(server.addr).await.unwrap(),
+            encoding: Encoding::Json,
+        }
+    }
+
+    /// 完成不需要密码的握手, 返回分配到的编号和已在线的其他客户端
+    pub async fn join(
+        server: &TestServer,
+        name: &str,
+        encodings: Vec<Encoding>,
+    ) -> (Self, u64, Vec<Peer>) {
+        let mut client = Self::connect(server).await;
+        client
+            .send(MessageKind::ConnectionRequest {
+                name: name.to_string(),
+                encodings,
+            })
+            .await;
+        let client_id = client.expect_welcome().await;
+        let peers = match client.recv().await {
+            MessageKind::PresenceList { peers } => peers,
+            other => panic!("expected PresenceList, got {other:?}"),
+        };
+        (client, client_id, peers)
+    }
+
+    pub async fn send(&mut self, kind: MessageKind) {
+        let frame = encode_frame(&ClientRequest::from(kind), self.encoding).unwrap();
+        self.stream.write_all(&frame).await.unwrap();
+    }
+
+    /// 发送原始的帧内容, 用于构造无法解析的帧
+    pub async fn send_raw(&mut self, body: &[u8]) {
+        self.send_prefix(body.len() as u32).await;
+        self.stream.write_all(body).await.unwrap();
+    }
+
+    /// 只发送长度前缀, 用于构造超长的帧
+    pub async fn send_prefix(&mut self, len: u32) {
+        self.stream.write_all(&len.to_be_bytes()).await.unwrap();
+    }
+
+    pub async fn recv(&mut self) -> MessageKind {
+        tokio::time::timeout(RECV_TIMEOUT, self.try_recv())
+            .await
+            .expect("timed out waiting for a message")
+            .expect("connection closed while waiting for a message")
+    }
+
+    /// 读取下一条消息, 连接关闭时返回 `None`
+    pub async fn try_recv(&mut self) -> Option<MessageKind> {
+        let mut len_buf = [0u8; PREFIX_LENGTH_BYTES];
+        self.stream.read_exact(&mut len_buf).await.ok()?;
+        let mut body = vec![0u8; u32::from_be_bytes(len_buf) as usize];
+        self.stream.read_exact(&mut body).await.ok()?;
+        let message: ServerMessage = decode_body(&body, self.encoding).unwrap();
+        Some(message.kind)
+    }
+
+    pub async fn expect_welcome(&mut self) -> u64 {
+        match self.recv().await {
+            MessageKind::Welcome {
+                client_id,
+                encoding,
+            } => {
+                self.encoding = encoding;
+                client_id
+            }
+            other => panic!("expected Welcome, got {other:?}"),
+        }
+    }
+
+    /// 断言服务器随后关闭了连接
+    pub async fn expect_closed(&mut self) {
+        let next = tokio::time::timeout(RECV_TIMEOUT, self.try_recv())
+            .await
+            .expect("timed out waiting for the connection to close");
+        assert_eq!(next, None);
+    }
+}
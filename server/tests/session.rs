@@ -0,0 +1,393 @@
+mod common;
+
+use common::{TestClient, start_admin, start_server};
+use protocol::{
+    BlobAssembler, Encoding, MAX_FRAME_BYTES, MessageKind, Peer, TypingPhase, split_blob,
+};
+use server::ServerConfig;
+use std::time::Duration;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+#[tokio::test]
+async fn test_handshake_without_password() {
+    let server = start_server(ServerConfig::default()).await;
+    let (mut client, client_id, peers) = TestClient::join(&server, "佩丽卡", vec![]).await;
+    assert_eq!(client_id, 1);
+    assert!(peers.is_empty());
+    assert_eq!(client.encoding, Encoding::Json);
+
+    client.send(MessageKind::Ping { nonce: 42 }).await;
+    assert_eq!(client.recv().await, MessageKind::Pong { nonce: 42 });
+}
+
+#[tokio::test]
+async fn test_presence_and_typing() {
+    let server = start_server(ServerConfig::default()).await;
+    let (mut alice, alice_id, _) = TestClient::join(&server, "alice", vec![]).await;
+    let (mut bob, bob_id, peers) = TestClient::join(&server, "bob", vec![]).await;
+    assert_eq!(
+        peers,
+        vec![Peer {
+            client_id: alice_id,
+            name: "alice".to_string(),
+        }]
+    );
+    assert_eq!(
+        alice.recv().await,
+        MessageKind::PeerJoined {
+            peer: Peer {
+                client_id: bob_id,
+                name: "bob".to_string(),
+            }
+        }
+    );
+
+    bob.send(MessageKind::Typing {
+        phase: TypingPhase::Typing,
+    })
+    .await;
+    assert_eq!(
+        alice.recv().await,
+        MessageKind::PeerTyping {
+            client_id: bob_id,
+            phase: TypingPhase::Typing,
+        }
+    );
+
+    drop(bob);
+    assert_eq!(
+        alice.recv().await,
+        MessageKind::PeerLeft { client_id: bob_id }
+    );
+}
+
+#[tokio::test]
+async fn test_password_handshake() {
+    let server = start_server(ServerConfig {
+        password: Some("hunter2".to_string()),
+        ..Default::default()
+    })
+    .await;
+
+    let mut wrong = TestClient::connect(&server).await;
+    wrong
+        .send(MessageKind::ConnectionRequest {
+            name: "mallory".to_string(),
+            encodings: vec![],
+        })
+        .await;
+    assert_eq!(wrong.recv().await, MessageKind::PasswordRequest);
+    wrong
+        .send(MessageKind::GiveYouPassword {
+            password: "guess".to_string(),
+        })
+        .await;
+    assert_eq!(
+        wrong.recv().await,
+        MessageKind::ConnectRefuse {
+            reason: "密码错误".to_string(),
+        }
+    );
+    wrong.expect_closed().await;
+
+    let mut right = TestClient::connect(&server).await;
+    right
+        .send(MessageKind::ConnectionRequest {
+            name: "alice".to_string(),
+            encodings: vec![Encoding::MessagePack],
+        })
+        .await;
+    assert_eq!(right.recv().await, MessageKind::PasswordRequest);
+    right
+        .send(MessageKind::GiveYouPassword {
+            password: "hunter2".to_string(),
+        })
+        .await;
+    right.expect_welcome().await;
+    assert_eq!(right.encoding, Encoding::MessagePack);
+    assert_eq!(
+        right.recv().await,
+        MessageKind::PresenceList { peers: vec![] }
+    );
+}
+
+#[tokio::test]
+async fn test_repeated_wrong_passwords_ban_the_address() {
+    let server = start_server(ServerConfig {
+        password: Some("hunter2".to_string()),
+        max_failed_passwords: 2,
+        ..Default::default()
+    })
+    .await;
+
+    let mut refusals = vec![];
+    for _ in 0..2 {
+        let mut client = TestClient::connect(&server).await;
+        client
+            .send(MessageKind::ConnectionRequest {
+                name: "mallory".to_string(),
+                encodings: vec![],
+            })
+            .await;
+        assert_eq!(client.recv().await, MessageKind::PasswordRequest);
+        client
+            .send(MessageKind::GiveYouPassword {
+                password: "guess".to_string(),
+            })
+            .await;
+        refusals.push(client.recv().await);
+        client.expect_closed().await;
+    }
+    assert_eq!(
+        refusals[0],
+        MessageKind::ConnectRefuse {
+            reason: "密码错误".to_string(),
+        }
+    );
+    assert!(
+        matches!(&refusals[1], MessageKind::ConnectRefuse { reason } if reason.starts_with("密码错误次数过多"))
+    );
+
+    // 封禁期间的新连接在握手前就被拒绝
+    let mut banned = TestClient::connect(&server).await;
+    assert!(
+        matches!(banned.recv().await, MessageKind::ConnectRefuse { reason } if reason.starts_with("密码错误次数过多"))
+    );
+    banned.expect_closed().await;
+}
+
+#[tokio::test]
+async fn test_connections_per_address_are_limited() {
+    let server = start_server(ServerConfig {
+        max_connections_per_ip: 1,
+        ..Default::default()
+    })
+    .await;
+    let (_first, _, _) = TestClient::join(&server, "alice", vec![]).await;
+
+    let mut second = TestClient::connect(&server).await;
+    assert_eq!(
+        second.recv().await,
+        MessageKind::ConnectRefuse {
+            reason: "来自同一地址的连接过多".to_string(),
+        }
+    );
+    second.expect_closed().await;
+}
+
+#[tokio::test]
+async fn test_heartbeat_and_idle_timeout() {
+    let server = start_server(ServerConfig {
+        heartbeat_interval: Duration::from_millis(100),
+        idle_timeout: Duration::from_millis(300),
+        ..Default::default()
+    })
+    .await;
+    let (mut client, _, _) = TestClient::join(&server, "alice", vec![]).await;
+
+    assert_eq!(client.recv().await, MessageKind::Ping { nonce: 1 });
+    client.send(MessageKind::Pong { nonce: 1 }).await;
+    // 回复之后重新计时, 不回复则断开
+    assert_eq!(client.recv().await, MessageKind::Ping { nonce: 2 });
+    assert_eq!(
+        client.recv().await,
+        MessageKind::Disconnect {
+            reason: "空闲超时".to_string(),
+        }
+    );
+    client.expect_closed().await;
+}
+
+#[tokio::test]
+async fn test_frame_rate_limit() {
+    let server = start_server(ServerConfig {
+        frame_burst: 3,
+        frames_per_sec: 0.001,
+        ..Default::default()
+    })
+    .await;
+    let (mut client, _, _) = TestClient::join(&server, "alice", vec![]).await;
+
+    for nonce in 1..=2 {
+        client.send(MessageKind::Ping { nonce }).await;
+        assert_eq!(client.recv().await, MessageKind::Pong { nonce });
+    }
+    client.send(MessageKind::Ping { nonce: 3 }).await;
+    assert_eq!(
+        client.recv().await,
+        MessageKind::ProtocolError {
+            reason: "发送过于频繁".to_string(),
+        }
+    );
+    client.expect_closed().await;
+}
+
+#[tokio::test]
+async fn test_malformed_and_oversized_frames() {
+    let server = start_server(ServerConfig {
+        max_malformed_frames: 2,
+        ..Default::default()
+    })
+    .await;
+
+    let mut client = TestClient::connect(&server).await;
+    client.send_raw(b"not json").await;
+    // 第一个无法解析的帧只会被忽略
+    client.send(MessageKind::Ping { nonce: 1 }).await;
+    assert_eq!(client.recv().await, MessageKind::Pong { nonce: 1 });
+    client.send_raw(b"\xff\xfe").await;
+    assert_eq!(
+        client.recv().await,
+        MessageKind::ProtocolError {
+            reason: "无法解析的帧过多".to_string(),
+        }
+    );
+    client.expect_closed().await;
+
+    let mut client = TestClient::connect(&server).await;
+    client.send_prefix(MAX_FRAME_BYTES as u32 + 1).await;
+    assert!(
+        matches!(client.recv().await, MessageKind::ProtocolError { reason } if reason.starts_with("帧过大"))
+    );
+    client.expect_closed().await;
+}
+
+#[tokio::test]
+async fn test_blob_relay_across_encodings() {
+    let server = start_server(ServerConfig::default()).await;
+    let (mut sender, sender_id, _) =
+        TestClient::join(&server, "alice", vec![Encoding::MessagePack]).await;
+    let (mut receiver, _, _) = TestClient::join(&server, "bob", vec![]).await;
+    assert_eq!(sender.encoding, Encoding::MessagePack);
+    assert!(matches!(
+        sender.recv().await,
+        MessageKind::PeerJoined { .. }
+    ));
+
+    // 手机拍摄的照片通常有几 MB, 分块数远多于消息的突发帧数
+    let data: Vec<u8> = (0..8 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let chunks = split_blob(1, "image/png", &data);
+    assert!(chunks.len() > ServerConfig::default().frame_burst as usize);
+    let sending = tokio::spawn(async move {
+        for chunk in chunks {
+            sender.send(MessageKind::Blob { chunk }).await;
+        }
+        sender
+    });
+
+    let mut assembler = BlobAssembler::default();
+    let blob = loop {
+        match receiver.recv().await {
+            MessageKind::PeerBlob { client_id, chunk } => {
+                assert_eq!(client_id, sender_id);
+                if let Some(blob) = assembler.push(chunk).unwrap() {
+                    break blob;
+                }
+            }
+            other => panic!("expected PeerBlob, got {other:?}"),
+        }
+    };
+    assert_eq!(blob.mime, "image/png");
+    assert_eq!(blob.data, data);
+
+    // 发完之后普通消息仍然可以发送
+    let mut sender = sending.await.unwrap();
+    sender.send(MessageKind::Ping { nonce: 1 }).await;
+    assert_eq!(sender.recv().await, MessageKind::Pong { nonce: 1 });
+}
+
+#[tokio::test]
+async fn test_blob_byte_rate_limit() {
+    let server = start_server(ServerConfig {
+        blob_burst_bytes: 200_000,
+        blob_bytes_per_sec: 1.0,
+        ..Default::default()
+    })
+    .await;
+    let (mut client, _, _) = TestClient::join(&server, "alice", vec![]).await;
+
+    let data = vec![0u8; 300_000];
+    for chunk in split_blob(1, "image/png", &data) {
+        client.send(MessageKind::Blob { chunk }).await;
+    }
+    assert_eq!(
+        client.recv().await,
+        MessageKind::ProtocolError {
+            reason: "发送过于频繁".to_string(),
+        }
+    );
+    client.expect_closed().await;
+}
+
+#[tokio::test]
+async fn test_slow_reader_is_disconnected() {
+    let server = start_server(ServerConfig {
+        outbox_capacity: 8,
+        ..Default::default()
+    })
+    .await;
+    let (mut sender, _, _) = TestClient::join(&server, "alice", vec![]).await;
+    let (mut slow, slow_id, _) = TestClient::join(&server, "bob", vec![]).await;
+    assert!(matches!(
+        sender.recv().await,
+        MessageKind::PeerJoined { .. }
+    ));
+
+    // bob 不读取, 数据先填满 TCP 缓冲区, 再填满服务器上的积压
+    let data = vec![7u8; 24 * 1024 * 1024];
+    for chunk in split_blob(1, "image/png", &data) {
+        sender.send(MessageKind::Blob { chunk }).await;
+    }
+    assert_eq!(
+        sender.recv().await,
+        MessageKind::PeerLeft { client_id: slow_id }
+    );
+
+    // 积压的数据之后是断开的理由
+    let mut last = None;
+    while let Some(kind) = slow.try_recv().await {
+        last = Some(kind);
+    }
+    assert_eq!(
+        last,
+        Some(MessageKind::Disconnect {
+            reason: "接收消息过慢".to_string(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_admin_lists_and_kicks_clients() {
+    let server = start_server(ServerConfig::default()).await;
+    let admin_addr = start_admin(&server).await;
+    let (mut client, client_id, _) = TestClient::join(&server, "alice", vec![]).await;
+
+    let (reader, mut writer) = TcpStream::connect(admin_addr).await.unwrap().into_split();
+    let mut lines = BufReader::new(reader).lines();
+    lines.next_line().await.unwrap().unwrap();
+
+    writer.write_all(b"clients\n").await.unwrap();
+    lines.next_line().await.unwrap().unwrap();
+    let row = lines.next_line().await.unwrap().unwrap();
+    assert!(row.contains("established"));
+    assert!(row.ends_with("alice"));
+
+    writer
+        .write_all(format!("kick {client_id} 维护中\n").as_bytes())
+        .await
+        .unwrap();
+    assert_eq!(
+        lines.next_line().await.unwrap().unwrap(),
+        format!("kicked client {client_id}")
+    );
+    assert_eq!(
+        client.recv().await,
+        MessageKind::Disconnect {
+            reason: "维护中".to_string(),
+        }
+    );
+    client.expect_closed().await;
+}
@@ -0,0 +1,478 @@
+use dioxus::prelude::*;
+use std::collections::HashSet;
+
+use crate::components::baker::capture::capture_pages;
+use crate::components::baker::capture_pages::{
+    CaptureFormat, DEFAULT_QUALITY, capture_filename, encode_capture, unique_filenames, zip_store,
+};
+use crate::components::baker::chat_area::ChatArea;
+use crate::components::baker::chat_render::contact_display_name;
+use crate::components::baker::save::{SaveOutcome, save_files};
+use crate::components::baker::storage::v2::{AppState, Contact, Message};
+use crate::components::baker::{Route, background_style, bytes_from_data_url, modals::Modal};
+use anyhow::Context;
+
+/// 批量导出时压缩包的文件名模板
+const ARCHIVE_TEMPLATE: &str = "baker-{date}";
+
+///
+/// 批量导出的进度。
+///
+#[derive(Clone, Debug, PartialEq)]
+struct BatchProgress {
+    /// 已经处理的会话数
+    done: usize,
+    total: usize,
+    /// 正在截图的会话
+    name: String,
+}
+
+///
+/// 一次截取多个会话并保存为一个压缩包或一组图片的页面。
+///
+#[component]
+pub(super) fn BatchCapturePage() -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let navigator = navigator();
+
+    let contacts = use_memo(move || app_state.read().contacts.clone());
+    let operators = use_signal(move || app_state.read().operators.clone());
+    let stickers = use_memo(move || app_state.read().stickers.clone());
+    let user_profile = app_state.read().user_profile.clone();
+    let menu_close_token = use_signal(|| 0usize);
+    let need_to_scroll_down = use_signal(|| false);
+
+    let mut selected = use_signal(|| {
+        app_state
+            .read()
+            .contacts
+            .iter()
+            .map(|contact| contact.id.clone())
+            .collect::<HashSet<_>>()
+    });
+    let mut width = use_signal(|| 800i64);
+    let mut scale = use_signal(|| 1.0f64);
+    let mut export_format = use_signal(|| CaptureFormat::Png);
+    let mut quality = use_signal(|| DEFAULT_QUALITY);
+    let mut as_archive = use_signal(|| true);
+
+    // 离屏聊天区域正在显示的会话
+    let mut current = use_signal(|| None::<Contact>);
+    let visible = use_signal(Vec::<Message>::new);
+    let mut progress = use_signal(|| None::<BatchProgress>);
+    let mut run_token = use_signal(|| 0usize);
+    let mut failures = use_signal(Vec::<(String, String)>::new);
+    let mut success_message = use_signal(|| None::<String>);
+    let mut save_error = use_signal(|| None::<String>);
+
+    let start_export = move |_| {
+        if progress().is_some() {
+            return;
+        }
+        let targets = contacts
+            .read()
+            .iter()
+            .filter(|contact| selected.read().contains(&contact.id))
+            .cloned()
+            .collect::<Vec<_>>();
+        if targets.is_empty() {
+            return;
+        }
+        let scale = scale();
+        let format = export_format();
+        let quality = quality();
+        let as_archive = as_archive();
+        let token = *run_token.peek() + 1;
+        run_token.set(token);
+        failures.set(Vec::new());
+
+        spawn(async move {
+            let is_current = move || *run_token.peek() == token;
+            let now = chrono::Local::now().naive_local();
+            let total = targets.len();
+            let mut names = Vec::with_capacity(total);
+            let mut images = Vec::with_capacity(total);
+            for (index, contact) in targets.into_iter().enumerate() {
+                if !is_current() {
+                    break;
+                }
+                let name = contact_display_name(&contact, &operators.read());
+                progress.set(Some(BatchProgress {
+                    done: index,
+                    total,
+                    name: name.clone(),
+                }));
+                let messages = app_state
+                    .read()
+                    .messages
+                    .get(&contact.id)
+                    .cloned()
+                    .unwrap_or_default();
+                if messages.is_empty() {
+                    failures
+                        .write()
+                        .push((name, "没有消息，已跳过".to_string()));
+                    continue;
+                }
+
+                current.set(Some(contact));
+                let result: anyhow::Result<Vec<u8>> = async {
+                    let srcs = capture_pages(messages, visible, scale, None, is_current).await?;
+                    let src = srcs.first().context("没有得到截图")?;
+                    let png = bytes_from_data_url(src).context("无法读取截图")?;
+                    encode_capture(&png, format, quality)
+                }
+                .await;
+                match result {
+                    Ok(bytes) => {
+                        names.push(capture_filename(
+                            "{name}",
+                            &name,
+                            now,
+                            None,
+                            format.extension(),
+                        ));
+                        images.push(bytes);
+                    }
+                    Err(err) => {
+                        error!("batch capture {name} failed: {err:?}");
+                        failures.write().push((name, err.to_string()));
+                    }
+                }
+            }
+            current.set(None);
+            progress.set(None);
+            if !is_current() || images.is_empty() {
+                return;
+            }
+
+            let files = unique_filenames(names)
+                .into_iter()
+                .zip(images)
+                .collect::<Vec<_>>();
+            let result = if as_archive {
+                let filename = capture_filename(ARCHIVE_TEMPLATE, "", now, None, "zip");
+                match zip_store(&files) {
+                    Ok(zip) => save_files(vec![(filename, zip)], "application/zip").await,
+                    Err(err) => Err(err),
+                }
+            } else {
+                save_files(files, format.mime()).await
+            };
+            match result {
+                Ok(outcome) => {
+                    if outcome == SaveOutcome::Cancelled {
+                        return;
+                    }
+                    let saved = total - failures.peek().len();
+                    let message = outcome.message().unwrap_or_default();
+                    success_message.set(Some(format!("已导出 {saved} 个会话。{message}")));
+                }
+                Err(err) => {
+                    error!("save batch capture failed: {err:?}");
+                    save_error.set(Some(err.to_string()));
+                }
+            }
+        });
+    };
+
+    let cancel_export = move |_| {
+        let next = *run_token.peek() + 1;
+        run_token.set(next);
+    };
+
+    let frame_style = background_style(&app_state.read().background);
+    let exporting = progress().is_some();
+    let selected_count = selected.read().len();
+    let contact_rows = contacts
+        .read()
+        .iter()
+        .map(|contact| {
+            let count = app_state
+                .read()
+                .messages
+                .get(&contact.id)
+                .map(Vec::len)
+                .unwrap_or_default();
+            (
+                contact.id.clone(),
+                contact_display_name(contact, &operators.read()),
+                count,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let input_class = "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30 resize-none";
+
+    rsx! {
+        if let Some(message) = success_message() {
+            Modal {
+                title: "操作成功",
+                content_confirmation_button: "好",
+                on_close: move |_| success_message.set(None),
+                on_confirm: move |_| success_message.set(None),
+
+                {
+                    rsx! {
+                        p { class: "text-black break-all", "{message}" }
+                    }
+                }
+            }
+        }
+        if let Some(err) = save_error() {
+            Modal {
+                title: "导出失败",
+                content_confirmation_button: "好",
+                on_close: move |_| save_error.set(None),
+                on_confirm: move |_| save_error.set(None),
+
+                {
+                    rsx! {
+                        p { class: "text-black break-all", "{err}" }
+                    }
+                }
+            }
+        }
+        div {
+            class: "h-auto",
+            style: "transform: translateX(-325000px) translateY(-325000px); overflow-y: hidden",
+            position: "absolute",
+            width: "{width}px",
+            div { id: "capture_frame", style: "{frame_style}",
+                for contact in current() {
+                    ChatArea {
+                        key: "{contact.id}",
+                        contact,
+                        operators,
+                        messages: visible,
+                        user_profile: user_profile.clone(),
+                        menu_close_token,
+                        first_prev_sender_id: None,
+                        force_first_avatar: true,
+                        pending_typing: None,
+                        need_to_scroll_down,
+                        on_send_message: move |_| {},
+                        on_send_other_message: move |_| {},
+                        on_send_status: move |_| {},
+                        on_send_image: move |_| {},
+                        on_send_sticker: move |_| {},
+                        on_send_sticker_other: move |_| {},
+                        on_send_mission: move |_| {},
+                        stickers,
+                        on_add_sticker: move |_| {},
+                        on_delete_message: move |_| {},
+                        on_edit_message: move |_| {},
+                        on_edit_mission: move |_| {},
+                        on_add_reaction: move |_| {},
+                        on_delete_reaction: move |_| {},
+                        on_insert_message: move |_| {},
+                        on_edit_replay_overrides: move |_| {},
+                        on_start_replay: move |_| {},
+                        on_update_chat_head_style: move |_| {},
+                        on_clear_messages: move |_| {},
+                        on_clear_chat: move |_| {},
+                        on_set_group_ops_list: move |_| {},
+                        on_send_image_other: move |_| {},
+                        is_replaying: false,
+                        hide_input_bar: false,
+                        hide_header: false,
+                        on_exit_replay: move |_| {},
+                    }
+                }
+            }
+        }
+        div {
+            width: "100%",
+            height: "100vh",
+            display: "flex",
+            flex_direction: "column",
+            position: "relative",
+            overflow: "hidden",
+            div { class: "h-14 shrink-0 flex items-center gap-3 px-6 border-b border-gray-600 bg-[#1f1f1f]/80 backdrop-blur-sm",
+                button {
+                    class: "text-gray-300 hover:text-white text-lg px-2 py-1 rounded-lg hover:bg-white/5 transition-colors",
+                    onclick: move |_| {
+                        navigator.push(Route::BakerLayout {});
+                    },
+                    "←"
+                }
+                h1 { class: "text-white text-lg font-bold", "批量导出截图" }
+            }
+            div {
+                width: "100%",
+                flex: "1",
+                display: "flex",
+                gap: "16px",
+                padding: "16px",
+                box_sizing: "border-box",
+                overflow_x: "hidden",
+                overflow_y: "auto",
+                div {
+                    flex: "1 1 auto",
+                    min_width: "200px",
+                    class: "space-y-4",
+                    div { class: "space-y-1",
+                        label { class: "block text-white text-sm", "宽度" }
+                        input {
+                            class: input_class,
+                            r#type: "number",
+                            min: "200",
+                            step: "100",
+                            value: "{width}",
+                            disabled: exporting,
+                            oninput: move |e| width.set(e.value().parse().unwrap_or(0)),
+                        }
+                    }
+                    div { class: "space-y-1",
+                        label { class: "block text-white text-sm", "缩放倍率" }
+                        input {
+                            class: input_class,
+                            r#type: "number",
+                            min: "0.1",
+                            max: "4.0",
+                            step: "0.1",
+                            value: "{scale}",
+                            disabled: exporting,
+                            oninput: move |e| {
+                                if let Ok(num) = e.value().parse::<f64>() {
+                                    scale.set(num.clamp(0.1, 4.0));
+                                }
+                            },
+                        }
+                    }
+                    div { class: "space-y-1",
+                        label { class: "block text-white text-sm", "格式" }
+                        select {
+                            class: input_class,
+                            disabled: exporting,
+                            onchange: move |e| {
+                                if let Some(format) = CaptureFormat::ALL
+                                    .into_iter()
+                                    .find(|format| format.extension() == e.value())
+                                {
+                                    export_format.set(format);
+                                }
+                            },
+                            for format in CaptureFormat::ALL {
+                                option {
+                                    selected: export_format() == format,
+                                    value: format.extension(),
+                                    {format.label()}
+                                }
+                            }
+                        }
+                    }
+                    if export_format().has_quality() {
+                        div { class: "space-y-1",
+                            label { class: "block text-white text-sm", "质量（1 到 100）" }
+                            input {
+                                class: input_class,
+                                r#type: "number",
+                                min: "1",
+                                max: "100",
+                                value: "{quality}",
+                                disabled: exporting,
+                                oninput: move |e| {
+                                    if let Ok(num) = e.value().parse::<u8>() {
+                                        quality.set(num.clamp(1, 100));
+                                    }
+                                },
+                            }
+                        }
+                    }
+                    label { class: "flex items-center gap-2 text-white text-sm cursor-pointer",
+                        input {
+                            r#type: "checkbox",
+                            class: "w-4 h-4 accent-black cursor-pointer",
+                            checked: as_archive(),
+                            disabled: exporting,
+                            onchange: move |e| as_archive.set(e.checked()),
+                        }
+                        "打包为一个 ZIP 文件（否则保存为一组以会话命名的图片）"
+                    }
+                    div { class: "flex items-center justify-between",
+                        span { class: "text-white text-sm", "会话（已选 {selected_count} 个）" }
+                        div { class: "flex gap-2",
+                            button {
+                                class: "text-blue-400 hover:text-blue-300 text-sm px-2 py-1 cursor-pointer",
+                                disabled: exporting,
+                                onclick: move |_| {
+                                    selected.set(contacts.read().iter().map(|contact| contact.id.clone()).collect());
+                                },
+                                "全选"
+                            }
+                            button {
+                                class: "text-blue-400 hover:text-blue-300 text-sm px-2 py-1 cursor-pointer",
+                                disabled: exporting,
+                                onclick: move |_| selected.write().clear(),
+                                "全不选"
+                            }
+                        }
+                    }
+                    div { class: "space-y-1 max-h-80 overflow-y-auto",
+                        for (id, name, count) in contact_rows {
+                            label {
+                                key: "{id}",
+                                class: "flex items-center gap-2 p-2 bg-[#2b2b2b] rounded border border-gray-600 text-white text-sm cursor-pointer",
+                                input {
+                                    r#type: "checkbox",
+                                    class: "w-4 h-4 accent-black cursor-pointer",
+                                    checked: selected.read().contains(&id),
+                                    disabled: exporting,
+                                    onchange: {
+                                        let id = id.clone();
+                                        move |e: FormEvent| {
+                                            if e.checked() {
+                                                selected.write().insert(id.clone());
+                                            } else {
+                                                selected.write().remove(&id);
+                                            }
+                                        }
+                                    },
+                                }
+                                span { class: "flex-1 truncate", "{name}" }
+                                span { class: "text-gray-400 text-xs", "{count} 条消息" }
+                            }
+                        }
+                    }
+                    div { class: "flex gap-3 pt-4",
+                        button {
+                            class: "flex-1 bg-blue-600 hover:bg-blue-500 disabled:opacity-50 text-white py-2 rounded text-sm font-medium transition-colors",
+                            disabled: exporting || selected_count == 0,
+                            onclick: start_export,
+                            if let Some(BatchProgress { done, total, name }) = progress() {
+                                "正在截图 {done} / {total}：{name}"
+                            } else {
+                                "导出 {selected_count} 个会话"
+                            }
+                        }
+                        if exporting {
+                            button {
+                                class: "bg-[#3a3a3a] hover:bg-[#4a4a4a] text-white px-4 py-2 rounded text-sm font-medium transition-colors",
+                                onclick: cancel_export,
+                                "取消"
+                            }
+                        }
+                    }
+                }
+                div {
+                    flex: "0 1 40%",
+                    width: "40%",
+                    min_width: "400px",
+                    min_height: 0,
+                    if !failures.read().is_empty() {
+                        h2 { class: "text-white text-base font-bold mb-2", "以下会话没有导出" }
+                        div { class: "space-y-2",
+                            for (name, reason) in failures() {
+                                div { class: "p-3 bg-[#2b2b2b] rounded border border-red-400/40",
+                                    p { class: "text-white text-sm", "{name}" }
+                                    p { class: "text-red-300 text-xs break-all", "{reason}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
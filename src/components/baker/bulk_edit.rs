@@ -0,0 +1,130 @@
+use crate::components::baker::storage::v2::{Message, MessageKind, Mission};
+use std::collections::HashSet;
+
+/// 批量编辑中可以选择的消息类型
+pub const EDITABLE_KINDS: [MessageKind; 6] = [
+    MessageKind::Normal,
+    MessageKind::Status,
+    MessageKind::TopicEnded,
+    MessageKind::Image,
+    MessageKind::Sticker,
+    MessageKind::Mission,
+];
+
+/// 消息类型在编辑器中显示的名字
+pub fn kind_label(kind: &MessageKind) -> &'static str {
+    match kind {
+        MessageKind::Normal => "文字",
+        MessageKind::Status => "状态",
+        MessageKind::TopicEnded => "话题结束",
+        MessageKind::Image => "图片",
+        MessageKind::Sticker => "表情",
+        MessageKind::Mission => "任务",
+    }
+}
+
+///
+/// 修改消息的类型，在文字和任务之间转换时保留标题或文字。
+///
+/// # 参数
+///
+/// - msg: 要修改的消息。
+/// - kind: 新的类型。
+///
+pub fn change_kind(msg: &mut Message, kind: MessageKind) {
+    if kind == MessageKind::Mission {
+        if msg.mission.is_none() {
+            msg.mission = Some(Mission {
+                title: std::mem::take(&mut msg.content),
+                ..Mission::default()
+            });
+        }
+    } else if msg.kind == MessageKind::Mission
+        && let Some(mission) = msg.mission.take()
+        && msg.content.is_empty()
+    {
+        msg.content = mission.title;
+    }
+    msg.kind = kind;
+}
+
+///
+/// 把选中的消息移动到某条消息之前，保持它们原来的先后顺序。
+///
+/// # 参数
+///
+/// - messages: 全部消息。
+/// - selected: 选中消息的 ID。
+/// - index: 移动前的位置，等于消息数时移动到末尾。
+///
+pub fn move_selected(messages: &mut Vec<Message>, selected: &HashSet<String>, index: usize) {
+    let index = index.min(messages.len());
+    let insert_at = messages[..index]
+        .iter()
+        .filter(|msg| !selected.contains(&msg.id))
+        .count();
+    let (moved, mut rest): (Vec<_>, Vec<_>) = messages
+        .drain(..)
+        .partition(|msg| selected.contains(&msg.id));
+    rest.splice(insert_at..insert_at, moved);
+    *messages = rest;
+}
+
+///
+/// 选中的消息各自上移或下移一条，已经到头的消息不动。
+///
+/// # 参数
+///
+/// - messages: 全部消息。
+/// - selected: 选中消息的 ID。
+/// - up: 为真时上移。
+///
+pub fn shift_selected(messages: &mut [Message], selected: &HashSet<String>, up: bool) {
+    let len = messages.len();
+    for step in 1..len {
+        // 上移时从前往后交换，下移时从后往前，选中的消息不会越过彼此
+        let index = if up { step } else { len - 1 - step };
+        let neighbor = if up { index - 1 } else { index + 1 };
+        if selected.contains(&messages[index].id) && !selected.contains(&messages[neighbor].id) {
+            messages.swap(index, neighbor);
+        }
+    }
+}
+
+///
+/// 修改选中消息的发送者。
+///
+/// # 参数
+///
+/// - messages: 全部消息。
+/// - selected: 选中消息的 ID。
+/// - sender_id: 新的发送者。
+///
+pub fn set_sender(messages: &mut [Message], selected: &HashSet<String>, sender_id: &str) {
+    for msg in messages.iter_mut().filter(|msg| selected.contains(&msg.id)) {
+        msg.sender_id = sender_id.to_string();
+    }
+}
+
+///
+/// 从 `anchor` 到 `index` 之间（包括两端）全部消息的 ID，用于按住 Shift 连续选择。
+///
+/// # 参数
+///
+/// - messages: 全部消息。
+/// - anchor: 上次点击的位置。
+/// - index: 这次点击的位置。
+///
+pub fn range_ids(messages: &[Message], anchor: usize, index: usize) -> Vec<String> {
+    let (start, end) = if anchor <= index {
+        (anchor, index)
+    } else {
+        (index, anchor)
+    };
+    messages
+        .iter()
+        .skip(start)
+        .take(end + 1 - start)
+        .map(|msg| msg.id.clone())
+        .collect()
+}
@@ -0,0 +1,414 @@
+use dioxus::prelude::*;
+use std::collections::HashSet;
+
+use crate::components::baker::Route;
+use crate::components::baker::bulk_edit::{
+    EDITABLE_KINDS, change_kind, kind_label, move_selected, range_ids, set_sender, shift_selected,
+};
+use crate::components::baker::chat_render::contact_display_name;
+use crate::components::baker::modals::Modal;
+use crate::components::baker::storage::v2::{AppState, Message, MessageKind};
+
+///
+/// 一次编辑整个会话全部消息的页面，修改在按下“应用”后一起写入会话。
+///
+/// # 参数
+///
+/// - contact_id: 会话的 ID。
+///
+#[component]
+pub(super) fn BulkEditorPage(contact_id: String) -> Element {
+    let mut app_state = use_context::<Signal<AppState>>();
+    let navigator = navigator();
+
+    let contact = use_signal({
+        let contact_id = contact_id.clone();
+        move || {
+            app_state
+                .read()
+                .contacts
+                .iter()
+                .find(|c| c.id == contact_id)
+                .cloned()
+        }
+    });
+    // 打开页面时的消息，用来判断是否有修改
+    let original = use_signal({
+        let contact_id = contact_id.clone();
+        move || {
+            app_state
+                .read()
+                .messages
+                .get(&contact_id)
+                .cloned()
+                .unwrap_or_default()
+        }
+    });
+    let mut draft = use_signal(move || original.peek().clone());
+    let mut selected = use_signal(HashSet::<String>::new);
+    // 上次点击的位置，按住 Shift 时从这里连续选择
+    let mut anchor = use_signal(|| None::<usize>);
+    let mut dragging = use_signal(|| None::<String>);
+    let mut drop_index = use_signal(|| None::<usize>);
+    let mut bulk_sender = use_signal(String::new);
+    let mut confirm_leave = use_signal(|| false);
+
+    // 可以选择的发送者：自己和会话中的干员
+    let members = use_memo(move || {
+        let state = app_state.read();
+        let mut members = vec![(
+            state.user_profile.id.clone(),
+            format!("{}（自己）", state.user_profile.name),
+        )];
+        if let Some(contact) = contact.read().as_ref() {
+            members.extend(contact.participant_ids.iter().filter_map(|id| {
+                state
+                    .operators
+                    .iter()
+                    .find(|op| op.id == *id)
+                    .map(|op| (op.id.clone(), op.name.clone()))
+            }));
+        }
+        members
+    });
+
+    let dirty = *draft.read() != *original.read();
+
+    let leave = move |_| {
+        if draft.peek().as_slice() != original.peek().as_slice() {
+            confirm_leave.set(true);
+        } else {
+            navigator.push(Route::BakerLayout {});
+        }
+    };
+
+    let apply = {
+        let contact_id = contact_id.clone();
+        move |_| {
+            app_state
+                .write()
+                .messages
+                .insert(contact_id.clone(), draft());
+            navigator.push(Route::BakerLayout {});
+        }
+    };
+
+    let mut edit = move |id: &str, update: &dyn Fn(&mut Message)| {
+        if let Some(msg) = draft.write().iter_mut().find(|msg| msg.id == id) {
+            update(msg);
+        }
+    };
+
+    let mut drop_on = move |index: usize| {
+        let Some(id) = dragging() else {
+            return;
+        };
+        // 拖动选中的消息时一起移动全部选中的消息
+        let moving = if selected.read().contains(&id) {
+            selected()
+        } else {
+            HashSet::from([id])
+        };
+        move_selected(&mut draft.write(), &moving, index);
+        dragging.set(None);
+        drop_index.set(None);
+    };
+
+    let selected_count = selected.read().len();
+    let total = draft.read().len();
+    let rows = draft
+        .read()
+        .iter()
+        .enumerate()
+        .map(|(index, msg)| (index, msg.clone()))
+        .collect::<Vec<_>>();
+    let title = contact
+        .read()
+        .as_ref()
+        .map(|contact| contact_display_name(contact, &app_state.read().operators))
+        .unwrap_or_default();
+    let toolbar_button = "bg-[#3a3a3a] hover:bg-[#4a4a4a] disabled:opacity-50 text-white px-3 py-1 rounded text-sm transition-colors";
+    let field_class = "bg-[#e9e9e9] border border-black/10 rounded p-2 text-black text-sm focus:outline-none focus:border-black/30";
+
+    rsx! {
+        if confirm_leave() {
+            Modal {
+                title: "放弃修改",
+                content_confirmation_button: "放弃",
+                on_close: move |_| confirm_leave.set(false),
+                on_confirm: move |_| {
+                    confirm_leave.set(false);
+                    navigator.push(Route::BakerLayout {});
+                },
+
+                {
+                    rsx! {
+                        p { class: "text-black", "还有没有应用的修改，确定要离开吗？" }
+                    }
+                }
+            }
+        }
+        div {
+            width: "100%",
+            height: "100vh",
+            display: "flex",
+            flex_direction: "column",
+            position: "relative",
+            overflow: "hidden",
+            div { class: "h-14 shrink-0 flex items-center gap-3 px-6 border-b border-gray-600 bg-[#1f1f1f]/80 backdrop-blur-sm",
+                button {
+                    class: "text-gray-300 hover:text-white text-lg px-2 py-1 rounded-lg hover:bg-white/5 transition-colors",
+                    onclick: leave,
+                    "←"
+                }
+                h1 { class: "flex-1 text-white text-lg font-bold truncate", "批量编辑：{title}" }
+                if dirty {
+                    span { class: "text-yellow-300 text-sm", "有未应用的修改" }
+                }
+                button {
+                    class: "bg-[#3a3a3a] hover:bg-[#4a4a4a] disabled:opacity-50 text-white px-4 py-2 rounded text-sm font-medium transition-colors",
+                    disabled: !dirty,
+                    onclick: move |_| {
+                        draft.set(original());
+                        selected.write().clear();
+                    },
+                    "撤销全部修改"
+                }
+                button {
+                    class: "bg-blue-600 hover:bg-blue-500 disabled:opacity-50 text-white px-4 py-2 rounded text-sm font-medium transition-colors",
+                    disabled: !dirty || contact.read().is_none(),
+                    onclick: apply,
+                    "应用全部修改"
+                }
+            }
+            if contact.read().is_none() {
+                p { class: "text-white p-6", "找不到这个会话。" }
+            } else {
+                div { class: "shrink-0 flex flex-wrap items-center gap-2 px-6 py-3 border-b border-gray-600",
+                    button {
+                        class: toolbar_button,
+                        onclick: move |_| {
+                            let all = draft.read().iter().map(|msg| msg.id.clone()).collect();
+                            selected.set(all);
+                        },
+                        "全选"
+                    }
+                    button {
+                        class: toolbar_button,
+                        onclick: move |_| selected.write().clear(),
+                        "全不选"
+                    }
+                    span { class: "text-gray-300 text-sm px-2", "已选 {selected_count} / {total} 条" }
+                    button {
+                        class: toolbar_button,
+                        disabled: selected_count == 0,
+                        onclick: move |_| shift_selected(&mut draft.write(), &selected.read(), true),
+                        "上移"
+                    }
+                    button {
+                        class: toolbar_button,
+                        disabled: selected_count == 0,
+                        onclick: move |_| shift_selected(&mut draft.write(), &selected.read(), false),
+                        "下移"
+                    }
+                    button {
+                        class: toolbar_button,
+                        disabled: selected_count == 0,
+                        onclick: move |_| move_selected(&mut draft.write(), &selected.read(), 0),
+                        "移到开头"
+                    }
+                    button {
+                        class: toolbar_button,
+                        disabled: selected_count == 0,
+                        onclick: move |_| {
+                            let len = draft.peek().len();
+                            move_selected(&mut draft.write(), &selected.read(), len);
+                        },
+                        "移到末尾"
+                    }
+                    select {
+                        class: field_class,
+                        value: "{bulk_sender}",
+                        onchange: move |e| bulk_sender.set(e.value()),
+                        option { value: "", "选择发送者……" }
+                        for (id, name) in members() {
+                            option { key: "{id}", value: "{id}", "{name}" }
+                        }
+                    }
+                    button {
+                        class: toolbar_button,
+                        disabled: selected_count == 0 || bulk_sender.read().is_empty(),
+                        onclick: move |_| set_sender(&mut draft.write(), &selected.read(), &bulk_sender()),
+                        "修改发送者"
+                    }
+                    button {
+                        class: "bg-red-700 hover:bg-red-600 disabled:opacity-50 text-white px-3 py-1 rounded text-sm transition-colors",
+                        disabled: selected_count == 0,
+                        onclick: move |_| {
+                            let ids = selected();
+                            draft.write().retain(|msg| !ids.contains(&msg.id));
+                            selected.write().clear();
+                            anchor.set(None);
+                        },
+                        "删除"
+                    }
+                    span { class: "text-gray-400 text-xs", "拖动消息可以调整顺序，按住 Shift 点击可以连续选择" }
+                }
+                div { class: "flex-1 overflow-y-auto px-6 py-3 space-y-2 custom-scrollbar",
+                    for (index , msg) in rows {
+                        div {
+                            key: "{msg.id}",
+                            draggable: "true",
+                            class: if selected.read().contains(&msg.id) { "flex items-start gap-3 p-2 rounded border border-blue-400/60 bg-blue-500/10" } else { "flex items-start gap-3 p-2 rounded border border-gray-600 bg-[#2b2b2b]" },
+                            style: if drop_index() == Some(index) { "box-shadow: 0 -3px 0 #60a5fa" } else { "" },
+                            ondragstart: {
+                                let id = msg.id.clone();
+                                move |_| dragging.set(Some(id.clone()))
+                            },
+                            ondragover: move |e| {
+                                e.prevent_default();
+                                if dragging.peek().is_some() && *drop_index.peek() != Some(index) {
+                                    drop_index.set(Some(index));
+                                }
+                            },
+                            ondrop: move |e| {
+                                e.prevent_default();
+                                drop_on(index);
+                            },
+                            ondragend: move |_| {
+                                dragging.set(None);
+                                drop_index.set(None);
+                            },
+                            input {
+                                r#type: "checkbox",
+                                class: "w-4 h-4 mt-2 accent-black cursor-pointer",
+                                checked: selected.read().contains(&msg.id),
+                                onclick: {
+                                    let id = msg.id.clone();
+                                    move |e: MouseEvent| {
+                                        let range = match anchor() {
+                                            Some(start) if e.modifiers().shift() => {
+                                                range_ids(&draft.read(), start, index)
+                                            }
+                                            _ => Vec::new(),
+                                        };
+                                        let mut set = selected.write();
+                                        if !range.is_empty() {
+                                            set.extend(range);
+                                        } else if !set.remove(&id) {
+                                            set.insert(id.clone());
+                                        }
+                                        anchor.set(Some(index));
+                                    }
+                                },
+                            }
+                            span { class: "text-gray-500 text-xs w-8 mt-2 text-right cursor-grab select-none",
+                                "{index + 1}"
+                            }
+                            select {
+                                class: "{field_class} w-36 shrink-0",
+                                onchange: {
+                                    let id = msg.id.clone();
+                                    move |e: FormEvent| {
+                                        let sender_id = e.value();
+                                        edit(&id, &|msg: &mut Message| msg.sender_id = sender_id.clone());
+                                    }
+                                },
+                                if !members.read().iter().any(|(id, _)| *id == msg.sender_id) {
+                                    option { value: "{msg.sender_id}", selected: true, "未知成员" }
+                                }
+                                for (id , name) in members() {
+                                    option {
+                                        key: "{id}",
+                                        value: "{id}",
+                                        selected: id == msg.sender_id,
+                                        "{name}"
+                                    }
+                                }
+                            }
+                            select {
+                                class: "{field_class} w-28 shrink-0",
+                                onchange: {
+                                    let id = msg.id.clone();
+                                    move |e: FormEvent| {
+                                        if let Some(kind) = EDITABLE_KINDS
+                                            .iter()
+                                            .find(|kind| kind_label(kind) == e.value())
+                                        {
+                                            edit(&id, &|msg: &mut Message| change_kind(msg, kind.clone()));
+                                        }
+                                    }
+                                },
+                                for kind in EDITABLE_KINDS.iter() {
+                                    option {
+                                        value: kind_label(kind),
+                                        selected: *kind == msg.kind,
+                                        {kind_label(kind)}
+                                    }
+                                }
+                            }
+                            if matches!(msg.kind, MessageKind::Image | MessageKind::Sticker) {
+                                img {
+                                    class: "w-12 h-12 object-contain shrink-0 rounded bg-black/20",
+                                    src: "{msg.content}",
+                                }
+                            }
+                            if msg.kind == MessageKind::Mission {
+                                // 这里只改标题，其他内容在聊天界面中修改
+                                input {
+                                    class: "{field_class} flex-1 min-w-0",
+                                    placeholder: "任务标题",
+                                    value: msg.mission.as_ref().map(|mission| mission.title.clone()).unwrap_or_default(),
+                                    oninput: {
+                                        let id = msg.id.clone();
+                                        move |e: FormEvent| {
+                                            let title = e.value();
+                                            edit(&id, &|msg: &mut Message| {
+                                                msg.mission.get_or_insert_with(Default::default).title = title.clone();
+                                            });
+                                        }
+                                    },
+                                }
+                            } else {
+                                textarea {
+                                    class: "{field_class} flex-1 min-w-0 resize-y",
+                                    rows: if matches!(msg.kind, MessageKind::Image | MessageKind::Sticker) { "1" } else { "2" },
+                                    placeholder: if matches!(msg.kind, MessageKind::Image | MessageKind::Sticker) { "图片的网址或 data URL" } else { "" },
+                                    value: "{msg.content}",
+                                    oninput: {
+                                        let id = msg.id.clone();
+                                        move |e: FormEvent| {
+                                            let content = e.value();
+                                            edit(&id, &|msg: &mut Message| msg.content = content.clone());
+                                        }
+                                    },
+                                }
+                            }
+                            if !msg.reactions.is_empty() {
+                                span { class: "text-gray-400 text-xs mt-2 shrink-0",
+                                    "{msg.reactions.len()} 个回应"
+                                }
+                            }
+                        }
+                    }
+                    // 拖到这里时移动到末尾
+                    div {
+                        class: "h-10 rounded border border-dashed border-gray-600 text-gray-500 text-xs flex items-center justify-center",
+                        style: if drop_index() == Some(total) { "border-color: #60a5fa" } else { "" },
+                        ondragover: move |e| {
+                            e.prevent_default();
+                            if dragging.peek().is_some() && *drop_index.peek() != Some(total) {
+                                drop_index.set(Some(total));
+                            }
+                        },
+                        ondrop: move |e| {
+                            e.prevent_default();
+                            drop_on(total);
+                        },
+                        "拖到这里移到末尾"
+                    }
+                }
+            }
+        }
+    }
+}
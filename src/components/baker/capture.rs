@@ -1,14 +1,109 @@
 use dioxus::prelude::*;
 
+use crate::components::baker::capture_pages::{
+    CaptureFormat, DEFAULT_FILENAME_TEMPLATE, DEFAULT_QUALITY, capture_filename, capture_range,
+    encode_capture, split_pages, zip_store,
+};
+use crate::components::baker::chat_render::{ChatSource, contact_display_name};
+use crate::components::baker::layout::sleep_ms;
+use crate::components::baker::replay_capture::RENDER_SETTLE_MS;
+use crate::components::baker::save::{
+    NATIVE_SAVE, SaveOutcome, copy_image, pick_export_dir, save_files,
+};
+use crate::components::baker::storage::v2::Message;
+use crate::components::baker::text_export::{MARKDOWN_IMAGE_DIR, TextFormat, export_text};
 use crate::components::baker::{
-    Route, capture, chat_area::ChatArea, download_image, modals::Modal,
+    Route, background_style, bytes_from_data_url, capture, chat_area::ChatArea, fetch_data_urls,
+    message_preview, modals::Modal,
 };
+use anyhow::Context;
+
+/// 分页时每页的默认最大高度
+const DEFAULT_PAGE_HEIGHT: i64 = 2000;
+/// 截图的区域：聊天区域连同外面的背景和留白
+const CAPTURE_SELECTOR: &str = "#capture_frame";
+
+/// 测量聊天区域中每条消息的位置，返回分页需要的边界和整个聊天区域的高度
+async fn measure_rows() -> anyhow::Result<(Vec<f64>, f64)> {
+    let eval = document::eval(
+        r##"
+            const frame = document.querySelector("#capture_frame");
+            const list = frame?.querySelector("#chat_area #chat-scroll-container");
+            if (!list) return null;
+            const top = list.getBoundingClientRect().top;
+            const boundaries = [parseFloat(getComputedStyle(list).paddingTop) || 0];
+            for (const row of list.children) {
+                boundaries.push(row.getBoundingClientRect().bottom - top);
+            }
+            return { boundaries, height: frame.getBoundingClientRect().height };
+        "##,
+    );
+    let value = eval.await.map_err(|err| anyhow::anyhow!(err.to_string()))?;
+    let boundaries = value
+        .get("boundaries")
+        .and_then(|v| v.as_array())
+        .context("找不到聊天区域")?
+        .iter()
+        .filter_map(|v| v.as_f64())
+        .collect::<Vec<_>>();
+    let height = value
+        .get("height")
+        .and_then(|v| v.as_f64())
+        .context("找不到聊天区域")?;
+    Ok((boundaries, height))
+}
+
+///
+/// 截取一段消息，需要时按最大高度分成多张。
+///
+/// # 参数
+///
+/// - source: 要截取的消息。
+/// - visible: 离屏聊天区域显示的消息。
+/// - scale: 截图的缩放倍率。
+/// - page_height: 每张图片的最大高度，为空时不分页。
+/// - is_current: 设置改变后旧的截图任务不再继续。
+///
+pub(super) async fn capture_pages(
+    source: Vec<Message>,
+    mut visible: Signal<Vec<Message>>,
+    scale: f64,
+    page_height: Option<f64>,
+    is_current: impl Fn() -> bool,
+) -> anyhow::Result<Vec<String>> {
+    visible.set(source.clone());
+    sleep_ms(RENDER_SETTLE_MS).await;
+    let Some(page_height) = page_height else {
+        return Ok(vec![capture(CAPTURE_SELECTOR, scale).await?]);
+    };
+
+    // 标题栏、边框、输入栏和留白在每一页都会出现，从每页的高度中扣除
+    let (boundaries, height) = measure_rows().await?;
+    let content = boundaries.last().copied().unwrap_or_default()
+        - boundaries.first().copied().unwrap_or_default();
+    let pages = split_pages(&boundaries, page_height - (height - content));
+    if pages.len() <= 1 {
+        return Ok(vec![capture(CAPTURE_SELECTOR, scale).await?]);
+    }
+
+    let mut images = Vec::with_capacity(pages.len());
+    for page in pages {
+        if !is_current() {
+            break;
+        }
+        visible.set(source[page].to_vec());
+        sleep_ms(RENDER_SETTLE_MS).await;
+        images.push(capture(CAPTURE_SELECTOR, scale).await?);
+    }
+    visible.set(source);
+    Ok(images)
+}
 
 #[component]
 pub(super) fn CapturePage(contact_id: String) -> Element {
-    let app_state = use_context::<Signal<crate::components::baker::storage::v2::AppState>>();
+    let mut app_state = use_context::<Signal<crate::components::baker::storage::v2::AppState>>();
     let contacts = &app_state.read().contacts;
-    let mut img_src = use_signal(String::new);
+    let mut images = use_signal(Vec::<String>::new);
 
     let navigator = navigator();
 
@@ -28,6 +123,7 @@ pub(super) fn CapturePage(contact_id: String) -> Element {
             .cloned()
             .unwrap_or_default()
     });
+    let visible = use_signal(Vec::<Message>::new);
     let user_profile = app_state.read().user_profile.clone();
     let menu_close_token = use_signal(|| 0usize);
     let need_to_scroll_down = use_signal(|| false);
@@ -35,23 +131,208 @@ pub(super) fn CapturePage(contact_id: String) -> Element {
 
     let mut width = use_signal(|| 800i64);
     let mut scale = use_signal(|| 1.0f64);
+    let mut start_id = use_signal(|| Option::<String>::None);
+    let mut end_id = use_signal(|| Option::<String>::None);
+    let mut paged = use_signal(|| false);
+    let mut page_height = use_signal(|| DEFAULT_PAGE_HEIGHT);
+    let mut as_archive = use_signal(|| false);
+    let mut capture_token = use_signal(|| 0usize);
+    let mut export_format = use_signal(|| CaptureFormat::Png);
+    let mut quality = use_signal(|| DEFAULT_QUALITY);
+    let mut transparent = use_signal(|| false);
+    let mut include_header = use_signal(|| true);
+    let mut padding = use_signal(|| 0i64);
+    let mut filename_template = use_signal(|| DEFAULT_FILENAME_TEMPLATE.to_string());
+    let mut text_format = use_signal(|| TextFormat::Markdown);
 
-    let mut show_download_success = use_signal(|| false);
+    let mut success_message = use_signal(|| None::<String>);
+    let mut capture_error = use_signal(|| None::<String>);
+    let mut download_error = use_signal(|| None::<String>);
 
     use_effect(move || {
         width.read();
-        scale.read();
-
-        info!("effect");
+        transparent.read();
+        include_header.read();
+        padding.read();
+        let scale = scale();
+        let page_height = paged().then(|| page_height().max(1) as f64);
+        let all = messages.read();
+        let range = capture_range(&all, start_id().as_deref(), end_id().as_deref());
+        let source = all[range].to_vec();
+        drop(all);
 
+        let token = *capture_token.peek() + 1;
+        capture_token.set(token);
         spawn(async move {
-            match capture("#chat_area", scale()).await {
-                Some(src) => img_src.set(src),
-                None => error!("capture chat area failed"),
+            let is_current = move || *capture_token.peek() == token;
+            let result = capture_pages(source, visible, scale, page_height, is_current).await;
+            if !is_current() {
+                return;
+            }
+            match result {
+                Ok(srcs) => {
+                    images.set(srcs);
+                    capture_error.set(None);
+                }
+                Err(err) => {
+                    error!("capture chat area failed: {err}");
+                    capture_error.set(Some(err.to_string()));
+                }
             }
         });
     });
 
+    let contact_name = use_memo(move || {
+        contact
+            .read()
+            .as_ref()
+            .map(|contact| contact_display_name(contact, &operators.read()))
+            .unwrap_or_default()
+    });
+
+    let download = move |_| {
+        let srcs = images();
+        if srcs.is_empty() {
+            return;
+        }
+        let format = export_format();
+        let quality = quality();
+        let template = filename_template();
+        let name = contact_name();
+        let now = chrono::Local::now().naive_local();
+        spawn(async move {
+            let result: anyhow::Result<SaveOutcome> = async {
+                let total = srcs.len();
+                let mut files = Vec::with_capacity(total);
+                for (index, src) in srcs.iter().enumerate() {
+                    let png = bytes_from_data_url(src).context("无法读取截图")?;
+                    let bytes = encode_capture(&png, format, quality)?;
+                    let page = (total > 1).then_some((index, total));
+                    let filename =
+                        capture_filename(&template, &name, now, page, format.extension());
+                    files.push((filename, bytes));
+                }
+                if total > 1 && as_archive() {
+                    let filename = capture_filename(&template, &name, now, None, "zip");
+                    return save_files(vec![(filename, zip_store(&files)?)], "application/zip")
+                        .await;
+                }
+                save_files(files, format.mime()).await
+            }
+            .await;
+            match result {
+                Ok(outcome) => success_message.set(outcome.message()),
+                Err(err) => {
+                    error!("download capture failed: {err:?}");
+                    download_error.set(Some(err.to_string()));
+                }
+            }
+        });
+    };
+
+    let export_text_file = move |_| {
+        let Some(contact) = contact() else {
+            return;
+        };
+        let all = messages.read();
+        let range = capture_range(&all, start_id().as_deref(), end_id().as_deref());
+        let source_messages = all[range].to_vec();
+        drop(all);
+        let operators = operators();
+        let user_profile = app_state.read().user_profile.clone();
+        let format = text_format();
+        let template = filename_template();
+        let name = contact_name();
+        let now = chrono::Local::now().naive_local();
+        spawn(async move {
+            let source = ChatSource {
+                contact: &contact,
+                messages: &source_messages,
+                operators: &operators,
+                user_profile: &user_profile,
+            };
+            // 应用内和网络上的图片先读取为 data URL，才能嵌入网页或另存为文件
+            let remote = source
+                .image_sources()
+                .into_iter()
+                .filter(|src| !src.starts_with("data:"))
+                .collect::<Vec<_>>();
+            let images = fetch_data_urls(remote).await;
+            let export = export_text(&source, format, &images);
+            let filename = capture_filename(&template, &name, now, None, format.extension());
+            let result = if export.files.is_empty() {
+                let mime = format!("{};charset=utf-8", format.mime());
+                save_files(vec![(filename, export.text.into_bytes())], &mime).await
+            } else {
+                // Markdown 和引用的图片一起打包
+                let mut files = vec![(filename, export.text.into_bytes())];
+                files.extend(export.files);
+                let archive = capture_filename(&template, &name, now, None, "zip");
+                match zip_store(&files) {
+                    Ok(zip) => save_files(vec![(archive, zip)], "application/zip").await,
+                    Err(err) => Err(err),
+                }
+            };
+            match result {
+                Ok(outcome) => success_message.set(outcome.message()),
+                Err(err) => {
+                    error!("export text failed: {err:?}");
+                    download_error.set(Some(err.to_string()));
+                }
+            }
+        });
+    };
+
+    let copy_page = move |src: String| {
+        spawn(async move {
+            let result = match bytes_from_data_url(&src) {
+                Some(png) => copy_image(png).await,
+                None => Err(anyhow::anyhow!("无法读取截图")),
+            };
+            match result {
+                Ok(()) => success_message.set(Some("已复制到剪贴板。".to_string())),
+                Err(err) => {
+                    error!("copy capture failed: {err:?}");
+                    download_error.set(Some(err.to_string()));
+                }
+            }
+        });
+    };
+
+    let choose_export_dir = move |_| {
+        let start = app_state.read().export.dir.clone();
+        spawn(async move {
+            if let Some(dir) = pick_export_dir(&start).await {
+                app_state.write().export.dir = dir;
+            }
+        });
+    };
+
+    let message_options = messages
+        .read()
+        .iter()
+        .enumerate()
+        .map(|(index, msg)| {
+            (
+                msg.id.clone(),
+                format!("{}. {}", index + 1, message_preview(msg)),
+            )
+        })
+        .collect::<Vec<_>>();
+    let start_index = capture_range(&messages.read(), start_id().as_deref(), None).start;
+    let end_options = message_options[start_index.min(message_options.len())..].to_vec();
+    let page_count = images.read().len();
+    let export_settings = app_state.read().export.clone();
+    let frame_style = if transparent() {
+        format!("padding: {}px;", padding().max(0))
+    } else {
+        format!(
+            "padding: {}px; {}",
+            padding().max(0),
+            background_style(&app_state.read().background)
+        )
+    };
+
     let Some(contact_val) = contact() else {
         navigator.push(Route::BakerLayout {});
         return rsx! {};
@@ -61,11 +342,11 @@ pub(super) fn CapturePage(contact_id: String) -> Element {
         ChatArea {
             contact: contact_val,
             operators,
-            messages,
+            messages: visible,
             user_profile,
             menu_close_token,
             first_prev_sender_id: None,
-            force_first_avatar: false,
+            force_first_avatar: true,
             pending_typing: None,
             need_to_scroll_down,
             on_send_message: move |_| {},
@@ -74,13 +355,16 @@ pub(super) fn CapturePage(contact_id: String) -> Element {
             on_send_image: move |_| {},
             on_send_sticker: move |_| {},
             on_send_sticker_other: move |_| {},
+            on_send_mission: move |_| {},
             stickers,
             on_add_sticker: move |_| {},
             on_delete_message: move |_| {},
             on_edit_message: move |_| {},
+            on_edit_mission: move |_| {},
             on_add_reaction: move |_| {},
             on_delete_reaction: move |_| {},
             on_insert_message: move |_| {},
+            on_edit_replay_overrides: move |_| {},
             on_start_replay: move |_| {},
             on_update_chat_head_style: move |_| {},
             on_clear_messages: move |_| {},
@@ -88,21 +372,51 @@ pub(super) fn CapturePage(contact_id: String) -> Element {
             on_set_group_ops_list: move |_| {},
             on_send_image_other: move |_| {},
             is_replaying: false,
+            hide_input_bar: false,
+            hide_header: !include_header(),
             on_exit_replay: move |_| {},
         }
     };
 
     rsx! {
-        if show_download_success() {
+        if let Some(err) = download_error() {
+            Modal {
+                title: "导出失败",
+                content_confirmation_button: "好",
+                on_close: move |_| download_error.set(None),
+                on_confirm: move |_| download_error.set(None),
+
+                {
+                    rsx! {
+                        p { class: "text-black break-all", "{err}" }
+                    }
+                }
+            }
+        }
+        if let Some(err) = capture_error() {
+            Modal {
+                title: "截图失败",
+                content_confirmation_button: "好",
+                on_close: move |_| capture_error.set(None),
+                on_confirm: move |_| capture_error.set(None),
+
+                {
+                    rsx! {
+                        p { class: "text-black", "无法生成截图：{err}" }
+                    }
+                }
+            }
+        }
+        if let Some(message) = success_message() {
             Modal {
                 title: "操作成功",
                 content_confirmation_button: "好",
-                on_close: move |_| show_download_success.set(false),
-                on_confirm: move |_| show_download_success.set(false),
+                on_close: move |_| success_message.set(None),
+                on_confirm: move |_| success_message.set(None),
 
                 {
                     rsx! {
-                        p { class: "text-black", "已下载到用户的下载目录中。" }
+                        p { class: "text-black break-all", "{message}" }
                     }
                 }
             }
@@ -112,7 +426,7 @@ pub(super) fn CapturePage(contact_id: String) -> Element {
             style: "transform: translateX(-325000px) translateY(-325000px); overflow-y: hidden",
             position: "absolute",
             width: "{width}px",
-            {chat_area}
+            div { id: "capture_frame", style: "{frame_style}", {chat_area} }
         }
         div {
             width: "100%",
@@ -180,17 +494,232 @@ pub(super) fn CapturePage(contact_id: String) -> Element {
                             },
                         }
                     }
+                    div { class: "space-y-1 mt-4",
+                        label { class: "block text-white text-sm", "留白（缩放前的像素）" }
+                        input {
+                            class: "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30 resize-none",
+                            r#type: "number",
+                            min: "0",
+                            step: "8",
+                            value: "{padding}",
+                            oninput: move |e| {
+                                if let Ok(num) = e.value().parse::<i64>() {
+                                    padding.set(num.max(0));
+                                }
+                            },
+                        }
+                    }
+                    label { class: "flex items-center gap-2 text-white text-sm cursor-pointer mt-4",
+                        input {
+                            r#type: "checkbox",
+                            class: "w-4 h-4 accent-black cursor-pointer",
+                            checked: transparent(),
+                            onchange: move |e| transparent.set(e.checked()),
+                        }
+                        "透明背景（否则使用设置中的背景）"
+                    }
+                    label { class: "flex items-center gap-2 text-white text-sm cursor-pointer mt-4",
+                        input {
+                            r#type: "checkbox",
+                            class: "w-4 h-4 accent-black cursor-pointer",
+                            checked: include_header(),
+                            onchange: move |e| include_header.set(e.checked()),
+                        }
+                        "包含标题栏"
+                    }
+                    div { class: "space-y-1 mt-4",
+                        label { class: "block text-white text-sm", "起始消息" }
+                        select {
+                            class: "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30",
+                            onchange: move |e| start_id.set(Some(e.value())),
+                            for (id, label) in message_options {
+                                option {
+                                    selected: start_id().as_ref() == Some(&id),
+                                    value: "{id}",
+                                    "{label}"
+                                }
+                            }
+                        }
+                    }
+                    div { class: "space-y-1 mt-4",
+                        label { class: "block text-white text-sm", "结束消息" }
+                        select {
+                            class: "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30",
+                            onchange: move |e| {
+                                let value = e.value();
+                                end_id.set(if value.is_empty() { None } else { Some(value) });
+                            },
+                            option { value: "", selected: end_id().is_none(), "截到最后" }
+                            for (id, label) in end_options {
+                                option {
+                                    selected: end_id().as_ref() == Some(&id),
+                                    value: "{id}",
+                                    "{label}"
+                                }
+                            }
+                        }
+                    }
+                    label { class: "flex items-center gap-2 text-white text-sm cursor-pointer mt-4",
+                        input {
+                            r#type: "checkbox",
+                            class: "w-4 h-4 accent-black cursor-pointer",
+                            checked: paged(),
+                            onchange: move |e| paged.set(e.checked()),
+                        }
+                        "按最大高度分成多张（在消息之间分开）"
+                    }
+                    if paged() {
+                        div { class: "space-y-1 mt-4",
+                            label { class: "block text-white text-sm",
+                                "每张的最大高度（缩放前的像素）"
+                            }
+                            input {
+                                class: "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30 resize-none",
+                                r#type: "number",
+                                min: "200",
+                                step: "100",
+                                value: "{page_height}",
+                                oninput: move |e| {
+                                    if let Ok(num) = e.value().parse::<i64>() {
+                                        page_height.set(num.max(200));
+                                    }
+                                },
+                            }
+                        }
+                        label { class: "flex items-center gap-2 text-white text-sm cursor-pointer mt-4",
+                            input {
+                                r#type: "checkbox",
+                                class: "w-4 h-4 accent-black cursor-pointer",
+                                checked: as_archive(),
+                                onchange: move |e| as_archive.set(e.checked()),
+                            }
+                            "打包为一个 ZIP 文件下载（否则逐张下载编号的文件）"
+                        }
+                    }
+                    div { class: "space-y-1 mt-4",
+                        label { class: "block text-white text-sm", "格式" }
+                        select {
+                            class: "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30",
+                            onchange: move |e| {
+                                if let Some(format) = CaptureFormat::ALL
+                                    .into_iter()
+                                    .find(|format| format.extension() == e.value())
+                                {
+                                    export_format.set(format);
+                                }
+                            },
+                            for format in CaptureFormat::ALL {
+                                option {
+                                    selected: export_format() == format,
+                                    value: format.extension(),
+                                    {format.label()}
+                                }
+                            }
+                        }
+                    }
+                    if export_format().has_quality() {
+                        div { class: "space-y-1 mt-4",
+                            label { class: "block text-white text-sm", "质量（1 到 100）" }
+                            input {
+                                class: "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30 resize-none",
+                                r#type: "number",
+                                min: "1",
+                                max: "100",
+                                value: "{quality}",
+                                oninput: move |e| {
+                                    if let Ok(num) = e.value().parse::<u8>() {
+                                        quality.set(num.clamp(1, 100));
+                                    }
+                                },
+                            }
+                        }
+                    }
+                    if export_format() == CaptureFormat::Jpeg && transparent() {
+                        p { class: "text-gray-400 text-xs mt-1",
+                            "JPEG 不支持透明，透明的部分会填充为深色。"
+                        }
+                    }
+                    div { class: "space-y-1 mt-4",
+                        label { class: "block text-white text-sm",
+                            "文件名（可以使用 {{name}} 会话名、{{date}} 日期、{{time}} 时间、{{page}} 编号）"
+                        }
+                        input {
+                            class: "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30 resize-none",
+                            value: "{filename_template}",
+                            oninput: move |e| filename_template.set(e.value()),
+                        }
+                    }
                     div { class: "space-y-1 mt-10",
                         button {
                             class: "w-full bg-blue-600 hover:bg-blue-500 text-white py-2 rounded text-sm font-medium transition-colors",
-                            onclick: move |_| {
-                                spawn(async move {
-                                    if download_image(&img_src(), "png", "download.png").await.is_ok() {
-                                        show_download_success.set(true)
+                            onclick: download,
+                            if page_count > 1 {
+                                "保存全部 {page_count} 张"
+                            } else {
+                                "保存"
+                            }
+                        }
+                    }
+                    div { class: "space-y-1 mt-10",
+                        label { class: "block text-white text-sm", "导出文字（使用上面的起止消息和文件名）" }
+                        div { class: "flex gap-3",
+                            select {
+                                class: "flex-1 bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30",
+                                onchange: move |e| {
+                                    if let Some(format) = TextFormat::ALL
+                                        .into_iter()
+                                        .find(|format| format.extension() == e.value())
+                                    {
+                                        text_format.set(format);
                                     }
-                                });
-                            },
-                            "下载"
+                                },
+                                for format in TextFormat::ALL {
+                                    option {
+                                        selected: text_format() == format,
+                                        value: format.extension(),
+                                        {format.label()}
+                                    }
+                                }
+                            }
+                            button {
+                                class: "bg-[#3a3a3a] hover:bg-[#4a4a4a] text-white px-4 rounded text-sm font-medium transition-colors",
+                                onclick: export_text_file,
+                                "导出文字"
+                            }
+                        }
+                        p { class: "text-gray-400 text-xs",
+                            if text_format() == TextFormat::Markdown {
+                                "图片另存到 {MARKDOWN_IMAGE_DIR} 文件夹，与 Markdown 文件一起打包为 ZIP。"
+                            } else if text_format() == TextFormat::Html {
+                                "单个网页文件，图片直接嵌入，样式与聊天界面相同。"
+                            } else {
+                                "只保留文字，图片和表情显示为 [图片]、[表情]。"
+                            }
+                        }
+                    }
+                    if NATIVE_SAVE {
+                        label { class: "flex items-center gap-2 text-white text-sm cursor-pointer mt-4",
+                            input {
+                                r#type: "checkbox",
+                                class: "w-4 h-4 accent-black cursor-pointer",
+                                checked: export_settings.ask,
+                                onchange: move |e| app_state.write().export.ask = e.checked(),
+                            }
+                            "每次询问保存位置（否则直接保存到导出文件夹）"
+                        }
+                        div { class: "flex items-center gap-3 mt-2",
+                            span { class: "flex-1 text-gray-300 text-sm break-all",
+                                if export_settings.dir.is_empty() {
+                                    "导出文件夹：未选择"
+                                } else {
+                                    "导出文件夹：{export_settings.dir}"
+                                }
+                            }
+                            button {
+                                class: "text-blue-400 hover:text-blue-300 text-sm px-2 py-1 cursor-pointer",
+                                onclick: choose_export_dir,
+                                "选择文件夹"
+                            }
                         }
                     }
                 }
@@ -199,7 +728,24 @@ pub(super) fn CapturePage(contact_id: String) -> Element {
                     width: "40%",
                     min_width: "500px",
                     min_height: 0,
-                    img { class: "w-full", src: img_src }
+                    for (page, src) in images().into_iter().enumerate().map(|(index, src)| (index + 1, src)) {
+                        div { class: "flex items-center justify-between mb-1",
+                            p { class: "text-gray-300 text-sm",
+                                if page_count > 1 {
+                                    "第 {page} / {page_count} 张"
+                                }
+                            }
+                            button {
+                                class: "text-blue-400 hover:text-blue-300 text-sm px-2 py-1 cursor-pointer",
+                                onclick: {
+                                    let src = src.clone();
+                                    move |_| copy_page(src.clone())
+                                },
+                                "复制到剪贴板"
+                            }
+                        }
+                        img { class: "w-full mb-4", src: "{src}" }
+                    }
                 }
             }
         }
@@ -0,0 +1,361 @@
+use crate::components::baker::storage::v2::Message;
+use anyhow::Context;
+use chrono::NaiveDateTime;
+use image::codecs::avif::AvifEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{ExtendedColorType, ImageEncoder, Rgb, RgbImage, Rgba, RgbaImage};
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// 默认的文件名模板
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "{name}-{date}";
+/// 默认的图片质量
+pub const DEFAULT_QUALITY: u8 = 90;
+/// JPEG 不支持透明，透明的部分铺上聊天区域默认的背景色
+pub const JPEG_MATTE: Rgba<u8> = Rgba([0x1a, 0x1a, 0x1a, 0xff]);
+/// AVIF 的编码速度，1 最慢最小，10 最快
+const AVIF_SPEED: u8 = 8;
+
+///
+/// 截图导出的格式。
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CaptureFormat {
+    Png,
+    Jpeg,
+    /// 只支持无损编码，不使用质量设置
+    WebP,
+    Avif,
+}
+
+impl CaptureFormat {
+    pub const ALL: [CaptureFormat; 4] = [
+        CaptureFormat::Png,
+        CaptureFormat::Jpeg,
+        CaptureFormat::WebP,
+        CaptureFormat::Avif,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "PNG",
+            CaptureFormat::Jpeg => "JPEG",
+            CaptureFormat::WebP => "WebP（无损）",
+            CaptureFormat::Avif => "AVIF",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "png",
+            CaptureFormat::Jpeg => "jpg",
+            CaptureFormat::WebP => "webp",
+            CaptureFormat::Avif => "avif",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "image/png",
+            CaptureFormat::Jpeg => "image/jpeg",
+            CaptureFormat::WebP => "image/webp",
+            CaptureFormat::Avif => "image/avif",
+        }
+    }
+
+    /// 是否使用质量设置
+    pub fn has_quality(self) -> bool {
+        matches!(self, CaptureFormat::Jpeg | CaptureFormat::Avif)
+    }
+}
+
+///
+/// 把截图得到的 PNG 转换为导出的格式。
+///
+/// # 参数
+///
+/// - png: 截图得到的 PNG。
+/// - format: 导出的格式，为 PNG 时原样返回。
+/// - quality: 1 到 100 的质量，只用于 JPEG 和 AVIF。
+///
+pub fn encode_capture(png: &[u8], format: CaptureFormat, quality: u8) -> anyhow::Result<Vec<u8>> {
+    if format == CaptureFormat::Png {
+        return Ok(png.to_vec());
+    }
+    let image = image::load_from_memory(png)?.to_rgba8();
+    let (width, height) = image.dimensions();
+    let quality = quality.clamp(1, 100);
+    let mut out = Vec::new();
+    match format {
+        CaptureFormat::Png => unreachable!(),
+        CaptureFormat::Jpeg => {
+            let flat = flatten(&image, JPEG_MATTE);
+            JpegEncoder::new_with_quality(&mut out, quality).write_image(
+                flat.as_raw(),
+                width,
+                height,
+                ExtendedColorType::Rgb8,
+            )?;
+        }
+        CaptureFormat::WebP => WebPEncoder::new_lossless(&mut out).write_image(
+            image.as_raw(),
+            width,
+            height,
+            ExtendedColorType::Rgba8,
+        )?,
+        CaptureFormat::Avif => AvifEncoder::new_with_speed_quality(&mut out, AVIF_SPEED, quality)
+            .write_image(
+            image.as_raw(),
+            width,
+            height,
+            ExtendedColorType::Rgba8,
+        )?,
+    }
+    Ok(out)
+}
+
+/// 把图片铺在不透明的底色上
+fn flatten(image: &RgbaImage, matte: Rgba<u8>) -> RgbImage {
+    RgbImage::from_fn(image.width(), image.height(), |x, y| {
+        let Rgba([r, g, b, a]) = *image.get_pixel(x, y);
+        let mix = |top: u8, bottom: u8| {
+            ((u32::from(top) * u32::from(a) + u32::from(bottom) * (255 - u32::from(a)) + 127) / 255)
+                as u8
+        };
+        Rgb([mix(r, matte[0]), mix(g, matte[1]), mix(b, matte[2])])
+    })
+}
+
+///
+/// 截图的消息范围，起止消息都包含在内。
+///
+/// # 参数
+///
+/// - messages: 会话的全部消息。
+/// - start_id: 第一条消息，为空或找不到时从头开始。
+/// - end_id: 最后一条消息，为空或找不到时截到最后；在起始消息之前时两者交换。
+///
+pub fn capture_range(
+    messages: &[Message],
+    start_id: Option<&str>,
+    end_id: Option<&str>,
+) -> Range<usize> {
+    let position =
+        |id: Option<&str>| id.and_then(|id| messages.iter().position(|msg| msg.id == id));
+    let start = position(start_id).unwrap_or(0);
+    let end = position(end_id).unwrap_or(messages.len().saturating_sub(1));
+    if messages.is_empty() {
+        0..0
+    } else if end < start {
+        end..start + 1
+    } else {
+        start..end + 1
+    }
+}
+
+///
+/// 在消息之间分页，每页消息部分的高度不超过 `budget`，不会把一条消息切开。
+///
+/// 一条消息本身就超出高度时单独成为一页。
+///
+/// # 参数
+///
+/// - boundaries: 消息列表内容开始的位置，以及之后每条消息底部的位置，长度为消息数加一。
+/// - budget: 每页留给消息的高度。
+///
+pub fn split_pages(boundaries: &[f64], budget: f64) -> Vec<Range<usize>> {
+    let count = boundaries.len().saturating_sub(1);
+    let mut pages = Vec::new();
+    let mut start = 0;
+    for index in 1..count {
+        // 加上这一条就超出时，从这一条开始新的一页
+        if boundaries[index + 1] - boundaries[start] > budget {
+            pages.push(start..index);
+            start = index;
+        }
+    }
+    if count > 0 {
+        pages.push(start..count);
+    }
+    pages
+}
+
+/// 分页的编号，按总页数补零
+fn page_number(index: usize, total: usize) -> String {
+    let digits = total.max(1).to_string().len();
+    format!("{:0digits$}", index + 1)
+}
+
+/// 分页的文件名，编号按总页数补零，如 `chat-01.png`
+pub fn page_filename(stem: &str, index: usize, total: usize, extension: &str) -> String {
+    format!("{stem}-{}.{extension}", page_number(index, total))
+}
+
+///
+/// 按模板生成截图的文件名，去掉文件名中不允许的字符。
+///
+/// 模板中可以使用 `{name}`（会话名）、`{date}`（如 2024-05-01）、`{time}`（如 093000）和
+/// `{page}`（分页的编号）。分页而模板中没有 `{page}` 时，编号加在文件名末尾。
+///
+/// # 参数
+///
+/// - template: 文件名模板，不含扩展名。
+/// - name: 会话名。
+/// - now: 导出的时间。
+/// - page: 分页时为这一张的序号和总张数。
+/// - extension: 扩展名。
+///
+pub fn capture_filename(
+    template: &str,
+    name: &str,
+    now: NaiveDateTime,
+    page: Option<(usize, usize)>,
+    extension: &str,
+) -> String {
+    let mut stem = template
+        .replace("{name}", name)
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H%M%S").to_string());
+    // 模板中没有 `{page}` 时，编号在清理文件名之后再加上
+    let appended_page = match page {
+        Some((index, total)) if stem.contains("{page}") => {
+            stem = stem.replace("{page}", &page_number(index, total));
+            None
+        }
+        page => page,
+    };
+    let stem = stem
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+    let stem = stem.trim().trim_matches('.');
+    let stem = if stem.is_empty() { "download" } else { stem };
+    match appended_page {
+        Some((index, total)) => page_filename(stem, index, total, extension),
+        None => format!("{stem}.{extension}"),
+    }
+}
+
+///
+/// 让一组文件名互不相同，重复的在扩展名前依次加上 ` (2)`、` (3)`。
+///
+/// 比较时不区分大小写，避免在不区分大小写的文件系统上互相覆盖。
+///
+pub fn unique_filenames(names: Vec<String>) -> Vec<String> {
+    unique_filenames_besides(names, [])
+}
+
+///
+/// 与 [`unique_filenames`] 相同，同时避开文件夹中已有的文件，不覆盖它们。
+///
+/// # 参数
+///
+/// - names: 要保存的文件名。
+/// - existing: 文件夹中已有的文件名。
+///
+pub fn unique_filenames_besides(
+    names: Vec<String>,
+    existing: impl IntoIterator<Item = String>,
+) -> Vec<String> {
+    let mut used = existing
+        .into_iter()
+        .map(|name| name.to_lowercase())
+        .collect::<HashSet<_>>();
+    names
+        .into_iter()
+        .map(|name| {
+            let (stem, extension) = match name.rsplit_once('.') {
+                Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+                _ => (name.as_str(), String::new()),
+            };
+            let mut candidate = name.clone();
+            let mut count = 1;
+            while !used.insert(candidate.to_lowercase()) {
+                count += 1;
+                candidate = format!("{stem} ({count}){extension}");
+            }
+            candidate
+        })
+        .collect()
+}
+
+/// ZIP 中的时间和日期，统一为 1980-01-01 00:00
+const ZIP_TIME: u16 = 0;
+const ZIP_DATE: u16 = (1 << 5) | 1;
+/// 文件名使用 UTF-8
+const ZIP_UTF8_FLAG: u16 = 1 << 11;
+
+///
+/// 把文件不压缩地打包为 ZIP，图片本身已经压缩过。
+///
+/// 不使用 ZIP64，文件或整个包超过 4 GiB、文件名过长或文件超过 65535 个时返回错误。
+///
+pub fn zip_store(files: &[(String, Vec<u8>)]) -> anyhow::Result<Vec<u8>> {
+    fn push_u16(buf: &mut Vec<u8>, value: u16) {
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+    fn push_u32(buf: &mut Vec<u8>, value: u32) {
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+    /// 本地文件头和中央目录共有的部分：版本、标志、方法、时间、校验和与大小
+    fn push_entry_info(buf: &mut Vec<u8>, crc: u32, size: u32) {
+        push_u16(buf, 20);
+        push_u16(buf, ZIP_UTF8_FLAG);
+        push_u16(buf, 0);
+        push_u16(buf, ZIP_TIME);
+        push_u16(buf, ZIP_DATE);
+        push_u32(buf, crc);
+        push_u32(buf, size);
+        push_u32(buf, size);
+    }
+    let too_large = "打包的文件超过 ZIP 的 4 GiB 上限";
+
+    let count = u16::try_from(files.len()).context("打包的文件超过 65535 个")?;
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in files {
+        let crc = crc32fast::hash(data);
+        let offset = u32::try_from(out.len()).context(too_large)?;
+        let size = u32::try_from(data.len()).with_context(|| format!("{name} 超过 4 GiB"))?;
+        let name_len = u16::try_from(name.len()).with_context(|| format!("文件名过长：{name}"))?;
+
+        push_u32(&mut out, 0x0403_4b50);
+        push_entry_info(&mut out, crc, size);
+        push_u16(&mut out, name_len);
+        push_u16(&mut out, 0);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        push_u32(&mut central, 0x0201_4b50);
+        push_u16(&mut central, 20);
+        push_entry_info(&mut central, crc, size);
+        push_u16(&mut central, name_len);
+        // 扩展字段、注释、磁盘号、内部属性
+        for _ in 0..4 {
+            push_u16(&mut central, 0);
+        }
+        push_u32(&mut central, 0);
+        push_u32(&mut central, offset);
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let central_offset = u32::try_from(out.len()).context(too_large)?;
+    let central_size = u32::try_from(central.len()).context(too_large)?;
+    out.extend_from_slice(&central);
+    push_u32(&mut out, 0x0605_4b50);
+    push_u16(&mut out, 0);
+    push_u16(&mut out, 0);
+    push_u16(&mut out, count);
+    push_u16(&mut out, count);
+    push_u32(&mut out, central_size);
+    push_u32(&mut out, central_offset);
+    push_u16(&mut out, 0);
+    Ok(out)
+}
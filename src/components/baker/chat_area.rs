@@ -1,11 +1,13 @@
 use crate::components::baker::Route;
+use crate::components::baker::chat_render::{contact_display_name, reaction_labels};
 use crate::components::baker::input_bar::InputBar;
 use crate::components::baker::modals::{
-    EditGroupChatProps, EditMessageModal, EditParticipantsSelvesIds, InsertMessageModal,
-    OpsSelection, PickSenderModal, ReactionModal,
+    EditGroupChatProps, EditMessageModal, EditMissionModal, EditParticipantsSelvesIds,
+    InsertMessageModal, MessageReplayModal, OpsSelection, PickSenderModal, ReactionModal,
 };
 use crate::components::baker::storage::v2::{
-    ChatHeadStyle, Contact, Message, MessageKind, Operator, UserProfile,
+    ChatHeadStyle, Contact, Message, MessageKind, MessageReplayOverrides, Mission, MissionStatus,
+    Operator, UserProfile,
 };
 use dioxus::prelude::*;
 use std::collections::HashMap;
@@ -29,13 +31,13 @@ fn menu_style(x: i32, y: i32, width: i32, height: i32) -> String {
     )
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ReplayTypingPhase {
     Typing,
     Reveal,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct PendingTyping {
     pub id: String,
     pub phase: ReplayTypingPhase,
@@ -59,15 +61,20 @@ pub fn ChatArea(
     on_send_image_other: EventHandler<(String, String)>,
     on_send_sticker: EventHandler<String>,
     on_send_sticker_other: EventHandler<(String, String)>,
+    on_send_mission: EventHandler<(Option<String>, Mission)>,
     stickers: ReadSignal<Vec<String>>,
     on_add_sticker: EventHandler<String>,
     on_delete_message: EventHandler<String>,
     on_edit_message: EventHandler<(String, String)>,
+    on_edit_mission: EventHandler<(String, Mission)>,
     on_add_reaction: EventHandler<(String, String)>,
     on_delete_reaction: EventHandler<String>,
     on_insert_message: EventHandler<(String, String, Option<String>)>,
+    on_edit_replay_overrides: EventHandler<(String, Option<MessageReplayOverrides>)>,
     on_start_replay: EventHandler<String>,
     is_replaying: bool,
+    hide_input_bar: bool,
+    hide_header: bool,
     on_exit_replay: EventHandler<()>,
     on_update_chat_head_style: EventHandler<ChatHeadStyle>,
     on_clear_messages: EventHandler<()>,
@@ -77,8 +84,10 @@ pub fn ChatArea(
     let messages_list = messages.read().clone();
     let mut context_menu = use_signal(|| Option::<(i32, i32, String)>::None);
     let mut editing_msg_id = use_signal(|| Option::<String>::None);
+    let mut creating_mission = use_signal(|| false);
     let mut insert_before_id = use_signal(|| Option::<String>::None);
     let mut reaction_msg_id = use_signal(|| Option::<String>::None);
+    let mut replay_overrides_msg_id = use_signal(|| Option::<String>::None);
     let mut header_menu_open = use_signal(|| false);
     let mut show_pick_sender = use_signal(|| false);
     let mut pick_sender_text = use_signal(|| "".to_string());
@@ -137,6 +146,13 @@ pub fn ChatArea(
         editing_msg_id.set(None);
         need_to_scroll_down.set(false);
     };
+    let handle_mission_save = move |(_, mission): (Option<String>, Mission)| {
+        if let Some(id) = editing_msg_id() {
+            on_edit_mission.call((id, mission));
+        }
+        editing_msg_id.set(None);
+        need_to_scroll_down.set(false);
+    };
     let handle_insert_save = move |(content, sender_id): (String, Option<String>)| {
         if let Some(before_id) = insert_before_id() {
             on_insert_message.call((before_id, content, sender_id));
@@ -151,6 +167,13 @@ pub fn ChatArea(
         reaction_msg_id.set(None);
         need_to_scroll_down.set(false);
     };
+    let handle_replay_overrides_save = move |overrides: Option<MessageReplayOverrides>| {
+        if let Some(id) = replay_overrides_msg_id() {
+            on_edit_replay_overrides.call((id, overrides));
+        }
+        replay_overrides_msg_id.set(None);
+        need_to_scroll_down.set(false);
+    };
     let mut handle_delete_reaction = move |id: String| {
         on_delete_reaction.call(id);
         context_menu.set(None);
@@ -161,15 +184,7 @@ pub fn ChatArea(
     let first_prev_sender_id = first_prev_sender_id.clone();
     let pending_typing_state = pending_typing.read().clone();
     let operators_list = operators.read().clone();
-    let header_name = if !contact.name.is_empty() {
-        contact.name.clone()
-    } else {
-        operators_list
-            .iter()
-            .find(|op| op.id == contact.id)
-            .map(|op| op.name.clone())
-            .unwrap_or_else(|| "未命名会话".to_string())
-    };
+    let header_name = contact_display_name(&contact, &operators_list);
     let operators_map: HashMap<&str, &Operator> = operators_list
         .iter()
         .map(|op| (op.id.as_str(), op))
@@ -209,7 +224,7 @@ pub fn ChatArea(
         rsx! {
             div {
                 class: "fixed z-[100] bg-[#2b2b2b] border border-gray-600 rounded shadow-xl py-1 w-32",
-                style: "{menu_style(x, y, 128, 256)}",
+                style: "{menu_style(x, y, 128, 336)}",
                 onclick: |e| e.stop_propagation(),
                 div {
                     class: "px-4 py-2 hover:bg-[#3a3a3a] cursor-pointer text-white text-sm transition-colors",
@@ -256,6 +271,17 @@ pub fn ChatArea(
                     },
                     "在此前插入…"
                 }
+                div {
+                    class: "px-4 py-2 hover:bg-[#3a3a3a] cursor-pointer text-white text-sm transition-colors",
+                    onclick: {
+                        let msg_id = msg_id.clone();
+                        move |_| {
+                            replay_overrides_msg_id.set(Some(msg_id.clone()));
+                            context_menu.set(None);
+                        }
+                    },
+                    "回放设置…"
+                }
                 div {
                     class: "px-4 py-2 hover:bg-[#3a3a3a] cursor-pointer text-white text-sm transition-colors",
                     onclick: {
@@ -267,6 +293,22 @@ pub fn ChatArea(
                     },
                     "从此开始回放…"
                 }
+                div {
+                    class: "px-4 py-2 hover:bg-[#3a3a3a] cursor-pointer text-white text-sm transition-colors",
+                    onclick: {
+                        let contact_id = contact.id.clone();
+                        let msg_id = msg_id.clone();
+                        move |_| {
+                            navigator()
+                                .push(Route::PresenterPage {
+                                    contact_id: contact_id.clone(),
+                                    start_id: msg_id.clone(),
+                                });
+                            context_menu.set(None);
+                        }
+                    },
+                    "从此开始演示…"
+                }
                 div {
                     class: "px-4 py-2 hover:bg-[#3a3a3a] cursor-pointer text-red-400 text-sm transition-colors",
                     onclick: {
@@ -289,7 +331,7 @@ pub fn ChatArea(
             animate: bool,
         },
         Message {
-            msg: Message,
+            msg: Box<Message>,
             is_self: bool,
             on_right: bool,
             show_avatar: bool,
@@ -349,29 +391,9 @@ pub fn ChatArea(
         });
         let (sender_name, sender_avatar) = resolve_sender(&msg.sender_id);
         // 将相同表情内容的反应用计数形式合并显示，例如 "😀 x3"
-        let mut reaction_counts: Vec<(String, u32)> = Vec::new();
-        for reaction in &msg.reactions {
-            if let Some((_, count)) = reaction_counts
-                .iter_mut()
-                .find(|(content, _)| content == &reaction.content)
-            {
-                *count += 1;
-            } else {
-                reaction_counts.push((reaction.content.clone(), 1));
-            }
-        }
-        let reaction_labels = reaction_counts
-            .into_iter()
-            .map(|(content, count)| {
-                if count > 1 {
-                    format!("{content} x{count}")
-                } else {
-                    content
-                }
-            })
-            .collect::<Vec<_>>();
+        let reaction_labels = reaction_labels(&msg.reactions);
         message_rows.push(ChatRow::Message {
-            msg: msg.clone(),
+            msg: Box::new(msg.clone()),
             is_self,
             on_right,
             show_avatar,
@@ -398,13 +420,34 @@ pub fn ChatArea(
 
             if let Some(editing_id) = editing_msg_id() {
                 if let Some(msg) = messages.read().iter().find(|m| m.id == editing_id) {
-                    EditMessageModal {
-                        initial_content: msg.content.clone(),
-                        on_close: move |_| editing_msg_id.set(None),
-                        on_save: handle_edit_save,
+                    if msg.kind == MessageKind::Mission {
+                        EditMissionModal {
+                            initial: msg.mission.clone().unwrap_or_default(),
+                            members: Vec::new(),
+                            on_close: move |_| editing_msg_id.set(None),
+                            on_save: handle_mission_save,
+                        }
+                    } else {
+                        EditMessageModal {
+                            initial_content: msg.content.clone(),
+                            on_close: move |_| editing_msg_id.set(None),
+                            on_save: handle_edit_save,
+                        }
                     }
                 }
             }
+            if creating_mission() {
+                EditMissionModal {
+                    initial: Mission::default(),
+                    members: selectable_members.clone(),
+                    on_close: move |_| creating_mission.set(false),
+                    on_save: move |(sender_id, mission)| {
+                        on_send_mission.call((sender_id, mission));
+                        creating_mission.set(false);
+                        need_to_scroll_down.set(true);
+                    },
+                }
+            }
             if insert_before_id().is_some() {
                 InsertMessageModal {
                     members: selectable_members.clone(),
@@ -418,6 +461,15 @@ pub fn ChatArea(
                     on_save: handle_reaction_save,
                 }
             }
+            if let Some(overrides_id) = replay_overrides_msg_id() {
+                if let Some(msg) = messages.read().iter().find(|m| m.id == overrides_id) {
+                    MessageReplayModal {
+                        initial: msg.replay.clone(),
+                        on_close: move |_| replay_overrides_msg_id.set(None),
+                        on_save: handle_replay_overrides_save,
+                    }
+                }
+            }
             if show_pick_sender() {
                 PickSenderModal {
                     members: selectable_members.clone(),
@@ -469,7 +521,8 @@ pub fn ChatArea(
 
             {context_menu_view}
 
-            div { class: "h-14 flex items-stretch shrink-0 mb-1 relative",
+            div {
+                class: if hide_header { "hidden" } else { "h-14 flex items-stretch shrink-0 mb-1 relative" },
                 {
                     let (left, mid, right) = match contact.chat_head_style {
                         ChatHeadStyle::Default => (CHAT_HEAD_LEFT, CHAT_HEAD_MID, CHAT_HEAD_RIGHT),
@@ -568,6 +621,39 @@ pub fn ChatArea(
                                     },
                                     "导出会话到图片"
                                 }
+                                div {
+                                    class: "px-4 py-2 hover:bg-[#3a3a3a] cursor-pointer text-white text-sm transition-colors",
+                                    onclick: move |_| {
+                                        navigator()
+                                            .push(Route::ReplayCapturePage {
+                                                contact_id: contact_id(),
+                                            });
+                                        header_menu_open.set(false);
+                                    },
+                                    "导出回放动画"
+                                }
+                                div {
+                                    class: "px-4 py-2 hover:bg-[#3a3a3a] cursor-pointer text-white text-sm transition-colors",
+                                    onclick: move |_| {
+                                        navigator()
+                                            .push(Route::ScriptPage {
+                                                contact_id: contact_id(),
+                                            });
+                                        header_menu_open.set(false);
+                                    },
+                                    "编辑会话脚本"
+                                }
+                                div {
+                                    class: "px-4 py-2 hover:bg-[#3a3a3a] cursor-pointer text-white text-sm transition-colors",
+                                    onclick: move |_| {
+                                        navigator()
+                                            .push(Route::BulkEditorPage {
+                                                contact_id: contact_id(),
+                                            });
+                                        header_menu_open.set(false);
+                                    },
+                                    "批量编辑消息"
+                                }
                                 if is_replaying {
                                     div {
                                         class: "px-4 py-2 hover:bg-[#3a3a3a] cursor-pointer text-white text-sm transition-colors",
@@ -700,7 +786,7 @@ pub fn ChatArea(
                                 rsx! {
                                     div { class: "{item_margin}", key: "{row_key}",
                                         MessageBubble {
-                                            message: msg,
+                                            message: *msg,
                                             is_self,
                                             on_right,
                                             show_avatar,
@@ -727,51 +813,54 @@ pub fn ChatArea(
                         }
                     }
                 }
-                div { class: "h-[2px] mx-3 mt-0 mb-1.5 bg-[rgb(71,71,71)] z-10" }
-                div {
-                    class: "mx-[1.5px] mb-[1.5px] rounded-b-[10px] flex flex-col",
-                    style: if sticker_menu_state().is_some() { "background-color: black; border-radius: 8px;" } else { "background-color: rgb(50, 50, 50); border-radius: 0 0 10px 10px;" },
-                    div { class: "p-4",
-                        InputBar {
-                            on_send: move |text| on_send_message.call(text),
-                            on_send_other: move |text| {
-                                if contact_is_group {
-                                    pick_sender_text.set(text);
-                                    show_pick_sender.set(true);
-                                } else {
-                                    on_send_other_message.call((contact_id_for_other_text.clone(), text));
-                                    clear_input_token.set(clear_input_token() + 1);
-                                }
-                            },
-                            is_group: contact_is_group,
-                            on_send_status: move |text| on_send_status.call(text),
-                            on_send_image: move |(data_url, is_ctrl)| {
-                                if is_ctrl {
+                if !hide_input_bar {
+                    div { class: "h-[2px] mx-3 mt-0 mb-1.5 bg-[rgb(71,71,71)] z-10" }
+                    div {
+                        class: "mx-[1.5px] mb-[1.5px] rounded-b-[10px] flex flex-col",
+                        style: if sticker_menu_state().is_some() { "background-color: black; border-radius: 8px;" } else { "background-color: rgb(50, 50, 50); border-radius: 0 0 10px 10px;" },
+                        div { class: "p-4",
+                            InputBar {
+                                on_send: move |text| on_send_message.call(text),
+                                on_send_other: move |text| {
                                     if contact_is_group {
-                                        pick_sender_image.set(Some(data_url));
+                                        pick_sender_text.set(text);
                                         show_pick_sender.set(true);
                                     } else {
-                                        on_send_image_other
-                                            .call((contact_id_for_other_image.clone(), data_url));
+                                        on_send_other_message.call((contact_id_for_other_text.clone(), text));
+                                        clear_input_token.set(clear_input_token() + 1);
                                     }
-                                } else {
-                                    on_send_image.call(data_url);
-                                }
-                            },
-                            on_send_sticker: move |(sticker_src, is_ctrl)| {
-                                if is_ctrl {
-                                    pick_sender_sticker.set(Some(sticker_src));
-                                    show_pick_sender.set(true);
-                                } else {
-                                    on_send_sticker.call(sticker_src);
-                                }
-                            },
-                            stickers,
-                            on_add_sticker,
-                            menu_close_token,
-                            sticker_menu: sticker_menu_state,
-                            clear_text_token: clear_input_token,
-                            need_to_scroll_down,
+                                },
+                                is_group: contact_is_group,
+                                on_send_status: move |text| on_send_status.call(text),
+                                on_send_image: move |(data_url, is_ctrl)| {
+                                    if is_ctrl {
+                                        if contact_is_group {
+                                            pick_sender_image.set(Some(data_url));
+                                            show_pick_sender.set(true);
+                                        } else {
+                                            on_send_image_other
+                                                .call((contact_id_for_other_image.clone(), data_url));
+                                        }
+                                    } else {
+                                        on_send_image.call(data_url);
+                                    }
+                                },
+                                on_send_mission: move |_| creating_mission.set(true),
+                                on_send_sticker: move |(sticker_src, is_ctrl)| {
+                                    if is_ctrl {
+                                        pick_sender_sticker.set(Some(sticker_src));
+                                        show_pick_sender.set(true);
+                                    } else {
+                                        on_send_sticker.call(sticker_src);
+                                    }
+                                },
+                                stickers,
+                                on_add_sticker,
+                                menu_close_token,
+                                sticker_menu: sticker_menu_state,
+                                clear_text_token: clear_input_token,
+                                need_to_scroll_down,
+                            }
                         }
                     }
                 }
@@ -1029,6 +1118,10 @@ fn MessageBubble(
                                     class: "max-w-[200px] object-contain",
                                     style: "{text_anim_style}",
                                 }
+                            } else if message.kind == MessageKind::Mission {
+                                div { style: "{text_anim_style}",
+                                    MissionCard { mission: message.mission.clone().unwrap_or_default() }
+                                }
                             } else {
                                 div { style: "{text_anim_style}", "{message.content}" }
                             }
@@ -1053,6 +1146,53 @@ fn MessageBubble(
     }
 }
 
+///
+/// 任务消息气泡中的卡片。
+///
+/// # 参数
+///
+/// - mission: 任务的内容。
+///
+#[component]
+fn MissionCard(mission: Mission) -> Element {
+    let status_class = match mission.status {
+        MissionStatus::Accepted => "bg-amber-500 text-black",
+        MissionStatus::Completed => "bg-green-600 text-white",
+        MissionStatus::Failed => "bg-red-600 text-white",
+    };
+
+    rsx! {
+        div { class: "min-w-[240px] border-l-4 border-amber-500 pl-3 flex flex-col gap-1",
+            div { class: "flex items-center gap-2",
+                span { class: "px-1.5 rounded bg-amber-500 text-black text-xs leading-5 font-bold shrink-0",
+                    "任务"
+                }
+                span { class: "flex-1 font-bold", "{mission.title}" }
+                span { class: "px-1.5 rounded text-xs leading-5 shrink-0 {status_class}",
+                    {mission.status.label()}
+                }
+            }
+            if !mission.description.is_empty() {
+                div { class: "text-sm opacity-80", "{mission.description}" }
+            }
+            for (index , objective) in mission.objectives.iter().enumerate() {
+                div {
+                    key: "{index}",
+                    class: if objective.done { "flex gap-2 text-sm line-through opacity-60" } else { "flex gap-2 text-sm" },
+                    span { class: "shrink-0",
+                        if objective.done {
+                            "☑"
+                        } else {
+                            "☐"
+                        }
+                    }
+                    span { "{objective.text}" }
+                }
+            }
+        }
+    }
+}
+
 #[component]
 fn FramedAvatar(avatar_url: String, fallback_name: String) -> Element {
     let fallback_text = fallback_name
@@ -0,0 +1,1380 @@
+use crate::components::baker::replay_export::encode_png;
+use crate::components::baker::storage::v2::{
+    ChatHeadStyle, Contact, Message, MessageKind, MessageReaction, Operator, UserProfile,
+};
+use ab_glyph::{Font, FontArc, GlyphId, PxScale, point};
+use ab_glyph_rasterizer::Rasterizer;
+use anyhow::Context;
+use image::imageops::FilterType;
+use image::{Rgba, RgbaImage};
+use std::collections::HashMap;
+use std::path::Path;
+
+// 以下尺寸均为 CSS 像素，与 chat_area.rs 中的样式一致
+const HEADER_HEIGHT: f32 = 56.0;
+const HEADER_GAP: f32 = 4.0;
+const LIST_PADDING: f32 = 24.0;
+const LIST_MARGIN_RIGHT: f32 = 12.0;
+const BORDER_WIDTH: f32 = 1.5;
+const BORDER_RADIUS: f32 = 12.0;
+const AVATAR_SIZE: f32 = 56.0;
+const AVATAR_FRAME_SIZE: f32 = 98.0;
+const AVATAR_FRAME_OFFSET: f32 = (AVATAR_FRAME_SIZE - AVATAR_SIZE) / 2.0;
+const AVATAR_FRAME_NUDGE: f32 = 3.0;
+const AVATAR_SPACE: f32 = 76.0;
+const BUBBLE_MAX_RATIO: f32 = 0.6;
+const BUBBLE_PADDING_X: f32 = 12.0;
+const BUBBLE_PADDING_Y: f32 = 8.0;
+const BUBBLE_RADIUS: f32 = 16.0;
+const BUBBLE_TAIL_WIDTH: f32 = 9.0;
+const BUBBLE_TAIL_HEIGHT: f32 = 20.0;
+const IMAGE_MAX_WIDTH: f32 = 320.0;
+const STICKER_MAX_WIDTH: f32 = 200.0;
+const TEXT_SIZE: f32 = 16.0;
+const TEXT_LINE_HEIGHT: f32 = 26.0;
+const NAME_SIZE: f32 = 14.0;
+const NAME_LINE_HEIGHT: f32 = 20.0;
+const STATUS_SIZE: f32 = 12.0;
+const STATUS_LINE_HEIGHT: f32 = 16.0;
+const REACTION_LINE_HEIGHT: f32 = 24.0;
+const REACTION_PADDING_X: f32 = 8.0;
+const REACTION_PADDING_Y: f32 = 2.0;
+const REACTION_GAP: f32 = 4.0;
+
+const BORDER_COLOR: Rgba<u8> = Rgba([202, 201, 201, 255]);
+const HEADER_FALLBACK_COLOR: Rgba<u8> = Rgba([40, 40, 40, 255]);
+const MENU_BUTTON_COLOR: Rgba<u8> = Rgba([68, 67, 67, 255]);
+const MENU_DOT_COLOR: Rgba<u8> = Rgba([255, 253, 253, 255]);
+const RIGHT_BUBBLE_COLOR: Rgba<u8> = Rgba([243, 242, 242, 255]);
+const RIGHT_BUBBLE_GRID_COLOR: Rgba<u8> = Rgba([239, 237, 237, 255]);
+const LEFT_BUBBLE_COLOR: Rgba<u8> = Rgba([69, 69, 69, 255]);
+const REACTION_COLOR: Rgba<u8> = Rgba([60, 60, 60, 255]);
+const REACTION_TEXT_COLOR: Rgba<u8> = Rgba([229, 231, 235, 255]);
+const NAME_COLOR: Rgba<u8> = Rgba([167, 167, 167, 255]);
+const STATUS_COLOR: Rgba<u8> = Rgba([107, 114, 128, 255]);
+const TOPIC_ENDED_COLOR: Rgba<u8> = Rgba([145, 145, 145, 255]);
+const AVATAR_COLOR: Rgba<u8> = Rgba([75, 85, 99, 255]);
+const AVATAR_BORDER_COLOR: Rgba<u8> = Rgba([255, 255, 255, 191]);
+const AVATAR_TEXT_COLOR: Rgba<u8> = Rgba([209, 213, 219, 255]);
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+/// 顶部三条彩色装饰条，从左到右
+const DECORATION_COLORS: [Rgba<u8>; 3] = [
+    Rgba([226, 2, 226, 255]),
+    Rgba([243, 241, 0, 255]),
+    Rgba([1, 241, 241, 255]),
+];
+
+///
+/// 渲染需要的字体与界面图片。
+///
+pub struct RenderAssets {
+    /// 按顺序查找字形的字体，第一个为正文字体，至少需要一个
+    pub fonts: Vec<FontArc>,
+    /// 会话标题栏的左、中、右三段图片
+    pub chat_head: Option<[RgbaImage; 3]>,
+    /// 另一种标题栏样式的三段图片
+    pub chat_head_alt: Option<[RgbaImage; 3]>,
+    pub avatar_frame: Option<RgbaImage>,
+}
+
+impl RenderAssets {
+    /// 只有字体，标题栏和头像框使用纯色代替
+    pub fn from_fonts(fonts: Vec<FontArc>) -> Self {
+        Self {
+            fonts,
+            chat_head: None,
+            chat_head_alt: None,
+            avatar_frame: None,
+        }
+    }
+
+    ///
+    /// 从资源目录读取随应用打包的思源黑体、Bender 字体和界面图片。
+    ///
+    /// # 参数
+    ///
+    /// - assets_dir: 仓库中的 `assets` 目录。
+    ///
+    pub fn load(assets_dir: &Path) -> anyhow::Result<Self> {
+        let font = |name: &str| -> anyhow::Result<FontArc> {
+            let bytes = std::fs::read(assets_dir.join(name))
+                .with_context(|| format!("无法读取字体 {name}"))?;
+            FontArc::try_from_vec(bytes).map_err(|_| anyhow::anyhow!("无法解析字体 {name}"))
+        };
+        let image = |name: String| {
+            image::open(assets_dir.join("images").join(name))
+                .ok()
+                .map(|image| image.to_rgba8())
+        };
+        let chat_head = |suffix: &str| {
+            Some([
+                image(format!("chat_head_left{suffix}.png"))?,
+                image(format!("chat_head_mid{suffix}.png"))?,
+                image(format!("chat_head_right{suffix}.png"))?,
+            ])
+        };
+        let fonts = vec![font("SourceHanSansSC-Regular.otf")?, font("bender.otf")?];
+        Ok(Self {
+            chat_head: chat_head(""),
+            chat_head_alt: chat_head("_2"),
+            avatar_frame: image("avatarframe.png".to_string()),
+            ..Self::from_fonts(fonts)
+        })
+    }
+
+    fn chat_head_for(&self, style: &ChatHeadStyle) -> Option<&[RgbaImage; 3]> {
+        match style {
+            ChatHeadStyle::Default => self.chat_head.as_ref(),
+            ChatHeadStyle::Alt => self.chat_head_alt.as_ref(),
+        }
+    }
+
+    /// 能显示这个字的字体，都没有时使用正文字体
+    fn glyph(&self, ch: char) -> (&FontArc, GlyphId) {
+        self.fonts
+            .iter()
+            .map(|font| (font, font.glyph_id(ch)))
+            .find(|(_, id)| id.0 != 0)
+            .unwrap_or_else(|| (&self.fonts[0], self.fonts[0].glyph_id(ch)))
+    }
+
+    fn advance(&self, size: f32, ch: char) -> f32 {
+        let (font, id) = self.glyph(ch);
+        font.h_advance_unscaled(id) * size / units_per_em(font)
+    }
+
+    fn text_width(&self, style: TextStyle, text: &str) -> f32 {
+        let spacing = style.letter_spacing * text.chars().count() as f32;
+        text.chars()
+            .map(|ch| self.advance(style.size, ch))
+            .sum::<f32>()
+            + spacing
+    }
+
+    ///
+    /// 按最大宽度折行，保留原有的换行。
+    ///
+    /// 在空格处和中日韩文字之间换行，一个词比整行还长时在任意位置断开。
+    ///
+    fn wrap(&self, style: TextStyle, text: &str, max_width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+            let mut width = 0.0;
+            // 最近一个可以换行的位置
+            let mut breakpoint: Option<usize> = None;
+            for ch in paragraph.chars() {
+                let advance = self.advance(style.size, ch) + style.letter_spacing;
+                while width + advance > max_width && !line.is_empty() {
+                    match breakpoint.filter(|at| *at > 0 && *at < line.len()) {
+                        Some(at) => {
+                            let rest = line.split_off(at);
+                            lines.push(line.trim_end().to_string());
+                            line = rest.trim_start().to_string();
+                        }
+                        None => lines.push(std::mem::take(&mut line)),
+                    }
+                    width = self.text_width(style, &line);
+                    breakpoint = None;
+                }
+                if is_wide(ch) {
+                    breakpoint = Some(line.len());
+                }
+                line.push(ch);
+                width += advance;
+                if ch.is_whitespace() || is_wide(ch) {
+                    breakpoint = Some(line.len());
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+fn units_per_em(font: &FontArc) -> f32 {
+    font.units_per_em().unwrap_or(1000.0)
+}
+
+/// 中日韩文字和全角符号，前后都可以换行
+fn is_wide(ch: char) -> bool {
+    matches!(ch, '\u{2E80}'..='\u{9FFF}' | '\u{AC00}'..='\u{D7AF}' | '\u{F900}'..='\u{FAFF}' | '\u{FF00}'..='\u{FFEF}')
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TextStyle {
+    size: f32,
+    line_height: f32,
+    color: Rgba<u8>,
+    bold: bool,
+    letter_spacing: f32,
+}
+
+impl TextStyle {
+    fn new(size: f32, line_height: f32, color: Rgba<u8>) -> Self {
+        Self {
+            size,
+            line_height,
+            color,
+            bold: false,
+            letter_spacing: 0.0,
+        }
+    }
+
+    fn bold(self) -> Self {
+        Self { bold: true, ..self }
+    }
+}
+
+///
+/// 原生渲染的设置。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderOptions {
+    /// 聊天区域的宽度 (CSS 像素)
+    pub width: u32,
+    /// 缩放倍率，如 2 即为以两倍的分辨率渲染
+    pub scale: f32,
+    /// 背景色，为空时背景透明，与页面截图一致
+    pub background: Option<Rgba<u8>>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            scale: 1.0,
+            background: None,
+        }
+    }
+}
+
+///
+/// 要渲染的会话。
+///
+pub struct ChatSource<'a> {
+    pub contact: &'a Contact,
+    pub messages: &'a [Message],
+    pub operators: &'a [Operator],
+    pub user_profile: &'a UserProfile,
+}
+
+impl ChatSource<'_> {
+    /// 标题栏显示的名字
+    pub fn header_name(&self) -> String {
+        contact_display_name(self.contact, self.operators)
+    }
+
+    /// 发送者的名字和头像
+    pub fn sender(&self, sender_id: &str) -> (String, String) {
+        if sender_id == self.user_profile.id {
+            return (
+                self.user_profile.name.clone(),
+                self.user_profile.avatar_url.clone(),
+            );
+        }
+        self.operators
+            .iter()
+            .find(|op| op.id == sender_id)
+            .map(|op| (op.name.clone(), op.avatar_url.clone()))
+            .unwrap_or_default()
+    }
+
+    /// 消息和头像中引用的所有图片
+    pub fn image_sources(&self) -> Vec<String> {
+        let mut sources = Vec::new();
+        for msg in self.messages {
+            if matches!(msg.kind, MessageKind::Image | MessageKind::Sticker) {
+                sources.push(msg.content.clone());
+            }
+            sources.push(self.sender(&msg.sender_id).1);
+        }
+        sources.retain(|src| !src.is_empty());
+        sources.sort();
+        sources.dedup();
+        sources
+    }
+
+    /// 消息是否显示在右侧 (自己或 `participants_selves_ids` 中的成员)
+    pub fn on_right(&self, sender_id: &str) -> bool {
+        sender_id == self.user_profile.id
+            || self
+                .contact
+                .participants_selves_ids
+                .iter()
+                .any(|id| id == sender_id)
+    }
+}
+
+/// 会话标题栏中显示的名字，没有设置名字时使用同 ID 的干员名
+pub fn contact_display_name(contact: &Contact, operators: &[Operator]) -> String {
+    if !contact.name.is_empty() {
+        return contact.name.clone();
+    }
+    operators
+        .iter()
+        .find(|op| op.id == contact.id)
+        .map(|op| op.name.clone())
+        .unwrap_or_else(|| "未命名会话".to_string())
+}
+
+///
+/// 把相同内容的回应合并为计数，例如 "😀 x3"，顺序为第一次出现的顺序。
+///
+pub fn reaction_labels(reactions: &[MessageReaction]) -> Vec<String> {
+    let mut counts: Vec<(String, u32)> = Vec::new();
+    for reaction in reactions {
+        if let Some((_, count)) = counts
+            .iter_mut()
+            .find(|(content, _)| content == &reaction.content)
+        {
+            *count += 1;
+        } else {
+            counts.push((reaction.content.clone(), 1));
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(content, count)| {
+            if count > 1 {
+                format!("{content} x{count}")
+            } else {
+                content
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+}
+
+/// 气泡中显示的内容
+#[derive(Clone, Debug, PartialEq)]
+pub enum BubbleContent {
+    /// 折行后的文字
+    Text(Vec<String>),
+    /// 图片或表情，以及显示的位置
+    Media { src: String, rect: Rect },
+}
+
+///
+/// 排版后的一条消息。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageLayout {
+    pub message_id: String,
+    /// 是否显示在右侧 (自己或 `participants_selves_ids` 中的成员)
+    pub on_right: bool,
+    /// 图片消息不画气泡
+    pub has_bubble: bool,
+    pub bubble: Rect,
+    pub content: BubbleContent,
+    /// 群聊中显示在气泡上方的名字
+    pub sender_name: Option<(String, Rect)>,
+    /// 头像 (不含头像框)，只在连续消息的第一条显示
+    pub avatar: Option<Rect>,
+    pub avatar_src: String,
+    pub avatar_fallback: String,
+    pub reactions: Vec<(String, Rect)>,
+}
+
+///
+/// 排版后的一行。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum RowLayout {
+    Status { lines: Vec<String>, rect: Rect },
+    TopicEnded { text: String, rect: Rect },
+    Message(MessageLayout),
+}
+
+///
+/// 整个会话的排版结果，坐标为 CSS 像素。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatLayout {
+    pub width: f32,
+    pub height: f32,
+    pub header_name: String,
+    pub chat_head_style: ChatHeadStyle,
+    pub rows: Vec<RowLayout>,
+}
+
+/// 图片按宽度上限等比缩小
+fn fit_width(image: &RgbaImage, max_width: f32) -> (f32, f32) {
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    let display = width.min(max_width);
+    (display, height * display / width.max(1.0))
+}
+
+///
+/// 按页面的样式排版会话。
+///
+/// # 参数
+///
+/// - source: 要渲染的会话。
+/// - assets: 用于测量文字的字体。
+/// - width: 聊天区域的宽度 (CSS 像素)。
+/// - images: 已读取的图片，按地址索引；没有读取到的图片显示为 "[图片]" / "[表情]"。
+///
+pub fn layout_chat(
+    source: &ChatSource,
+    assets: &RenderAssets,
+    width: f32,
+    images: &HashMap<String, RgbaImage>,
+) -> ChatLayout {
+    let text_style = TextStyle::new(TEXT_SIZE, TEXT_LINE_HEIGHT, BLACK);
+    let status_style = TextStyle::new(STATUS_SIZE, STATUS_LINE_HEIGHT, STATUS_COLOR);
+    let name_style = TextStyle::new(NAME_SIZE, NAME_LINE_HEIGHT, NAME_COLOR);
+    let reaction_style = TextStyle::new(TEXT_SIZE, REACTION_LINE_HEIGHT, REACTION_TEXT_COLOR);
+
+    let content_left = LIST_PADDING;
+    let content_right = width - LIST_MARGIN_RIGHT - LIST_PADDING;
+    let content_width = (content_right - content_left).max(1.0);
+    let bubble_max = content_width * BUBBLE_MAX_RATIO;
+
+    let mut rows = Vec::new();
+    let mut y = HEADER_HEIGHT + HEADER_GAP + LIST_PADDING;
+    let mut last_sender_id: Option<&str> = None;
+    for msg in source.messages {
+        match msg.kind {
+            MessageKind::Status => {
+                let lines = assets.wrap(status_style, &msg.content, content_width);
+                y += 8.0;
+                let height = lines.len() as f32 * STATUS_LINE_HEIGHT;
+                rows.push(RowLayout::Status {
+                    lines,
+                    rect: Rect::new(content_left, y, content_width, height),
+                });
+                y += height + 8.0;
+                continue;
+            }
+            MessageKind::TopicEnded => {
+                y += 24.0;
+                rows.push(RowLayout::TopicEnded {
+                    text: msg.content.clone(),
+                    rect: Rect::new(content_left, y, content_width, NAME_LINE_HEIGHT),
+                });
+                y += NAME_LINE_HEIGHT + 24.0;
+                continue;
+            }
+            _ => {}
+        }
+
+        let on_right = source.on_right(&msg.sender_id);
+        let show_avatar = last_sender_id != Some(msg.sender_id.as_str());
+        last_sender_id = Some(msg.sender_id.as_str());
+        let (name, avatar_src) = source.sender(&msg.sender_id);
+
+        y += if show_avatar { 16.0 } else { 4.0 };
+        let sender_name = (source.contact.is_group && show_avatar).then(|| {
+            let width = assets.text_width(name_style, &name);
+            let x = if on_right {
+                content_right - AVATAR_SPACE - width
+            } else {
+                content_left + AVATAR_SPACE
+            };
+            let rect = Rect::new(x, y, width, NAME_LINE_HEIGHT);
+            y += NAME_LINE_HEIGHT + 4.0;
+            (name.clone(), rect)
+        });
+        let row_top = y;
+        let bubble_top = row_top + 4.0;
+
+        let media = match msg.kind {
+            MessageKind::Image => images
+                .get(&msg.content)
+                .map(|image| (fit_width(image, IMAGE_MAX_WIDTH.min(bubble_max)), false)),
+            MessageKind::Sticker => images.get(&msg.content).map(|image| {
+                let max = STICKER_MAX_WIDTH.min(bubble_max - BUBBLE_PADDING_X * 2.0);
+                (fit_width(image, max), true)
+            }),
+            _ => None,
+        };
+        let has_bubble = !matches!(media, Some((_, false)));
+        let (padding_x, padding_y) = if has_bubble {
+            (BUBBLE_PADDING_X, BUBBLE_PADDING_Y)
+        } else {
+            (0.0, 0.0)
+        };
+        let inner_max = bubble_max - padding_x * 2.0;
+
+        let (content, mut inner_width, mut inner_height) = match media {
+            Some(((width, height), _)) => (
+                BubbleContent::Media {
+                    src: msg.content.clone(),
+                    rect: Rect::new(0.0, 0.0, width, height),
+                },
+                width,
+                height,
+            ),
+            None => {
+                let mission_text;
+                let text = match msg.kind {
+                    MessageKind::Image => "[图片]",
+                    MessageKind::Sticker => "[表情]",
+                    MessageKind::Mission => {
+                        mission_text = msg.mission_text();
+                        mission_text.as_str()
+                    }
+                    _ => msg.content.as_str(),
+                };
+                let lines = assets.wrap(text_style, text, inner_max);
+                let width = lines
+                    .iter()
+                    .map(|line| assets.text_width(text_style, line))
+                    .fold(0.0, f32::max);
+                let height = lines.len() as f32 * TEXT_LINE_HEIGHT;
+                (BubbleContent::Text(lines), width, height)
+            }
+        };
+
+        // 回应排在内容下方，超出宽度时换行
+        let mut reactions = Vec::new();
+        let labels = reaction_labels(&msg.reactions);
+        if !labels.is_empty() {
+            let pill_height = REACTION_LINE_HEIGHT + REACTION_PADDING_Y * 2.0;
+            let mut line: Vec<(String, f32)> = Vec::new();
+            let mut lines = Vec::new();
+            let mut line_width = 0.0;
+            for label in labels {
+                let width = (assets.text_width(reaction_style, &label) + REACTION_PADDING_X * 2.0)
+                    .min(inner_max);
+                let gap = if line.is_empty() { 0.0 } else { REACTION_GAP };
+                if !line.is_empty() && line_width + gap + width > inner_max {
+                    lines.push((std::mem::take(&mut line), line_width));
+                    line_width = 0.0;
+                }
+                line_width += if line.is_empty() {
+                    width
+                } else {
+                    REACTION_GAP + width
+                };
+                line.push((label, width));
+            }
+            lines.push((line, line_width));
+            inner_width = lines
+                .iter()
+                .map(|(_, width)| *width)
+                .fold(inner_width, f32::max);
+            inner_height += 8.0;
+            for (line, _) in lines {
+                reactions.push((line, inner_height));
+                inner_height += pill_height + REACTION_GAP;
+            }
+            inner_height -= REACTION_GAP;
+        }
+
+        let bubble_width = inner_width + padding_x * 2.0;
+        let bubble_height = inner_height + padding_y * 2.0;
+        let bubble_x = if on_right {
+            content_right - AVATAR_SPACE - bubble_width
+        } else {
+            content_left + AVATAR_SPACE
+        };
+        let bubble = Rect::new(bubble_x, bubble_top, bubble_width, bubble_height);
+        let inner_x = bubble.x + padding_x;
+        let inner_y = bubble.y + padding_y;
+        let content = match content {
+            BubbleContent::Media { src, rect } => BubbleContent::Media {
+                src,
+                rect: Rect::new(inner_x, inner_y, rect.width, rect.height),
+            },
+            text => text,
+        };
+
+        // 每行回应在气泡内靠向发送者一侧
+        let reactions = reactions
+            .into_iter()
+            .flat_map(|(line, top)| {
+                let total = line.iter().map(|(_, width)| width).sum::<f32>()
+                    + REACTION_GAP * (line.len() as f32 - 1.0);
+                let mut x = if on_right {
+                    inner_x + inner_width - total
+                } else {
+                    inner_x
+                };
+                line.into_iter()
+                    .map(|(label, width)| {
+                        let rect = Rect::new(
+                            x,
+                            inner_y + top,
+                            width,
+                            REACTION_LINE_HEIGHT + REACTION_PADDING_Y * 2.0,
+                        );
+                        x += width + REACTION_GAP;
+                        (label, rect)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        // 头像框向外伸出 AVATAR_FRAME_OFFSET，头像本身与消息列表的边缘对齐
+        let avatar = show_avatar.then(|| {
+            let x = if on_right {
+                content_right - AVATAR_SIZE
+            } else {
+                content_left
+            };
+            Rect::new(x, row_top, AVATAR_SIZE, AVATAR_SIZE)
+        });
+
+        rows.push(RowLayout::Message(MessageLayout {
+            message_id: msg.id.clone(),
+            on_right,
+            has_bubble,
+            bubble,
+            content,
+            sender_name,
+            avatar,
+            avatar_src,
+            avatar_fallback: name
+                .chars()
+                .next()
+                .map(|ch| ch.to_string())
+                .unwrap_or_else(|| "?".to_string()),
+            reactions,
+        }));
+        y = bubble.bottom();
+    }
+    y += LIST_PADDING + BORDER_WIDTH;
+
+    ChatLayout {
+        width,
+        height: y.ceil(),
+        header_name: source.header_name(),
+        chat_head_style: source.contact.chat_head_style.clone(),
+        rows,
+    }
+}
+
+///
+/// 读取消息和头像中引用的图片，支持 data URL 和资源目录中的文件，不访问网络。
+///
+/// # 参数
+///
+/// - src: 图片的地址。
+/// - assets_dir: `/assets/...` 形式的地址相对的资源目录。
+///
+pub fn load_image_source(src: &str, assets_dir: &Path) -> Option<RgbaImage> {
+    use base64::Engine;
+    let bytes = if let Some(data) = src.strip_prefix("data:") {
+        let (_, encoded) = data.split_once(";base64,")?;
+        base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .ok()?
+    } else {
+        let relative = src.trim_start_matches('/');
+        let relative = relative.strip_prefix("assets/").unwrap_or(relative);
+        std::fs::read(assets_dir.join(relative)).ok()?
+    };
+    image::load_from_memory(&bytes)
+        .ok()
+        .map(|image| image.to_rgba8())
+}
+
+///
+/// 不经过页面，直接把会话渲染为图片，效果接近页面截图。
+///
+/// # 参数
+///
+/// - source: 要渲染的会话。
+/// - assets: 字体与界面图片。
+/// - options: 宽度、缩放和背景。
+/// - load_image: 根据地址读取消息和头像中的图片，例如 [`load_image_source`]。
+///
+pub fn render_chat(
+    source: &ChatSource,
+    assets: &RenderAssets,
+    options: &RenderOptions,
+    load_image: impl Fn(&str) -> Option<RgbaImage>,
+) -> RgbaImage {
+    let images = source
+        .image_sources()
+        .into_iter()
+        .filter_map(|src| load_image(&src).map(|image| (src, image)))
+        .collect::<HashMap<_, _>>();
+    let layout = layout_chat(source, assets, options.width as f32, &images);
+    paint_chat(&layout, assets, options, &images)
+}
+
+/// 渲染并编码为 PNG
+pub fn render_chat_png(
+    source: &ChatSource,
+    assets: &RenderAssets,
+    options: &RenderOptions,
+    load_image: impl Fn(&str) -> Option<RgbaImage>,
+) -> anyhow::Result<Vec<u8>> {
+    encode_png(&render_chat(source, assets, options, load_image))
+}
+
+/// 按排版结果绘制
+fn paint_chat(
+    layout: &ChatLayout,
+    assets: &RenderAssets,
+    options: &RenderOptions,
+    images: &HashMap<String, RgbaImage>,
+) -> RgbaImage {
+    let scale = if options.scale > 0.0 {
+        options.scale
+    } else {
+        1.0
+    };
+    let mut canvas = Canvas {
+        image: RgbaImage::from_pixel(
+            (layout.width * scale).ceil().max(1.0) as u32,
+            (layout.height * scale).ceil().max(1.0) as u32,
+            options.background.unwrap_or(Rgba([0, 0, 0, 0])),
+        ),
+        scale,
+        assets,
+    };
+    canvas.header(layout);
+    canvas.frame(layout);
+    for row in &layout.rows {
+        match row {
+            RowLayout::Status { lines, rect } => {
+                let style = TextStyle::new(STATUS_SIZE, STATUS_LINE_HEIGHT, STATUS_COLOR);
+                for (index, line) in lines.iter().enumerate() {
+                    let width = assets.text_width(style, line);
+                    let x = rect.x + (rect.width - width) / 2.0;
+                    canvas.text(style, line, x, rect.y + index as f32 * STATUS_LINE_HEIGHT);
+                }
+            }
+            RowLayout::TopicEnded { text, rect } => canvas.topic_ended(text, *rect),
+            RowLayout::Message(msg) => canvas.message(msg, images),
+        }
+    }
+    canvas.image
+}
+
+/// 由直线和曲线组成的闭合图形，坐标为 CSS 像素
+#[derive(Default)]
+struct Shape {
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Copy)]
+enum Segment {
+    Line([(f32, f32); 2]),
+    Quad([(f32, f32); 3]),
+    Cubic([(f32, f32); 4]),
+}
+
+impl Segment {
+    fn points(&self) -> &[(f32, f32)] {
+        match self {
+            Segment::Line(points) => points,
+            Segment::Quad(points) => points,
+            Segment::Cubic(points) => points,
+        }
+    }
+
+    fn reversed(self) -> Self {
+        match self {
+            Segment::Line([a, b]) => Segment::Line([b, a]),
+            Segment::Quad([a, b, c]) => Segment::Quad([c, b, a]),
+            Segment::Cubic([a, b, c, d]) => Segment::Cubic([d, c, b, a]),
+        }
+    }
+}
+
+/// 用三次贝塞尔曲线近似四分之一圆
+const ARC_K: f32 = 0.552_284_8;
+
+impl Shape {
+    fn polygon(points: &[(f32, f32)]) -> Self {
+        let mut shape = Shape::default();
+        for (index, point) in points.iter().enumerate() {
+            let next = points[(index + 1) % points.len()];
+            shape.segments.push(Segment::Line([*point, next]));
+        }
+        shape
+    }
+
+    /// 圆角矩形，圆角依次为左上、右上、右下、左下
+    fn rounded_rect(rect: Rect, radii: [f32; 4]) -> Self {
+        let Rect {
+            x,
+            y,
+            width: w,
+            height: h,
+        } = rect;
+        let max = (w.min(h) / 2.0).max(0.0);
+        let [tl, tr, br, bl] = radii.map(|r| r.clamp(0.0, max));
+        let mut shape = Shape::default();
+        let mut line = |a, b| shape.segments.push(Segment::Line([a, b]));
+        line((x + tl, y), (x + w - tr, y));
+        line((x + w, y + tr), (x + w, y + h - br));
+        line((x + w - br, y + h), (x + bl, y + h));
+        line((x, y + h - bl), (x, y + tl));
+        let corners = [
+            (
+                (x + w - tr, y),
+                (x + w, y + tr),
+                tr,
+                (1.0, 0.0),
+                (0.0, -1.0),
+            ),
+            (
+                (x + w, y + h - br),
+                (x + w - br, y + h),
+                br,
+                (0.0, 1.0),
+                (1.0, 0.0),
+            ),
+            (
+                (x + bl, y + h),
+                (x, y + h - bl),
+                bl,
+                (-1.0, 0.0),
+                (0.0, 1.0),
+            ),
+            ((x, y + tl), (x + tl, y), tl, (0.0, -1.0), (-1.0, 0.0)),
+        ];
+        for (from, to, r, out_dir, in_dir) in corners {
+            if r <= 0.0 {
+                continue;
+            }
+            let c1 = (
+                from.0 + out_dir.0 * r * ARC_K,
+                from.1 + out_dir.1 * r * ARC_K,
+            );
+            let c2 = (to.0 + in_dir.0 * r * ARC_K, to.1 + in_dir.1 * r * ARC_K);
+            shape.segments.push(Segment::Cubic([from, c1, c2, to]));
+        }
+        shape
+    }
+
+    fn circle(rect: Rect) -> Self {
+        Self::rounded_rect(rect, [rect.width / 2.0; 4])
+    }
+
+    /// 挖去另一个图形 (两者不能部分重叠)
+    fn subtract(mut self, hole: Shape) -> Self {
+        self.segments
+            .extend(hole.segments.into_iter().map(Segment::reversed));
+        self
+    }
+
+    /// 折线描边，每一段画成一个四边形
+    fn stroke(points: &[(f32, f32)], width: f32) -> Self {
+        let mut shape = Shape::default();
+        for pair in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            let length = ((x1 - x0).powi(2) + (y1 - y0).powi(2))
+                .sqrt()
+                .max(f32::EPSILON);
+            let (nx, ny) = (
+                -(y1 - y0) / length * width / 2.0,
+                (x1 - x0) / length * width / 2.0,
+            );
+            let quad = Shape::polygon(&[
+                (x0 + nx, y0 + ny),
+                (x1 + nx, y1 + ny),
+                (x1 - nx, y1 - ny),
+                (x0 - nx, y0 - ny),
+            ]);
+            shape.segments.extend(quad.segments);
+        }
+        shape
+    }
+}
+
+/// 图形在画布上的覆盖率
+struct Mask {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    coverage: Vec<f32>,
+}
+
+impl Mask {
+    fn new(shape: &Shape, scale: f32) -> Option<Self> {
+        let points = shape.segments.iter().flat_map(|segment| segment.points());
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for (x, y) in points {
+            min_x = min_x.min(*x);
+            min_y = min_y.min(*y);
+            max_x = max_x.max(*x);
+            max_y = max_y.max(*y);
+        }
+        if min_x > max_x {
+            return None;
+        }
+        let x = (min_x * scale).floor() as i32;
+        let y = (min_y * scale).floor() as i32;
+        let width = ((max_x * scale).ceil() as i32 - x).max(1) as u32 + 1;
+        let height = ((max_y * scale).ceil() as i32 - y).max(1) as u32 + 1;
+        let to_point = |(px, py): (f32, f32)| point(px * scale - x as f32, py * scale - y as f32);
+
+        let mut rasterizer = Rasterizer::new(width as usize, height as usize);
+        for segment in &shape.segments {
+            match *segment {
+                Segment::Line([a, b]) => rasterizer.draw_line(to_point(a), to_point(b)),
+                Segment::Quad([a, b, c]) => {
+                    rasterizer.draw_quad(to_point(a), to_point(b), to_point(c))
+                }
+                Segment::Cubic([a, b, c, d]) => {
+                    rasterizer.draw_cubic(to_point(a), to_point(b), to_point(c), to_point(d))
+                }
+            }
+        }
+        let mut coverage = vec![0.0; (width * height) as usize];
+        rasterizer.for_each_pixel(|index, alpha| coverage[index] = alpha);
+        Some(Self {
+            x,
+            y,
+            width,
+            height,
+            coverage,
+        })
+    }
+
+    /// 画布上某个像素的覆盖率
+    fn at(&self, x: i32, y: i32) -> f32 {
+        let (dx, dy) = (x - self.x, y - self.y);
+        if dx < 0 || dy < 0 || dx >= self.width as i32 || dy >= self.height as i32 {
+            return 0.0;
+        }
+        self.coverage[(dy as u32 * self.width + dx as u32) as usize]
+    }
+}
+
+/// 把颜色按覆盖率叠加到像素上
+fn blend(image: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>, coverage: f32) {
+    if x < 0 || y < 0 || x >= image.width() as i32 || y >= image.height() as i32 {
+        return;
+    }
+    let src_a = color.0[3] as f32 / 255.0 * coverage.clamp(0.0, 1.0);
+    if src_a <= 0.0 {
+        return;
+    }
+    let dst = image.get_pixel_mut(x as u32, y as u32);
+    let dst_a = dst.0[3] as f32 / 255.0;
+    let out_a = src_a + dst_a * (1.0 - src_a);
+    for channel in 0..3 {
+        let value = (color.0[channel] as f32 * src_a
+            + dst.0[channel] as f32 * dst_a * (1.0 - src_a))
+            / out_a;
+        dst.0[channel] = value.round().clamp(0.0, 255.0) as u8;
+    }
+    dst.0[3] = (out_a * 255.0).round() as u8;
+}
+
+struct Canvas<'a> {
+    image: RgbaImage,
+    scale: f32,
+    assets: &'a RenderAssets,
+}
+
+impl Canvas<'_> {
+    fn fill_with(&mut self, shape: &Shape, paint: impl Fn(f32, f32) -> Rgba<u8>) {
+        let Some(mask) = Mask::new(shape, self.scale) else {
+            return;
+        };
+        for dy in 0..mask.height as i32 {
+            for dx in 0..mask.width as i32 {
+                let (x, y) = (mask.x + dx, mask.y + dy);
+                let coverage = mask.at(x, y);
+                if coverage > 0.0 {
+                    let color = paint(x as f32 / self.scale, y as f32 / self.scale);
+                    blend(&mut self.image, x, y, color, coverage);
+                }
+            }
+        }
+    }
+
+    fn fill(&mut self, shape: &Shape, color: Rgba<u8>) {
+        self.fill_with(shape, |_, _| color);
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Rgba<u8>) {
+        self.fill(&Shape::rounded_rect(rect, [0.0; 4]), color);
+    }
+
+    ///
+    /// 把图片缩放到指定区域。
+    ///
+    /// `cover` 为真时裁掉超出比例的部分，否则拉伸；`clip` 为图片的裁剪形状。
+    ///
+    fn draw_image(
+        &mut self,
+        source: &RgbaImage,
+        rect: Rect,
+        cover: bool,
+        clip: Option<Shape>,
+        opacity: f32,
+    ) {
+        let x0 = (rect.x * self.scale).round() as i32;
+        let y0 = (rect.y * self.scale).round() as i32;
+        let width = (rect.width * self.scale).round().max(1.0) as u32;
+        let height = (rect.height * self.scale).round().max(1.0) as u32;
+        let cropped;
+        let source = if cover {
+            let (sw, sh) = (source.width() as f32, source.height() as f32);
+            let ratio = (sw / width as f32).min(sh / height as f32);
+            let (cw, ch) = (
+                (width as f32 * ratio).round().max(1.0).min(sw),
+                (height as f32 * ratio).round().max(1.0).min(sh),
+            );
+            cropped = image::imageops::crop_imm(
+                source,
+                ((sw - cw) / 2.0) as u32,
+                ((sh - ch) / 2.0) as u32,
+                cw as u32,
+                ch as u32,
+            )
+            .to_image();
+            &cropped
+        } else {
+            source
+        };
+        let resized = image::imageops::resize(source, width, height, FilterType::Triangle);
+        let mask = clip.and_then(|shape| Mask::new(&shape, self.scale));
+        for (dx, dy, pixel) in resized.enumerate_pixels() {
+            let (x, y) = (x0 + dx as i32, y0 + dy as i32);
+            let coverage = mask.as_ref().map(|mask| mask.at(x, y)).unwrap_or(1.0);
+            blend(&mut self.image, x, y, *pixel, coverage * opacity);
+        }
+    }
+
+    ///
+    /// 画一行文字，`top` 为行框的顶部。
+    ///
+    fn text(&mut self, style: TextStyle, text: &str, x: f32, top: f32) {
+        let assets = self.assets;
+        let mut pen = x;
+        let mut previous: Option<(&FontArc, GlyphId)> = None;
+        for ch in text.chars() {
+            let (font, id) = assets.glyph(ch);
+            let upem = units_per_em(font);
+            let em = style.size / upem;
+            if let Some((prev_font, prev_id)) = previous
+                && std::ptr::eq(prev_font, font)
+            {
+                pen += font.kern_unscaled(prev_id, id) * em;
+            }
+            // 行框内按字体的上下高度垂直居中
+            let ascent = font.ascent_unscaled() * em;
+            let descent = font.descent_unscaled() * em;
+            let baseline = top + (style.line_height - (ascent - descent)) / 2.0 + ascent;
+            let px_scale = PxScale::from(style.size * self.scale * font.height_unscaled() / upem);
+            let offsets: &[f32] = if style.bold { &[0.0, 0.5] } else { &[0.0] };
+            for offset in offsets {
+                let glyph = id.with_scale_and_position(
+                    px_scale,
+                    point((pen + offset) * self.scale, baseline * self.scale),
+                );
+                if let Some(outlined) = font.outline_glyph(glyph) {
+                    let bounds = outlined.px_bounds();
+                    let image = &mut self.image;
+                    outlined.draw(|gx, gy, coverage| {
+                        let x = bounds.min.x as i32 + gx as i32;
+                        let y = bounds.min.y as i32 + gy as i32;
+                        blend(image, x, y, style.color, coverage);
+                    });
+                }
+            }
+            pen += font.h_advance_unscaled(id) * em + style.letter_spacing;
+            previous = Some((font, id));
+        }
+    }
+
+    fn header(&mut self, layout: &ChatLayout) {
+        let bar = Rect::new(0.0, 0.0, layout.width, HEADER_HEIGHT);
+        match self.assets.chat_head_for(&layout.chat_head_style) {
+            Some([left, mid, right]) => {
+                let side_width = |image: &RgbaImage| {
+                    image.width() as f32 * HEADER_HEIGHT / image.height().max(1) as f32
+                };
+                let (left_width, right_width) = (side_width(left), side_width(right));
+                self.draw_image(
+                    left,
+                    Rect::new(0.0, 0.0, left_width, HEADER_HEIGHT),
+                    false,
+                    None,
+                    1.0,
+                );
+                let mid_width = (layout.width - left_width - right_width).max(0.0);
+                if mid_width > 0.0 {
+                    let rect = Rect::new(left_width, 0.0, mid_width, HEADER_HEIGHT);
+                    self.draw_image(mid, rect, false, None, 1.0);
+                }
+                let rect = Rect::new(layout.width - right_width, 0.0, right_width, HEADER_HEIGHT);
+                self.draw_image(right, rect, false, None, 1.0);
+            }
+            None => self.fill(
+                &Shape::rounded_rect(bar, [12.0, 12.0, 0.0, 0.0]),
+                HEADER_FALLBACK_COLOR,
+            ),
+        }
+
+        let style = TextStyle::new(18.0, 28.0, WHITE).bold();
+        self.text(
+            style,
+            &layout.header_name,
+            32.0,
+            (HEADER_HEIGHT - 28.0) / 2.0,
+        );
+
+        // 右侧的菜单按钮
+        let center_x = layout.width - LIST_PADDING - 16.0;
+        let center_y = HEADER_HEIGHT / 2.0;
+        self.fill(
+            &Shape::circle(Rect::new(center_x - 16.0, center_y - 16.0, 32.0, 32.0)),
+            MENU_BUTTON_COLOR,
+        );
+        for offset in [-6.0, 0.0, 6.0] {
+            let dot = Rect::new(center_x + offset - 2.0, center_y - 2.0, 4.0, 4.0);
+            self.fill(&Shape::circle(dot), MENU_DOT_COLOR);
+        }
+    }
+
+    /// 聊天主体的边框和顶部的装饰
+    fn frame(&mut self, layout: &ChatLayout) {
+        let top = HEADER_HEIGHT + HEADER_GAP;
+        let width = layout.width;
+        let outer = Rect::new(0.0, top, width, layout.height - top);
+        let inner = Rect::new(
+            BORDER_WIDTH,
+            top - 1.0,
+            width - BORDER_WIDTH * 2.0,
+            layout.height - top + 1.0 - BORDER_WIDTH,
+        );
+        let inner_radius = BORDER_RADIUS - BORDER_WIDTH;
+        let border = Shape::rounded_rect(outer, [0.0, 0.0, BORDER_RADIUS, BORDER_RADIUS]).subtract(
+            Shape::rounded_rect(inner, [0.0, 0.0, inner_radius, inner_radius]),
+        );
+        self.fill(&border, BORDER_COLOR);
+
+        // 顶部的横线在右侧向下凹陷
+        self.fill_rect(
+            Rect::new(0.0, top, (width - 264.0).max(0.0), BORDER_WIDTH),
+            BORDER_COLOR,
+        );
+        self.fill_rect(
+            Rect::new(width - 32.0, top, 32.0, BORDER_WIDTH),
+            BORDER_COLOR,
+        );
+        let half = BORDER_WIDTH / 2.0;
+        let notch = Shape::stroke(
+            &[
+                (width - 264.0, top + half),
+                (width - 248.0, top + 6.0),
+                (width - 48.0, top + 6.0),
+                (width - 32.0, top + half),
+            ],
+            BORDER_WIDTH,
+        );
+        self.fill(&notch, BORDER_COLOR);
+
+        let bar_width = 64.0;
+        let first_x = width - 44.0 - bar_width * 3.0 - 8.0 * 2.0;
+        for (index, color) in DECORATION_COLORS.into_iter().enumerate() {
+            let x = first_x + index as f32 * (bar_width + 8.0);
+            let points = match index {
+                0 => [
+                    (x, top),
+                    (x + bar_width, top),
+                    (x + bar_width, top + 2.0),
+                    (x + 8.0, top + 2.0),
+                ],
+                2 => [
+                    (x, top),
+                    (x + bar_width, top),
+                    (x + bar_width - 8.0, top + 2.0),
+                    (x, top + 2.0),
+                ],
+                _ => [
+                    (x, top),
+                    (x + bar_width, top),
+                    (x + bar_width, top + 2.0),
+                    (x, top + 2.0),
+                ],
+            };
+            self.fill(&Shape::polygon(&points), color);
+        }
+    }
+
+    fn topic_ended(&mut self, text: &str, rect: Rect) {
+        let mut style = TextStyle::new(NAME_SIZE, NAME_LINE_HEIGHT, TOPIC_ENDED_COLOR).bold();
+        style.letter_spacing = NAME_SIZE * 0.025;
+        let text_width = self.assets.text_width(style, text) + 24.0;
+        let line_width = ((rect.width - text_width) / 2.0).max(0.0);
+        let line_y = rect.y + (rect.height - 2.0) / 2.0;
+        self.fill_rect(
+            Rect::new(rect.x, line_y, line_width, 2.0),
+            TOPIC_ENDED_COLOR,
+        );
+        self.fill_rect(
+            Rect::new(rect.right() - line_width, line_y, line_width, 2.0),
+            TOPIC_ENDED_COLOR,
+        );
+        self.text(style, text, rect.x + line_width + 12.0, rect.y);
+    }
+
+    fn message(&mut self, msg: &MessageLayout, images: &HashMap<String, RgbaImage>) {
+        if let Some((name, rect)) = &msg.sender_name {
+            let style = TextStyle::new(NAME_SIZE, NAME_LINE_HEIGHT, NAME_COLOR);
+            self.text(style, name, rect.x, rect.y);
+        }
+        if let Some(avatar) = msg.avatar {
+            self.avatar(msg, avatar, images);
+        }
+
+        let bubble = msg.bubble;
+        let (color, text_color) = if msg.on_right {
+            (RIGHT_BUBBLE_COLOR, BLACK)
+        } else {
+            (LEFT_BUBBLE_COLOR, WHITE)
+        };
+        if msg.has_bubble {
+            // 发送者一侧的上角是尖的，并带一个小尾巴
+            let (radii, tail) = if msg.on_right {
+                let x = bubble.right() - 1.0;
+                (
+                    [BUBBLE_RADIUS, 0.0, BUBBLE_RADIUS, BUBBLE_RADIUS],
+                    Shape {
+                        segments: vec![
+                            Segment::Line([(x, bubble.y), (x + BUBBLE_TAIL_WIDTH, bubble.y)]),
+                            Segment::Quad([
+                                (x + BUBBLE_TAIL_WIDTH, bubble.y),
+                                (x, bubble.y),
+                                (x, bubble.y + BUBBLE_TAIL_HEIGHT),
+                            ]),
+                            Segment::Line([(x, bubble.y + BUBBLE_TAIL_HEIGHT), (x, bubble.y)]),
+                        ],
+                    },
+                )
+            } else {
+                let x = bubble.x - BUBBLE_TAIL_WIDTH + 1.0;
+                let right = x + BUBBLE_TAIL_WIDTH;
+                (
+                    [0.0, BUBBLE_RADIUS, BUBBLE_RADIUS, BUBBLE_RADIUS],
+                    Shape {
+                        segments: vec![
+                            Segment::Line([(right, bubble.y), (x, bubble.y)]),
+                            Segment::Quad([
+                                (x, bubble.y),
+                                (right, bubble.y),
+                                (right, bubble.y + BUBBLE_TAIL_HEIGHT),
+                            ]),
+                            Segment::Line([
+                                (right, bubble.y + BUBBLE_TAIL_HEIGHT),
+                                (right, bubble.y),
+                            ]),
+                        ],
+                    },
+                )
+            };
+            self.fill(&tail, color);
+            let shape = Shape::rounded_rect(bubble, radii);
+            if msg.on_right {
+                // 自己的气泡带 4px 的浅色网格
+                self.fill_with(&shape, |x, y| {
+                    let (local_x, local_y) = (x - bubble.x, y - bubble.y);
+                    if local_x.rem_euclid(4.0) < 1.0 || local_y.rem_euclid(4.0) < 1.0 {
+                        RIGHT_BUBBLE_GRID_COLOR
+                    } else {
+                        RIGHT_BUBBLE_COLOR
+                    }
+                });
+            } else {
+                self.fill(&shape, color);
+            }
+        }
+
+        match &msg.content {
+            BubbleContent::Text(lines) => {
+                let style = TextStyle::new(TEXT_SIZE, TEXT_LINE_HEIGHT, text_color);
+                for (index, line) in lines.iter().enumerate() {
+                    self.text(
+                        style,
+                        line,
+                        bubble.x + BUBBLE_PADDING_X,
+                        bubble.y + BUBBLE_PADDING_Y + index as f32 * TEXT_LINE_HEIGHT,
+                    );
+                }
+            }
+            BubbleContent::Media { src, rect } => {
+                if let Some(image) = images.get(src) {
+                    // 图片有 4px 的圆角，表情没有
+                    let clip = (!msg.has_bubble).then(|| Shape::rounded_rect(*rect, [4.0; 4]));
+                    self.draw_image(image, *rect, false, clip, 1.0);
+                }
+            }
+        }
+
+        let style = TextStyle::new(TEXT_SIZE, REACTION_LINE_HEIGHT, REACTION_TEXT_COLOR);
+        for (label, rect) in &msg.reactions {
+            self.fill(
+                &Shape::rounded_rect(*rect, [rect.height / 2.0; 4]),
+                REACTION_COLOR,
+            );
+            self.text(
+                style,
+                label,
+                rect.x + REACTION_PADDING_X,
+                rect.y + REACTION_PADDING_Y,
+            );
+        }
+    }
+
+    fn avatar(&mut self, msg: &MessageLayout, avatar: Rect, images: &HashMap<String, RgbaImage>) {
+        if let Some(frame) = &self.assets.avatar_frame {
+            let rect = Rect::new(
+                avatar.x - AVATAR_FRAME_OFFSET,
+                avatar.y - AVATAR_FRAME_OFFSET - AVATAR_FRAME_NUDGE,
+                AVATAR_FRAME_SIZE,
+                AVATAR_FRAME_SIZE,
+            );
+            self.draw_image(frame, rect, false, None, 0.9);
+        }
+        self.fill(&Shape::circle(avatar), AVATAR_COLOR);
+        match images.get(&msg.avatar_src) {
+            Some(image) => self.draw_image(image, avatar, true, Some(Shape::circle(avatar)), 1.0),
+            None => {
+                let style = TextStyle::new(24.0, 32.0, AVATAR_TEXT_COLOR).bold();
+                let width = self.assets.text_width(style, &msg.avatar_fallback);
+                let x = avatar.x + (avatar.width - width) / 2.0;
+                let y = avatar.y + (avatar.height - 32.0) / 2.0;
+                self.text(style, &msg.avatar_fallback, x, y);
+            }
+        }
+        let inner = Rect::new(
+            avatar.x + 1.0,
+            avatar.y + 1.0,
+            avatar.width - 2.0,
+            avatar.height - 2.0,
+        );
+        let ring = Shape::circle(avatar).subtract(Shape::circle(inner));
+        self.fill(&ring, AVATAR_BORDER_COLOR);
+    }
+}
@@ -21,6 +21,7 @@ pub fn InputBar(
     on_send_status: EventHandler<String>,
     on_send_image: EventHandler<(String, bool)>,
     on_send_sticker: EventHandler<(String, bool)>,
+    on_send_mission: EventHandler<()>,
     stickers: ReadSignal<Vec<String>>,
     on_add_sticker: EventHandler<String>,
     menu_close_token: ReadSignal<usize>,
@@ -110,7 +111,7 @@ pub fn InputBar(
             if let Some((x, y)) = plus_menu() {
                 div {
                     class: "fixed z-[100] bg-[#2b2b2b] border border-gray-600 rounded shadow-xl py-1 w-36",
-                    style: "{menu_style(x, y, 144, 56)}",
+                    style: "{menu_style(x, y, 144, 96)}",
                     onclick: |e| e.stop_propagation(),
                     div { class: "px-4 py-2 hover:bg-[#3a3a3a] cursor-pointer text-white text-sm transition-colors relative overflow-hidden",
                         "发送图片……"
@@ -150,6 +151,14 @@ pub fn InputBar(
                             },
                         }
                     }
+                    div {
+                        class: "px-4 py-2 hover:bg-[#3a3a3a] cursor-pointer text-white text-sm transition-colors",
+                        onclick: move |_| {
+                            on_send_mission.call(());
+                            plus_menu.set(None);
+                        },
+                        "发送任务……"
+                    }
                 }
             }
 
@@ -1,15 +1,28 @@
+use crate::components::baker::batch_capture::BatchCapturePage;
+use crate::components::baker::bulk_editor::BulkEditorPage;
 use crate::components::baker::capture::CapturePage;
-use crate::components::baker::chat_area::{ChatArea, PendingTyping, ReplayTypingPhase};
+use crate::components::baker::chat_area::{ChatArea, PendingTyping};
 use crate::components::baker::modals::{
-    NewChatModal, NewChatSelection, Notice, OpsSelection, ProfileModal, ReplayIntervalMode,
-    ReplaySettings, ReplaySettingsModal, TutorialModal, UpdateAvailableModal,
+    NewChatModal, NewChatSelection, Notice, OpsSelection, ProfileModal, ReplayPlaylistModal,
+    ReplaySettingsModal, TutorialModal, UpdateAvailableModal,
 };
+use crate::components::baker::presenter::PresenterPage;
+use crate::components::baker::replay::{
+    PlaylistSession, ReplayAction, ReplayEnd, ReplayPlayer, ReplayPlaylist, ReplaySettings,
+    TOPIC_ENDED_ANIMATE_MS, TOPIC_ENDED_MESSAGE_ID, replay_range,
+};
+use crate::components::baker::replay_bar::{PlaylistBar, ReplayControlBar};
+use crate::components::baker::replay_capture::ReplayCapturePage;
+use crate::components::baker::script_page::{NewScriptPage, ScriptPage};
 use crate::components::baker::settings::SettingsPage;
 use crate::components::baker::sidebar::Sidebar;
+use crate::components::baker::sound::play_sound;
 use crate::components::baker::storage::v2::{
-    BackgroundMode, ChatHeadStyle, Contact, Message, MessageKind, MessageReaction,
+    ChatHeadStyle, Contact, Message, MessageKind, MessageReaction, MessageReplayOverrides, Mission,
+    SoundEvent,
 };
 use crate::components::baker::use_synced_field;
+use crate::components::baker::{background_style, message_preview};
 use chrono::Utc;
 use dioxus::prelude::*;
 #[cfg(target_arch = "wasm32")]
@@ -22,27 +35,6 @@ use std::time::Duration;
 use tokio::time::sleep;
 use uuid::Uuid;
 
-const MESSAGE_SOUND: Asset = asset!("/assets/sound/message.mp3");
-const MESSAGE_SELF_SOUND: Asset = asset!("/assets/sound/message-self.mp3");
-
-fn play_message_sound(is_self: bool) {
-    let sound_src = if is_self {
-        MESSAGE_SELF_SOUND.to_string()
-    } else {
-        MESSAGE_SOUND.to_string()
-    };
-    spawn(async move {
-        let script = format!(
-            r#"
-            const audio = new Audio("{sound_src}");
-            audio.volume = 0.5;
-            audio.play();
-        "#
-        );
-        let _ = document::eval(&script).await;
-    });
-}
-
 #[derive(Clone, PartialEq)]
 struct UpdateInfo {
     version: String,
@@ -68,12 +60,12 @@ struct ReplayContext {
 }
 
 #[cfg(target_arch = "wasm32")]
-async fn sleep_ms(ms: u64) {
+pub(super) async fn sleep_ms(ms: u64) {
     TimeoutFuture::new(ms.min(u32::MAX as u64) as u32).await;
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-async fn sleep_ms(ms: u64) {
+pub(super) async fn sleep_ms(ms: u64) {
     sleep(Duration::from_millis(ms)).await;
 }
 
@@ -137,6 +129,201 @@ fn schedule_reaction_animate_off_in_list(mut list: Signal<Vec<Message>>, msg_id:
     });
 }
 
+/// 执行回放时间线中的一个动作, 包括声音、滚动和关闭动画等副作用
+fn drive_replay_action(
+    action: &ReplayAction,
+    source: &[Message],
+    user_id: &str,
+    mut messages: Signal<Vec<Message>>,
+    mut pending: Signal<Option<PendingTyping>>,
+    mut need_to_scroll_down: Signal<bool>,
+) {
+    if action.changes_messages() {
+        messages.with_mut(|list| action.apply_to_messages(source, user_id, list));
+    }
+    let next_pending = action.pending_after(source, pending());
+    if next_pending != pending() {
+        pending.set(next_pending);
+    }
+    match action {
+        ReplayAction::ShowTyping { index } | ReplayAction::Show { index } => {
+            schedule_animate_off_in_list(messages, source[*index].id.clone());
+        }
+        ReplayAction::ApplyReactions { index } => {
+            schedule_reaction_animate_off_in_list(messages, source[*index].id.clone());
+            play_sound(SoundEvent::Reaction, true);
+        }
+        ReplayAction::TopicEnded { .. } => {
+            schedule_animate_off_in_list_with_delay(
+                messages,
+                TOPIC_ENDED_MESSAGE_ID.to_string(),
+                TOPIC_ENDED_ANIMATE_MS,
+            );
+            play_sound(SoundEvent::TopicEnded, true);
+        }
+        ReplayAction::PlaySound { event } => play_sound(*event, true),
+        ReplayAction::Reveal { .. } | ReplayAction::ClearPending => {}
+    }
+    if action.scrolls() {
+        need_to_scroll_down.set(true);
+    }
+}
+
+/// 回放用到的信号; 播放、暂停和跳转都会让正在执行的回放任务失效并按需重新开始
+#[derive(Clone, Copy)]
+pub(super) struct ReplayHandles {
+    pub(super) player: Signal<Option<ReplayPlayer>>,
+    pub(super) token: Signal<usize>,
+    pub(super) messages: Signal<Vec<Message>>,
+    pub(super) pending: Signal<Option<PendingTyping>>,
+    pub(super) need_to_scroll_down: Signal<bool>,
+}
+
+impl ReplayHandles {
+    /// 让正在执行的回放任务停下
+    pub(super) fn stop(mut self) {
+        if let Some(player) = self.player.write().as_mut() {
+            player.interrupt_wait(Utc::now().timestamp_millis());
+        }
+        let token = *self.token.read() + 1;
+        self.token.set(token);
+    }
+
+    /// 从当前位置开始按时间线执行, 直到暂停、跳转、退出或播放完毕
+    fn play(self) {
+        self.play_until(None);
+    }
+
+    /// 同 `play`, 但执行到时间线的 `stop` 位置就停下
+    pub(super) fn play_until(mut self, stop: Option<usize>) {
+        self.stop();
+        let token = *self.token.read();
+        spawn(async move {
+            loop {
+                let delay_ms = match self.player.write().as_mut() {
+                    Some(player) if stop.is_some_and(|stop| player.cursor >= stop) => break,
+                    Some(player) if !player.paused => match player.next_event() {
+                        Some((delay_ms, _)) => {
+                            player.begin_wait(Utc::now().timestamp_millis());
+                            delay_ms
+                        }
+                        None => break,
+                    },
+                    _ => return,
+                };
+                if delay_ms > 0 {
+                    sleep_ms(delay_ms).await;
+                }
+                if *self.token.read() != token {
+                    return;
+                }
+                let mut player = self.player.write();
+                let Some(player) = player.as_mut() else {
+                    return;
+                };
+                let action = player.timeline.events[player.cursor].action.clone();
+                player.cursor += 1;
+                drive_replay_action(
+                    &action,
+                    &player.source,
+                    &player.user_id,
+                    self.messages,
+                    self.pending,
+                    self.need_to_scroll_down,
+                );
+            }
+            self.pending.set(None);
+        });
+    }
+
+    /// 跳转到时间线中的某个位置, 不播放声音和入场动画; 暂停状态保持不变
+    pub(super) fn seek(mut self, cursor: usize) {
+        self.stop();
+        let paused = {
+            let mut player = self.player.write();
+            let Some(player) = player.as_mut() else {
+                return;
+            };
+            player.seek(cursor);
+            let (messages, pending) = player.snapshot();
+            self.messages.set(messages);
+            self.pending.set(pending);
+            player.paused
+        };
+        self.need_to_scroll_down.set(true);
+        if !paused {
+            self.play();
+        }
+    }
+
+    fn toggle_pause(mut self) {
+        let Some((paused, finished)) = self
+            .player
+            .read()
+            .as_ref()
+            .map(|player| (player.paused, player.is_finished()))
+        else {
+            return;
+        };
+        if let Some(player) = self.player.write().as_mut() {
+            player.paused = !paused && !finished;
+        }
+        if finished {
+            self.seek(0);
+        } else if paused {
+            self.play();
+        } else {
+            self.stop();
+        }
+    }
+
+    /// 暂停并前进或后退一条消息
+    fn step(mut self, forward: bool) {
+        let target = {
+            let mut player = self.player.write();
+            let Some(player) = player.as_mut() else {
+                return;
+            };
+            player.paused = true;
+            if forward {
+                player.next_boundary()
+            } else {
+                player.previous_boundary()
+            }
+        };
+        self.seek(target);
+    }
+
+    /// 跳转到前 `count` 条消息播放完之后
+    fn jump(self, count: usize) {
+        let target = self
+            .player
+            .read()
+            .as_ref()
+            .map(|player| player.position_after(count));
+        if let Some(target) = target {
+            self.seek(target);
+        }
+    }
+
+    fn set_speed(mut self, speed: f64) {
+        // 先按原来的速度记下已经等过的时间
+        self.stop();
+        let playing = {
+            let mut player = self.player.write();
+            let Some(player) = player.as_mut() else {
+                return;
+            };
+            player.speed = speed;
+            !player.paused && !player.is_finished()
+        };
+        // 按新速度等待下一个动作剩下的部分
+        if playing {
+            self.play();
+        }
+    }
+}
+
 pub fn is_remote_newer(local: &str, remote: &str) -> anyhow::Result<bool> {
     use semver::Version;
 
@@ -199,6 +386,11 @@ pub fn BakerLayout() -> Element {
     let mut replay_messages = use_signal(Vec::<Message>::new);
     let mut replay_token = use_signal(|| 0usize);
     let mut replay_pending = use_signal(|| Option::<PendingTyping>::None);
+    let mut replay_player = use_signal(|| Option::<ReplayPlayer>::None);
+    let mut show_playlist = use_signal(|| false);
+    let mut playlist = use_signal(|| Option::<PlaylistSession>::None);
+    // 会话之间的停顿结束后切换到下一个会话; 暂停、跳过和退出都会让正在等待的切换失效
+    let mut playlist_token = use_signal(|| 0usize);
     let mut update_info = use_signal(|| Option::<UpdateInfo>::None);
     let mut update_checked = use_signal(|| false);
     let mut show_notice = use_signal(|| !app_state.read().showed_notice);
@@ -240,34 +432,46 @@ pub fn BakerLayout() -> Element {
         });
     });
 
-    let mut add_message = move |sender_id: String, content: String, kind: MessageKind| {
-        let current_contact_id = match selected_contact_id() {
-            Some(id) => id,
-            None => return,
-        };
+    let mut push_message =
+        move |sender_id: String, content: String, kind: MessageKind, mission: Option<Mission>| {
+            let current_contact_id = match selected_contact_id() {
+                Some(id) => id,
+                None => return,
+            };
 
-        let is_self = sender_id == app_state.read().user_profile.id;
-        let new_id = {
-            let mut state = app_state.write();
-            let messages = state
-                .messages
-                .entry(current_contact_id.clone())
-                .or_default();
-            let new_id = Uuid::new_v4().to_string();
-
-            messages.push(Message {
-                id: new_id.clone(),
-                sender_id,
-                content,
-                kind,
-                animate: true,
-                animate_reactions: false,
-                reactions: Vec::new(),
-            });
-            new_id
+            let sound = if matches!(kind, MessageKind::Status) {
+                SoundEvent::Status
+            } else if sender_id == app_state.read().user_profile.id {
+                SoundEvent::Outgoing
+            } else {
+                SoundEvent::Incoming
+            };
+            let new_id = {
+                let mut state = app_state.write();
+                let messages = state
+                    .messages
+                    .entry(current_contact_id.clone())
+                    .or_default();
+                let new_id = Uuid::new_v4().to_string();
+
+                messages.push(Message {
+                    id: new_id.clone(),
+                    sender_id,
+                    content,
+                    kind,
+                    animate: true,
+                    animate_reactions: false,
+                    reactions: Vec::new(),
+                    replay: None,
+                    mission,
+                });
+                new_id
+            };
+            play_sound(sound, false);
+            schedule_animate_off_in_state(app_state, current_contact_id, new_id);
         };
-        play_message_sound(is_self);
-        schedule_animate_off_in_state(app_state, current_contact_id, new_id);
+    let mut add_message = move |sender_id: String, content: String, kind: MessageKind| {
+        push_message(sender_id, content, kind, None);
     };
 
     let handle_send = move |content: String| {
@@ -300,6 +504,15 @@ pub fn BakerLayout() -> Element {
     let mut handle_send_sticker_other = move |sender_id: String, sticker_src: String| {
         add_message(sender_id, sticker_src, MessageKind::Sticker);
     };
+    let handle_send_mission = move |(sender_id, mission): (Option<String>, Mission)| {
+        let sender_id = sender_id.unwrap_or_else(|| app_state.read().user_profile.id.clone());
+        push_message(
+            sender_id,
+            String::new(),
+            MessageKind::Mission,
+            Some(mission),
+        );
+    };
     let handle_add_sticker = move |sticker_src: String| {
         let trimmed = sticker_src.trim();
         if trimmed.is_empty() {
@@ -439,6 +652,29 @@ pub fn BakerLayout() -> Element {
         }
     };
 
+    let edit_mission = move |(msg_id, mission): (String, Mission)| {
+        if let Some(contact_id) = selected_contact_id() {
+            let mut state = app_state.write();
+            if let Some(msgs) = state.messages.get_mut(&contact_id)
+                && let Some(msg) = msgs.iter_mut().find(|m| m.id == msg_id)
+            {
+                msg.mission = Some(mission);
+            }
+        }
+    };
+
+    let edit_replay_overrides =
+        move |(msg_id, overrides): (String, Option<MessageReplayOverrides>)| {
+            if let Some(contact_id) = selected_contact_id() {
+                let mut state = app_state.write();
+                if let Some(msgs) = state.messages.get_mut(&contact_id)
+                    && let Some(msg) = msgs.iter_mut().find(|m| m.id == msg_id)
+                {
+                    msg.replay = overrides;
+                }
+            }
+        };
+
     let add_reaction = move |(msg_id, reaction): (String, String)| {
         let reaction = reaction.trim().to_string();
         if reaction.is_empty() {
@@ -462,6 +698,7 @@ pub fn BakerLayout() -> Element {
                 }
             }
             if should_animate {
+                play_sound(SoundEvent::Reaction, false);
                 schedule_reaction_animate_off_in_state(app_state, contact_id, msg_id_value);
             }
         }
@@ -509,6 +746,8 @@ pub fn BakerLayout() -> Element {
                             animate: true,
                             animate_reactions: false,
                             reactions: Vec::new(),
+                            replay: None,
+                            mission: None,
                         },
                     );
                     new_id
@@ -532,11 +771,16 @@ pub fn BakerLayout() -> Element {
         let mut replay_messages = replay_messages;
         let mut replay_token = replay_token;
         let mut replay_pending = replay_pending;
+        let mut replay_player = replay_player;
         move || {
             replay_token.set(replay_token() + 1);
             replay_active.set(None);
             replay_messages.set(Vec::new());
             replay_pending.set(None);
+            replay_player.set(None);
+            let next = *playlist_token.peek() + 1;
+            playlist_token.set(next);
+            playlist.set(None);
         }
     };
 
@@ -561,6 +805,11 @@ pub fn BakerLayout() -> Element {
             if let Some(contact_id) = selected_contact_id() {
                 let mut state = app_state.write();
                 state.messages.remove(&contact_id);
+                state.replay_settings.remove(&contact_id);
+                state
+                    .replay_playlist
+                    .contact_ids
+                    .retain(|id| *id != contact_id);
                 state.contacts.retain(|c| c.id != contact_id);
                 selected_contact_id.set(None);
                 cancel_replay();
@@ -599,42 +848,52 @@ pub fn BakerLayout() -> Element {
             replay_active.set(None);
             replay_messages.set(Vec::new());
             replay_pending.set(None);
+            replay_player.set(None);
+            let next = *playlist_token.peek() + 1;
+            playlist_token.set(next);
+            playlist.set(None);
         }
     });
 
     let need_to_scroll_down = use_signal(|| false);
 
+    let replay = ReplayHandles {
+        player: replay_player,
+        token: replay_token,
+        messages: replay_messages,
+        pending: replay_pending,
+        need_to_scroll_down,
+    };
+
     let mut start_replay = {
         let mut replay_messages = replay_messages;
         let mut replay_active = replay_active;
-        let mut replay_token = replay_token;
         let mut replay_pending = replay_pending;
-        let app_state = app_state;
+        let mut replay_player = replay_player;
+        let mut app_state = app_state;
         let selected_contact_id = selected_contact_id;
-        let mut need_to_scroll_down = need_to_scroll_down;
-        move |start_msg_id: String, settings: ReplaySettings| {
+        move |start_msg_id: String, settings: ReplaySettings, end: ReplayEnd| {
             let contact_id = match selected_contact_id() {
                 Some(id) => id,
                 None => return,
             };
+            app_state
+                .write()
+                .replay_settings
+                .insert(contact_id.clone(), settings.clone());
             let all_messages = app_state
                 .read()
                 .messages
                 .get(&contact_id)
                 .cloned()
                 .unwrap_or_default();
-            let start_index = all_messages
-                .iter()
-                .position(|m| m.id == start_msg_id)
-                .unwrap_or(0);
-            let prev_sender_id = if start_index > 0 {
-                Some(all_messages[start_index - 1].sender_id.clone())
+            let range = replay_range(&all_messages, &start_msg_id, &end);
+            let prev_sender_id = if range.start > 0 {
+                Some(all_messages[range.start - 1].sender_id.clone())
             } else {
                 None
             };
 
-            let token = replay_token() + 1;
-            replay_token.set(token);
             replay_messages.set(Vec::new());
             replay_pending.set(None);
             replay_active.set(Some(ReplayContext {
@@ -642,143 +901,156 @@ pub fn BakerLayout() -> Element {
                 prev_sender_id,
             }));
 
-            let mut replay_messages_async = replay_messages;
-            let replay_token_async = replay_token;
-            let mut replay_pending_async = replay_pending;
-            let settings_clone = settings.clone();
+            let source = all_messages[range].to_vec();
             let user_id = app_state.read().user_profile.id.clone();
-            spawn(async move {
-                for msg in all_messages.into_iter().skip(start_index) {
-                    if replay_token_async() != token {
-                        break;
-                    }
-                    if settings_clone.gap_ms > 0 {
-                        sleep_ms(settings_clone.gap_ms).await;
-                    }
-                    if matches!(msg.kind, MessageKind::Status) {
-                        replay_messages_async.with_mut(|list| {
-                            list.push(Message {
-                                animate: true,
-                                animate_reactions: false,
-                                ..msg.clone()
-                            });
-                        });
-                        let is_self = msg.sender_id == user_id;
-                        play_message_sound(is_self);
-                        schedule_animate_off_in_list(replay_messages_async, msg.id);
-                        need_to_scroll_down.set(true);
-                        continue;
-                    }
-                    let typing_ms = match msg.kind {
-                        MessageKind::Image | MessageKind::Sticker => settings_clone.fixed_ms,
-                        _ => match settings_clone.mode {
-                            ReplayIntervalMode::Fixed => settings_clone.fixed_ms,
-                            ReplayIntervalMode::PerChar => {
-                                let len = msg.content.chars().count() as u64;
-                                len.saturating_mul(settings_clone.per_char_ms)
-                            }
-                        },
-                    };
-                    let is_other = msg.sender_id != user_id;
-                    if is_other {
-                        let msg_id = msg.id.clone();
-                        replay_messages_async.with_mut(|list| {
-                            list.push(Message {
-                                animate: true,
-                                animate_reactions: false,
-                                reactions: Vec::new(),
-                                ..msg.clone()
-                            });
-                        });
-                        replay_pending_async.set(Some(PendingTyping {
-                            id: msg_id.clone(),
-                            phase: ReplayTypingPhase::Typing,
-                        }));
-                        schedule_animate_off_in_list(replay_messages_async, msg_id.clone());
-                        if typing_ms > 0 {
-                            sleep_ms(typing_ms).await;
-                        }
-                        replay_pending_async.set(Some(PendingTyping {
-                            id: msg_id,
-                            phase: ReplayTypingPhase::Reveal,
-                        }));
-                        need_to_scroll_down.set(true);
-                        play_message_sound(false);
-                        sleep_ms(200).await;
-                        replay_pending_async.set(None);
-                        if !msg.reactions.is_empty() {
-                            if settings_clone.gap_ms > 0 {
-                                sleep_ms(settings_clone.gap_ms).await;
-                            }
-                            let reactions = msg.reactions.clone();
-                            let msg_id = msg.id.clone();
-                            replay_messages_async.with_mut(|list| {
-                                if let Some(item) = list.iter_mut().find(|m| m.id == msg_id) {
-                                    item.reactions = reactions;
-                                    item.animate_reactions = true;
-                                }
-                            });
-                            schedule_reaction_animate_off_in_list(replay_messages_async, msg_id);
-                        }
-                    } else {
-                        replay_pending_async.set(None);
-                        if typing_ms > 0 {
-                            sleep_ms(typing_ms).await;
-                        }
-                        replay_messages_async.with_mut(|list| {
-                            list.push(Message {
-                                animate: true,
-                                animate_reactions: false,
-                                reactions: Vec::new(),
-                                ..msg.clone()
-                            });
-                        });
-                        need_to_scroll_down.set(true);
-                        play_message_sound(true);
-                        schedule_animate_off_in_list(replay_messages_async, msg.id.clone());
-                        if !msg.reactions.is_empty() {
-                            if settings_clone.gap_ms > 0 {
-                                sleep_ms(settings_clone.gap_ms).await;
-                            }
-                            let reactions = msg.reactions.clone();
-                            let msg_id = msg.id.clone();
-                            replay_messages_async.with_mut(|list| {
-                                if let Some(item) = list.iter_mut().find(|m| m.id == msg_id) {
-                                    item.reactions = reactions;
-                                    item.animate_reactions = true;
-                                }
-                            });
-                            schedule_reaction_animate_off_in_list(replay_messages_async, msg_id);
-                        }
-                    }
-                }
-                if replay_token_async() == token {
-                    let topic_end_id = Uuid::new_v4().to_string();
-                    replay_messages_async.with_mut(|list| {
-                        list.push(Message {
-                            id: topic_end_id.clone(),
-                            sender_id: user_id.clone(),
-                            content: "话题结束，暂无新话题".to_string(),
-                            kind: MessageKind::TopicEnded,
-                            animate: true,
-                            animate_reactions: false,
-                            reactions: Vec::new(),
-                        });
-                    });
-                    need_to_scroll_down.set(true);
-                    schedule_animate_off_in_list_with_delay(
-                        replay_messages_async,
-                        topic_end_id,
-                        900,
-                    );
-                }
-                replay_pending_async.set(None);
-            });
+            replay_player.set(Some(ReplayPlayer::new(source, user_id, &settings)));
+            replay.play();
         }
     };
 
+    // 播放列表中的会话按各自上次的回放设置从头回放
+    let mut play_playlist_item = move |contact_id: String| {
+        let (first_msg_id, settings) = {
+            let state = app_state.read();
+            let first_msg_id = state
+                .messages
+                .get(&contact_id)
+                .and_then(|msgs| msgs.first())
+                .map(|msg| msg.id.clone())
+                .unwrap_or_default();
+            let settings = state
+                .replay_settings
+                .get(&contact_id)
+                .cloned()
+                .unwrap_or_default();
+            (first_msg_id, settings)
+        };
+        selected_contact_id.set(Some(contact_id));
+        start_replay(first_msg_id, settings, ReplayEnd::Last);
+    };
+
+    let mut advance_playlist = move || {
+        let next = *playlist_token.peek() + 1;
+        playlist_token.set(next);
+        let next = playlist
+            .write()
+            .as_mut()
+            .and_then(|session| session.advance().map(str::to_string));
+        match next {
+            Some(contact_id) => play_playlist_item(contact_id),
+            None => playlist.set(None),
+        }
+    };
+
+    let mut schedule_playlist_next = move || {
+        let token = *playlist_token.peek() + 1;
+        playlist_token.set(token);
+        let Some(pause_ms) = playlist.peek().as_ref().map(|session| session.pause_ms) else {
+            return;
+        };
+        spawn(async move {
+            sleep_ms(pause_ms).await;
+            // 停顿期间重播了当前会话时不切换
+            let finished = replay_player
+                .peek()
+                .as_ref()
+                .is_some_and(ReplayPlayer::is_finished);
+            if *playlist_token.peek() == token && finished {
+                advance_playlist();
+            }
+        });
+    };
+
+    // 当前会话回放完毕后等待停顿, 再切换到下一个会话
+    use_effect(move || {
+        let finished = replay_player
+            .read()
+            .as_ref()
+            .is_some_and(ReplayPlayer::is_finished);
+        let waiting = playlist
+            .peek()
+            .as_ref()
+            .is_some_and(|session| !session.paused);
+        if finished && waiting {
+            schedule_playlist_next();
+        }
+    });
+
+    let mut start_playlist = move |config: ReplayPlaylist| {
+        app_state.write().replay_playlist = config.clone();
+        let session = PlaylistSession::new(&config, |id| {
+            app_state.read().contacts.iter().any(|c| c.id == id)
+        });
+        let Some(session) = session else {
+            return;
+        };
+        let first = session.current().map(str::to_string);
+        cancel_replay();
+        playlist.set(Some(session));
+        if let Some(contact_id) = first {
+            play_playlist_item(contact_id);
+        }
+    };
+
+    let mut toggle_playlist_pause = move || {
+        let Some(paused) = playlist.read().as_ref().map(|session| session.paused) else {
+            return;
+        };
+        if let Some(session) = playlist.write().as_mut() {
+            session.paused = !paused;
+        }
+        let (player_paused, finished) = replay_player
+            .read()
+            .as_ref()
+            .map(|player| (player.paused, player.is_finished()))
+            .unwrap_or((true, true));
+        if paused {
+            if finished {
+                schedule_playlist_next();
+            } else if player_paused {
+                replay.toggle_pause();
+            }
+        } else {
+            let next = *playlist_token.peek() + 1;
+            playlist_token.set(next);
+            if !finished && !player_paused {
+                replay.toggle_pause();
+            }
+        }
+    };
+
+    let replay_settings_for_contact = move || {
+        selected_contact_id()
+            .and_then(|id| app_state.read().replay_settings.get(&id).cloned())
+            .unwrap_or_default()
+    };
+    let replay_end_options = move |start_msg_id: String| {
+        let Some(contact_id) = selected_contact_id() else {
+            return Vec::new();
+        };
+        let state = app_state.read();
+        let messages = state
+            .messages
+            .get(&contact_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let start = replay_range(messages, &start_msg_id, &ReplayEnd::Last).start;
+        messages
+            .iter()
+            .enumerate()
+            .skip(start)
+            .map(|(index, msg)| {
+                (
+                    msg.id.clone(),
+                    format!("{}. {}", index + 1, message_preview(msg)),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
     let user_profile = app_state.read().user_profile.clone();
     let hide_tutorial = app_state.read().hide_tutorial;
+    let replay_muted = app_state.read().sound.replay_muted;
     let replay_pending_for_contact = use_memo(move || {
         if let Some(replay) = replay_active()
             && let Some(selected_id) = selected_contact_id()
@@ -788,25 +1060,7 @@ pub fn BakerLayout() -> Element {
         }
         None
     });
-    let background_style = use_memo(move || {
-        let bg = app_state.read().background.clone();
-        match bg.mode {
-            BackgroundMode::DotDark => {
-                "background-color: #1a1a1a; background-image: radial-gradient(#2a2a2a 1px, transparent 1px); background-size: 20px 20px;".to_string()
-            }
-            BackgroundMode::DotLight => {
-                "background-color: #f2f2f2; background-image: radial-gradient(#d0d0d0 1px, transparent 1px); background-size: 20px 20px;".to_string()
-            }
-            BackgroundMode::CustomColor => format!("background-color: {};", bg.custom_color),
-            BackgroundMode::CustomImage => {
-                if bg.custom_image.is_empty() {
-                    format!("background-color: {};", bg.custom_color)
-                } else {
-                    format!("background-image: url({}); background-size: cover; background-position: center; background-repeat: no-repeat; background-color: #1a1a1a;", bg.custom_image)
-                }
-            }
-        }
-    });
+    let background_style = use_memo(move || background_style(&app_state.read().background));
 
     rsx! {
         div {
@@ -843,17 +1097,27 @@ pub fn BakerLayout() -> Element {
                 }
             }
 
-            if replay_request_msg_id().is_some() {
+            if let Some(request_msg_id) = replay_request_msg_id() {
                 ReplaySettingsModal {
+                    initial: replay_settings_for_contact(),
+                    end_options: replay_end_options(request_msg_id),
                     on_close: move |_| replay_request_msg_id.set(None),
-                    on_start: move |settings| {
+                    on_start: move |(settings, end)| {
                         if let Some(msg_id) = replay_request_msg_id() {
-                            start_replay(msg_id, settings);
+                            start_replay(msg_id, settings, end);
                         }
                         replay_request_msg_id.set(None);
                     },
                 }
             }
+            if show_playlist() {
+                ReplayPlaylistModal {
+                    contacts: contacts(),
+                    initial: app_state.read().replay_playlist.clone(),
+                    on_close: move |_| show_playlist.set(false),
+                    on_start: start_playlist,
+                }
+            }
             if let Some(info) = update_info() {
                 UpdateAvailableModal {
                     latest_version: info.version.clone(),
@@ -902,6 +1166,25 @@ pub fn BakerLayout() -> Element {
                             "点击这里看教程！！"
                         }
                     }
+                    button {
+                        class: "text-gray-300 text-sm hover:text-white transition-colors cursor-pointer",
+                        onclick: move |_| show_playlist.set(true),
+                        "播放列表"
+                    }
+                    button {
+                        class: "text-gray-300 text-sm hover:text-white transition-colors cursor-pointer",
+                        onclick: move |_| {
+                            navigator.push(Route::BatchCapturePage {});
+                        },
+                        "批量导出"
+                    }
+                    button {
+                        class: "text-gray-300 text-sm hover:text-white transition-colors cursor-pointer",
+                        onclick: move |_| {
+                            navigator.push(Route::NewScriptPage {});
+                        },
+                        "导入脚本"
+                    }
                 }
 
                 // Profile Button
@@ -954,44 +1237,77 @@ pub fn BakerLayout() -> Element {
                             .map(|replay| replay.contact_id == contact.id)
                             .unwrap_or(false);
                         let force_first_avatar = is_replaying;
+                        let contact_name = contact.name.clone();
                         rsx! {
-                            ChatArea {
-                                contact,
-                                operators,
-                                messages,
-                                user_profile,
-                                menu_close_token,
-                                first_prev_sender_id: replay_prev_sender_id,
-                                force_first_avatar,
-                                pending_typing: replay_pending_for_contact,
-                                need_to_scroll_down,
-                                on_send_message: handle_send,
-                                on_send_other_message: move |(sender_id, text)| {
-                                    handle_send_other(sender_id, text);
-                                },
-                                on_send_status: handle_send_status,
-                                on_send_image: handle_send_image,
-                                on_send_image_other: move |(sender_id, data_url)| {
-                                    handle_send_image_other(sender_id, data_url);
-                                },
-                                on_send_sticker: handle_send_sticker,
-                                on_send_sticker_other: move |(sender_id, sticker)| {
-                                    handle_send_sticker_other(sender_id, sticker);
-                                },
-                                stickers,
-                                on_add_sticker: handle_add_sticker,
-                                on_delete_message: delete_message,
-                                on_edit_message: edit_message,
-                                on_add_reaction: add_reaction,
-                                on_delete_reaction: delete_reaction,
-                                on_insert_message: insert_message,
-                                on_start_replay: move |msg_id| replay_request_msg_id.set(Some(msg_id)),
-                                is_replaying,
-                                on_exit_replay: move |_| cancel_replay(),
-                                on_update_chat_head_style: update_chat_head_style,
-                                on_clear_messages: move |_| clear_messages(),
-                                on_clear_chat: move |_| clear_chat(),
-                                on_set_group_ops_list: set_group_ops_list,
+                            div { class: "flex-1 flex flex-col min-h-0 gap-3",
+                                ChatArea {
+                                    contact,
+                                    operators,
+                                    messages,
+                                    user_profile,
+                                    menu_close_token,
+                                    first_prev_sender_id: replay_prev_sender_id,
+                                    force_first_avatar,
+                                    pending_typing: replay_pending_for_contact,
+                                    need_to_scroll_down,
+                                    on_send_message: handle_send,
+                                    on_send_other_message: move |(sender_id, text)| {
+                                        handle_send_other(sender_id, text);
+                                    },
+                                    on_send_status: handle_send_status,
+                                    on_send_image: handle_send_image,
+                                    on_send_image_other: move |(sender_id, data_url)| {
+                                        handle_send_image_other(sender_id, data_url);
+                                    },
+                                    on_send_sticker: handle_send_sticker,
+                                    on_send_sticker_other: move |(sender_id, sticker)| {
+                                        handle_send_sticker_other(sender_id, sticker);
+                                    },
+                                    on_send_mission: handle_send_mission,
+                                    stickers,
+                                    on_add_sticker: handle_add_sticker,
+                                    on_delete_message: delete_message,
+                                    on_edit_message: edit_message,
+                                    on_edit_mission: edit_mission,
+                                    on_add_reaction: add_reaction,
+                                    on_delete_reaction: delete_reaction,
+                                    on_insert_message: insert_message,
+                                    on_edit_replay_overrides: edit_replay_overrides,
+                                    on_start_replay: move |msg_id| replay_request_msg_id.set(Some(msg_id)),
+                                    is_replaying,
+                                    hide_input_bar: false,
+                                    hide_header: false,
+                                    on_exit_replay: move |_| cancel_replay(),
+                                    on_update_chat_head_style: update_chat_head_style,
+                                    on_clear_messages: move |_| clear_messages(),
+                                    on_clear_chat: move |_| clear_chat(),
+                                    on_set_group_ops_list: set_group_ops_list,
+                                }
+                                if is_replaying {
+                                    if let Some(session) = playlist() {
+                                        PlaylistBar {
+                                            session,
+                                            contact_name,
+                                            on_toggle_pause: move |_| toggle_playlist_pause(),
+                                            on_skip: move |_| advance_playlist(),
+                                            on_exit: move |_| cancel_replay(),
+                                        }
+                                    }
+                                    ReplayControlBar {
+                                        player: replay_player,
+                                        on_toggle_pause: move |_| replay.toggle_pause(),
+                                        on_step_back: move |_| replay.step(false),
+                                        on_step_forward: move |_| replay.step(true),
+                                        on_seek: move |count| replay.jump(count),
+                                        on_speed: move |speed| replay.set_speed(speed),
+                                        muted: replay_muted,
+                                        on_toggle_mute: move |_| {
+                                            let mut state = app_state.write();
+                                            state.sound.replay_muted = !state.sound.replay_muted;
+                                        },
+                                        on_exit: move |_| cancel_replay(),
+                                    }
+                                }
                             }
                         }
                     }
@@ -1014,4 +1330,16 @@ pub enum Route {
     SettingsPage {},
     #[route("/capture/:contact_id")]
     CapturePage { contact_id: String },
+    #[route("/capture-all")]
+    BatchCapturePage {},
+    #[route("/replay-capture/:contact_id")]
+    ReplayCapturePage { contact_id: String },
+    #[route("/present/:contact_id/:start_id")]
+    PresenterPage { contact_id: String, start_id: String },
+    #[route("/script/:contact_id")]
+    ScriptPage { contact_id: String },
+    #[route("/script")]
+    NewScriptPage {},
+    #[route("/edit/:contact_id")]
+    BulkEditorPage { contact_id: String },
 }
@@ -1,15 +1,36 @@
+pub mod batch_capture;
+pub mod bulk_edit;
+pub mod bulk_editor;
 pub mod capture;
+pub mod capture_pages;
 pub mod chat_area;
+pub mod chat_render;
 pub mod input_bar;
 pub mod layout;
 pub mod modals;
+pub mod presenter;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod render_cli;
+pub mod replay;
+pub mod replay_bar;
+pub mod replay_capture;
+pub mod replay_export;
+pub mod save;
+pub mod script;
+pub mod script_page;
 pub mod settings;
 pub mod sidebar;
+pub mod sound;
 pub mod storage;
+pub mod subtitles;
+pub mod text_export;
 
-use crate::components::baker::storage::v2::AppState;
+use crate::components::baker::storage::v2::{
+    AppState, BackgroundMode, BackgroundSettings, Message, MessageKind,
+};
 use dioxus::prelude::*;
 pub use layout::Route;
+use std::collections::HashMap;
 
 /// 创建一个本地 Signal，镜像 AppState 中的某个字段，并在字段变动时自动同步回 AppState。
 pub(super) fn use_synced_field<T, G, S>(
@@ -44,16 +65,71 @@ pub(super) fn mime_from_filename(name: &str) -> &'static str {
         Some("jpg") | Some("jpeg") => "image/jpeg",
         Some("gif") => "image/gif",
         Some("webp") => "image/webp",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("ogg") => "audio/ogg",
+        Some("m4a") => "audio/mp4",
         _ => "application/octet-stream",
     }
 }
 
+/// 在下拉框等地方显示的消息摘要
+pub(super) fn message_preview(msg: &Message) -> String {
+    const PREVIEW_CHARS: usize = 16;
+    match msg.kind {
+        MessageKind::Image => "[图片]".to_string(),
+        MessageKind::Sticker => "[表情]".to_string(),
+        MessageKind::Mission => {
+            let title = msg
+                .mission
+                .as_ref()
+                .map(|m| m.title.as_str())
+                .unwrap_or_default();
+            format!("[任务] {title}")
+        }
+        _ if msg.content.chars().count() > PREVIEW_CHARS => {
+            let head = msg.content.chars().take(PREVIEW_CHARS).collect::<String>();
+            format!("{head}…")
+        }
+        _ => msg.content.clone(),
+    }
+}
+
+/// 背景设置对应的 CSS
+pub(super) fn background_style(bg: &BackgroundSettings) -> String {
+    match bg.mode {
+        BackgroundMode::DotDark => {
+            "background-color: #1a1a1a; background-image: radial-gradient(#2a2a2a 1px, transparent 1px); background-size: 20px 20px;".to_string()
+        }
+        BackgroundMode::DotLight => {
+            "background-color: #f2f2f2; background-image: radial-gradient(#d0d0d0 1px, transparent 1px); background-size: 20px 20px;".to_string()
+        }
+        BackgroundMode::CustomColor => format!("background-color: {};", bg.custom_color),
+        BackgroundMode::CustomImage => {
+            if bg.custom_image.is_empty() {
+                format!("background-color: {};", bg.custom_color)
+            } else {
+                format!("background-image: url({}); background-size: cover; background-position: center; background-repeat: no-repeat; background-color: #1a1a1a;", bg.custom_image)
+            }
+        }
+    }
+}
+
 pub(super) fn data_url_from_bytes(mime: &str, bytes: Vec<u8>) -> String {
     use base64::Engine;
     let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
     format!("data:{mime};base64,{encoded}")
 }
 
+/// 取出 base64 data URL 中的数据
+pub(super) fn bytes_from_data_url(url: &str) -> Option<Vec<u8>> {
+    use base64::Engine;
+    let (_, encoded) = url.strip_prefix("data:")?.split_once(";base64,")?;
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()
+}
+
 #[deprecated]
 #[allow(unused)]
 pub(super) fn avif_data_url_from_bytes(bytes: Vec<u8>) -> Option<String> {
@@ -69,23 +145,86 @@ pub(super) fn avif_data_url_from_bytes(bytes: Vec<u8>) -> Option<String> {
     Some(data_url_from_bytes("image/avif", out))
 }
 
-pub(super) async fn capture(selector: &str, scale: f64) -> Option<String> {
+///
+/// 用随应用打包的截图脚本 (`assets/js/capture.js`) 截取页面中的元素，不需要访问网络。
+///
+/// # 参数
+///
+/// - selector: 要截取的元素的选择器。
+/// - scale: 截图的缩放倍率。
+///
+/// 返回 PNG 的 data URL。
+///
+pub(super) async fn capture(selector: &str, scale: f64) -> anyhow::Result<String> {
     let eval = document::eval(
         r#"
             const selector = await dioxus.recv();
-            const scaleRaw = Number(await dioxus.recv());
-            const scale = Number.isFinite(scaleRaw) && scaleRaw > 0 ? scaleRaw : 1;
+            const scale = Number(await dioxus.recv());
+            if (typeof window.bakerCapture !== "function") {
+                return { error: "截图脚本没有加载" };
+            }
             const el = document.querySelector(selector);
-            if (!el) return null;
-            const result = await snapdom(el, { scale });
-            const img = await result.toPng();
-            return img?.src ?? null;
+            if (!el) return { error: `找不到要截图的元素 ${selector}` };
+            try {
+                return { src: await window.bakerCapture(el, { scale }) };
+            } catch (err) {
+                return { error: String(err?.message ?? err) };
+            }
         "#,
     );
-    eval.send(selector.to_owned()).ok()?;
-    eval.send(scale).ok()?;
-    let value = eval.await.ok()?;
-    value.as_str().map(|src| src.to_string())
+    eval.send(selector.to_owned())?;
+    eval.send(scale)?;
+    let value = eval.await.map_err(|err| anyhow::anyhow!(err.to_string()))?;
+    if let Some(src) = value.get("src").and_then(|src| src.as_str()) {
+        return Ok(src.to_string());
+    }
+    let reason = value
+        .get("error")
+        .and_then(|err| err.as_str())
+        .unwrap_or("截图脚本没有返回图片");
+    anyhow::bail!("{reason}")
+}
+
+///
+/// 把图片地址读取为 data URL，读取失败的地址不在结果中。
+///
+/// # 参数
+///
+/// - srcs: 应用内或网络上的图片地址。
+///
+pub(super) async fn fetch_data_urls(srcs: Vec<String>) -> HashMap<String, String> {
+    if srcs.is_empty() {
+        return HashMap::new();
+    }
+    let eval = document::eval(
+        r#"
+            const srcs = await dioxus.recv();
+            const out = {};
+            for (const src of srcs) {
+                try {
+                    const response = await fetch(src);
+                    if (!response.ok) continue;
+                    const blob = await response.blob();
+                    out[src] = await new Promise((resolve, reject) => {
+                        const reader = new FileReader();
+                        reader.onload = () => resolve(reader.result);
+                        reader.onerror = () => reject(reader.error);
+                        reader.readAsDataURL(blob);
+                    });
+                } catch (_) {
+                    // 保留原来的地址
+                }
+            }
+            return out;
+        "#,
+    );
+    if eval.send(srcs).is_err() {
+        return HashMap::new();
+    }
+    eval.await
+        .ok()
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
 }
 
 pub(super) async fn download_image(src: &str, format: &str, filename: &str) -> anyhow::Result<()> {
@@ -1,4 +1,10 @@
-use crate::components::baker::storage::v2::Operator;
+use crate::components::baker::replay::{
+    ReplayEnd, ReplayIntervalMode, ReplayReactionTiming, ReplaySettings,
+};
+use crate::components::baker::storage::v2::{
+    Contact, MessageReplayOverrides, Mission, MissionObjective, MissionStatus, Operator,
+    ReplayPlaylist,
+};
 use crate::components::baker::{data_url_from_bytes, mime_from_filename};
 use crate::dioxus_elements::FileData;
 use dioxus::prelude::*;
@@ -84,48 +90,34 @@ pub(crate) fn Modal(
     }
 }
 
-///
-/// 回放间隔模式。
-///
-#[derive(Clone, PartialEq)]
-pub enum ReplayIntervalMode {
-    /// 固定间隔
-    Fixed,
-    /// 按字数：当前消息字数 * 每个字的间隔。请注意，当消息为表情包和图片时仍按照固定间隔处理
-    PerChar,
-}
-
-///
-/// 回放设置。
-///
-#[derive(Clone, PartialEq)]
-pub struct ReplaySettings {
-    /// 回放间隔模式
-    pub mode: ReplayIntervalMode,
-    /// 当设为固定间隔时的间隔
-    pub fixed_ms: u64,
-    /// 当设为按字数时，每个字的间隔
-    pub per_char_ms: u64,
-    /// 发送后的间隔
-    pub gap_ms: u64,
-}
-
 ///
 /// 回放设置的弹窗。
 ///
 /// # 参数
 ///
+/// - initial: 弹窗打开时的回放设置，通常是这个会话上次使用的设置。
+/// - end_options: 可以作为回放终点的消息，每项为消息编号和显示的文字。
 /// - on_start: 处理开始回放的事件。
 ///
 #[component]
 pub fn ReplaySettingsModal(
+    initial: ReplaySettings,
+    end_options: Vec<(String, String)>,
     on_close: EventHandler<()>,
-    on_start: EventHandler<ReplaySettings>,
+    on_start: EventHandler<(ReplaySettings, ReplayEnd)>,
 ) -> Element {
-    let mut mode = use_signal(|| ReplayIntervalMode::Fixed);
-    let mut fixed_ms = use_signal(|| "800".to_string());
-    let mut per_char_ms = use_signal(|| "40".to_string());
-    let mut gap_ms = use_signal(|| "200".to_string());
+    let defaults = ReplaySettings::default();
+    let mut mode = use_signal(|| initial.mode.clone());
+    let mut fixed_ms = use_signal(|| initial.fixed_ms.to_string());
+    let mut per_char_ms = use_signal(|| initial.per_char_ms.to_string());
+    let mut gap_ms = use_signal(|| initial.gap_ms.to_string());
+    let mut self_typing = use_signal(|| initial.self_typing);
+    let mut status_pause_ms = use_signal(|| initial.status_pause_ms.to_string());
+    let mut reactions_with_message =
+        use_signal(|| matches!(initial.reaction_timing, ReplayReactionTiming::WithMessage));
+    let mut topic_ended_text = use_signal(|| initial.topic_ended_text.clone());
+    let mut end = use_signal(ReplayEnd::default);
+    let end_value = end().option_value();
 
     let fixed_class = if matches!(mode(), ReplayIntervalMode::Fixed) {
         "bg-[#fdfc00] text-black"
@@ -143,16 +135,31 @@ pub fn ReplaySettingsModal(
             title: "回放设置",
             content_confirmation_button: "开始回放",
             on_confirm: move |_| {
-                let fixed = fixed_ms().parse::<u64>().unwrap_or(800);
-                let per_char = per_char_ms().parse::<u64>().unwrap_or(40);
-                let gap = gap_ms().parse::<u64>().unwrap_or(200);
+                let fixed = fixed_ms().parse::<u64>().unwrap_or(defaults.fixed_ms);
+                let per_char = per_char_ms().parse::<u64>().unwrap_or(defaults.per_char_ms);
+                let gap = gap_ms().parse::<u64>().unwrap_or(defaults.gap_ms);
+                let status_pause = status_pause_ms()
+                    .parse::<u64>()
+                    .unwrap_or(defaults.status_pause_ms);
+                let reaction_timing = if reactions_with_message() {
+                    ReplayReactionTiming::WithMessage
+                } else {
+                    ReplayReactionTiming::AfterMessage
+                };
                 on_start
-                    .call(ReplaySettings {
-                        mode: mode(),
-                        fixed_ms: fixed,
-                        per_char_ms: per_char,
-                        gap_ms: gap,
-                    });
+                    .call((
+                        ReplaySettings {
+                            mode: mode(),
+                            fixed_ms: fixed,
+                            per_char_ms: per_char,
+                            gap_ms: gap,
+                            self_typing: self_typing(),
+                            status_pause_ms: status_pause,
+                            reaction_timing,
+                            topic_ended_text: topic_ended_text().trim().to_string(),
+                        },
+                        end(),
+                    ));
                 on_close.call(());
             },
             on_close,
@@ -171,7 +178,7 @@ pub fn ReplaySettingsModal(
                             "按字数"
                         }
                     }
-                    div { class: "space-y-3",
+                    div { class: "space-y-3 max-h-[60vh] overflow-y-auto pr-1",
                         div { class: "space-y-1",
                             label { class: "block text-black text-sm", "固定间隔 (ms)" }
                             input {
@@ -202,6 +209,59 @@ pub fn ReplaySettingsModal(
                                 oninput: move |e| gap_ms.set(e.value()),
                             }
                         }
+                        div { class: "space-y-1",
+                            label { class: "block text-black text-sm", "状态消息后停顿 (ms)" }
+                            input {
+                                class: "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30 resize-none",
+                                r#type: "number",
+                                min: "0",
+                                value: "{status_pause_ms}",
+                                oninput: move |e| status_pause_ms.set(e.value()),
+                            }
+                        }
+                        div { class: "space-y-1",
+                            label { class: "block text-black text-sm", "结束提示（留空则不显示）" }
+                            input {
+                                class: "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30 resize-none",
+                                value: "{topic_ended_text}",
+                                oninput: move |e| topic_ended_text.set(e.value()),
+                            }
+                        }
+                        label { class: "flex items-center gap-2 text-black text-sm cursor-pointer",
+                            input {
+                                r#type: "checkbox",
+                                class: "w-4 h-4 accent-black cursor-pointer",
+                                checked: self_typing(),
+                                onchange: move |e| self_typing.set(e.checked()),
+                            }
+                            "自己的消息也显示“输入中”"
+                        }
+                        label { class: "flex items-center gap-2 text-black text-sm cursor-pointer",
+                            input {
+                                r#type: "checkbox",
+                                class: "w-4 h-4 accent-black cursor-pointer",
+                                checked: reactions_with_message(),
+                                onchange: move |e| reactions_with_message.set(e.checked()),
+                            }
+                            "回应与消息同时出现"
+                        }
+                        div { class: "space-y-1",
+                            label { class: "block text-black text-sm", "回放终点" }
+                            select {
+                                class: "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30 cursor-pointer",
+                                value: "{end_value}",
+                                onchange: move |e| end.set(ReplayEnd::from_option_value(&e.value())),
+                                option { value: ReplayEnd::Last.option_value(), "播放到最后" }
+                                option { value: ReplayEnd::NextMarker.option_value(),
+                                    "在下一条状态或话题结束提示前停止"
+                                }
+                                for (id, label) in end_options {
+                                    option { value: ReplayEnd::Message(id).option_value(),
+                                        "播放到：{label}"
+                                    }
+                                }
+                            }
+                        }
                     }
                 }
             }
@@ -349,6 +409,127 @@ pub fn EditMessageModal(
     }
 }
 
+///
+/// 发送或编辑任务消息的弹窗。
+///
+/// # 参数
+///
+/// - initial: 任务的初始内容。
+/// - members: 可以选择的发送者，为空时不显示发送者的选择。
+/// - on_save: 处理保存的事件，发送者为 `None` 时由自己发送。
+///
+#[component]
+pub fn EditMissionModal(
+    initial: Mission,
+    members: Vec<Operator>,
+    on_close: EventHandler<()>,
+    on_save: EventHandler<(Option<String>, Mission)>,
+) -> Element {
+    let mut mission = use_signal(|| initial);
+    let mut sender_id = use_signal(String::new);
+    let field_class = "w-full bg-[#e9e9e9] border border-black/10 rounded p-2 text-black text-sm focus:outline-none focus:border-black/30";
+    let objectives = mission.read().objectives.clone();
+    let status = mission.read().status;
+
+    rsx! {
+        Modal {
+            title: "任务",
+            content_confirmation_button: "保存",
+            on_close,
+            on_confirm: move |_| {
+                let mut value = mission();
+                if value.title.trim().is_empty() {
+                    return;
+                }
+                value.objectives.retain(|objective| !objective.text.trim().is_empty());
+                let sender = Some(sender_id()).filter(|id| !id.is_empty());
+                on_save.call((sender, value));
+            },
+
+            div { class: "space-y-3 max-h-[60vh] overflow-y-auto custom-scrollbar",
+                if !members.is_empty() {
+                    div { class: "space-y-1",
+                        label { class: "text-black text-sm", "发送者" }
+                        select {
+                            class: field_class,
+                            onchange: move |e| sender_id.set(e.value()),
+                            option { value: "", selected: sender_id().is_empty(), "自己" }
+                            for member in members.iter() {
+                                option {
+                                    key: "{member.id}",
+                                    value: "{member.id}",
+                                    selected: sender_id() == member.id,
+                                    "{member.name}"
+                                }
+                            }
+                        }
+                    }
+                }
+                div { class: "space-y-1",
+                    label { class: "text-black text-sm", "标题" }
+                    input {
+                        class: field_class,
+                        placeholder: "任务的标题",
+                        value: "{mission.read().title}",
+                        oninput: move |e| mission.write().title = e.value(),
+                    }
+                }
+                div { class: "space-y-1",
+                    label { class: "text-black text-sm", "说明" }
+                    textarea {
+                        class: "{field_class} h-20 resize-none",
+                        value: "{mission.read().description}",
+                        oninput: move |e| mission.write().description = e.value(),
+                    }
+                }
+                div { class: "space-y-1",
+                    label { class: "text-black text-sm", "状态" }
+                    div { class: "flex gap-2",
+                        for choice in MissionStatus::ALL {
+                            button {
+                                key: "{choice.label()}",
+                                class: if choice == status { "flex-1 px-3 py-2 rounded text-sm font-medium transition-colors cursor-pointer bg-[#fdfc00] text-black" } else { "flex-1 px-3 py-2 rounded text-sm font-medium transition-colors cursor-pointer bg-black/5 text-black" },
+                                onclick: move |_| mission.write().status = choice,
+                                {choice.label()}
+                            }
+                        }
+                    }
+                }
+                div { class: "space-y-1",
+                    label { class: "text-black text-sm", "目标" }
+                    for (index , objective) in objectives.into_iter().enumerate() {
+                        div { key: "{index}", class: "flex items-center gap-2",
+                            input {
+                                r#type: "checkbox",
+                                class: "cursor-pointer",
+                                checked: objective.done,
+                                onchange: move |e| mission.write().objectives[index].done = e.checked(),
+                            }
+                            input {
+                                class: field_class,
+                                value: "{objective.text}",
+                                oninput: move |e| mission.write().objectives[index].text = e.value(),
+                            }
+                            button {
+                                class: "w-7 h-7 shrink-0 rounded flex items-center justify-center text-black hover:bg-black/10 transition-colors cursor-pointer",
+                                onclick: move |_| {
+                                    mission.write().objectives.remove(index);
+                                },
+                                "✕"
+                            }
+                        }
+                    }
+                    button {
+                        class: "px-3 py-1 rounded bg-black/10 hover:bg-black/20 text-black text-sm cursor-pointer",
+                        onclick: move |_| mission.write().objectives.push(MissionObjective::default()),
+                        "添加目标"
+                    }
+                }
+            }
+        }
+    }
+}
+
 ///
 /// 添加反应的弹窗。
 ///
@@ -410,6 +591,265 @@ pub fn ReactionModal(on_close: EventHandler<()>, on_save: EventHandler<String>)
     }
 }
 
+///
+/// 单条消息回放设置的弹窗。
+///
+/// 留空的时长沿用全局的回放设置；所有项都为默认值时保存为 `None`。
+///
+/// # 参数
+///
+/// - initial: 消息当前的回放设置。
+/// - on_save: 处理保存的事件。
+///
+#[component]
+pub fn MessageReplayModal(
+    initial: Option<MessageReplayOverrides>,
+    on_close: EventHandler<()>,
+    on_save: EventHandler<Option<MessageReplayOverrides>>,
+) -> Element {
+    let initial = initial.unwrap_or_default();
+    let optional_ms = |value: Option<u64>| value.map(|ms| ms.to_string()).unwrap_or_default();
+    let mut typing_ms = use_signal(|| optional_ms(initial.typing_ms));
+    let mut pause_before_ms = use_signal(|| initial.pause_before_ms.to_string());
+    let mut pause_after_ms = use_signal(|| initial.pause_after_ms.to_string());
+    let mut skip_typing = use_signal(|| initial.skip_typing);
+    let mut reactions_after_ms = use_signal(|| optional_ms(initial.reactions_after_ms));
+
+    let input_class = "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30 resize-none";
+
+    rsx! {
+        Modal {
+            title: "单条回放设置",
+            content_confirmation_button: "保存",
+            on_close,
+            on_confirm: move |_| {
+                let overrides = MessageReplayOverrides {
+                    typing_ms: typing_ms().trim().parse::<u64>().ok(),
+                    pause_before_ms: pause_before_ms().trim().parse::<u64>().unwrap_or(0),
+                    pause_after_ms: pause_after_ms().trim().parse::<u64>().unwrap_or(0),
+                    skip_typing: skip_typing(),
+                    reactions_after_ms: reactions_after_ms().trim().parse::<u64>().ok(),
+                };
+                on_save
+                    .call(
+                        (overrides != MessageReplayOverrides::default()).then_some(overrides),
+                    );
+            },
+
+            {
+                rsx! {
+                    div { class: "space-y-3",
+                        div { class: "space-y-1",
+                            label { class: "block text-black text-sm", "输入时长 (ms)" }
+                            input {
+                                class: input_class,
+                                r#type: "number",
+                                min: "0",
+                                placeholder: "留空则按回放设置计算",
+                                value: "{typing_ms}",
+                                oninput: move |e| typing_ms.set(e.value()),
+                            }
+                        }
+                        div { class: "space-y-1",
+                            label { class: "block text-black text-sm", "之前停顿 (ms)" }
+                            input {
+                                class: input_class,
+                                r#type: "number",
+                                min: "0",
+                                value: "{pause_before_ms}",
+                                oninput: move |e| pause_before_ms.set(e.value()),
+                            }
+                        }
+                        div { class: "space-y-1",
+                            label { class: "block text-black text-sm", "之后停顿 (ms)" }
+                            input {
+                                class: input_class,
+                                r#type: "number",
+                                min: "0",
+                                value: "{pause_after_ms}",
+                                oninput: move |e| pause_after_ms.set(e.value()),
+                            }
+                        }
+                        div { class: "space-y-1",
+                            label { class: "block text-black text-sm", "回应延迟 (ms)" }
+                            input {
+                                class: input_class,
+                                r#type: "number",
+                                min: "0",
+                                placeholder: "留空则使用发送后间隔",
+                                value: "{reactions_after_ms}",
+                                oninput: move |e| reactions_after_ms.set(e.value()),
+                            }
+                        }
+                        label { class: "flex items-center gap-2 text-black text-sm cursor-pointer",
+                            input {
+                                r#type: "checkbox",
+                                class: "w-4 h-4 accent-black cursor-pointer",
+                                checked: skip_typing(),
+                                onchange: move |e| skip_typing.set(e.checked()),
+                            }
+                            "不显示“输入中”"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+///
+/// 播放列表的弹窗：选择依次回放的会话和会话之间的停顿。
+///
+/// # 参数
+///
+/// - contacts: 所有会话。
+/// - initial: 上次使用的播放列表。
+/// - on_start: 处理开始回放的事件，列表为空时不会触发。
+///
+#[component]
+pub fn ReplayPlaylistModal(
+    contacts: Vec<Contact>,
+    initial: ReplayPlaylist,
+    on_close: EventHandler<()>,
+    on_start: EventHandler<ReplayPlaylist>,
+) -> Element {
+    let mut contact_ids = use_signal(|| {
+        initial
+            .contact_ids
+            .iter()
+            .filter(|id| contacts.iter().any(|c| c.id == **id))
+            .cloned()
+            .collect::<Vec<_>>()
+    });
+    let mut pause_ms = use_signal(|| initial.pause_ms.to_string());
+
+    let contact_name = |id: &str| {
+        contacts
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.name.clone())
+            .unwrap_or_default()
+    };
+    let selected = contact_ids()
+        .into_iter()
+        .map(|id| {
+            let name = contact_name(&id);
+            (id, name)
+        })
+        .collect::<Vec<_>>();
+    let selected_count = selected.len();
+    let available = contacts
+        .iter()
+        .filter(|c| !contact_ids().contains(&c.id))
+        .map(|c| (c.id.clone(), c.name.clone()))
+        .collect::<Vec<_>>();
+
+    let input_class = "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30 resize-none";
+    let small_button_class = "w-7 h-7 rounded flex items-center justify-center text-black hover:bg-black/10 disabled:opacity-30 transition-colors cursor-pointer";
+
+    rsx! {
+        Modal {
+            title: "播放列表",
+            content_confirmation_button: "开始回放",
+            on_close,
+            on_confirm: move |_| {
+                let ids = contact_ids();
+                if !ids.is_empty() {
+                    let pause = pause_ms()
+                        .trim()
+                        .parse::<u64>()
+                        .unwrap_or(ReplayPlaylist::default().pause_ms);
+                    on_start
+                        .call(ReplayPlaylist {
+                            contact_ids: ids,
+                            pause_ms: pause,
+                        });
+                }
+                on_close.call(());
+            },
+
+            {
+                rsx! {
+                    div { class: "space-y-3",
+                        p { class: "text-black/60 text-xs",
+                            "按顺序回放每个会话的全部消息，输入时长等使用各会话上次的回放设置。"
+                        }
+                        div { class: "space-y-1 max-h-[40vh] overflow-y-auto",
+                            if selected.is_empty() {
+                                div { class: "text-black/50 text-sm py-2", "还没有添加会话" }
+                            }
+                            for (index, (id, name)) in selected.into_iter().enumerate() {
+                                div {
+                                    key: "{id}",
+                                    class: "flex items-center gap-2 bg-[#e9e9e9] border border-black/10 rounded px-3 py-2",
+                                    span { class: "text-black/50 text-xs tabular-nums w-5", "{index + 1}" }
+                                    span { class: "flex-1 text-black text-sm truncate", "{name}" }
+                                    button {
+                                        class: small_button_class,
+                                        title: "上移",
+                                        disabled: index == 0,
+                                        onclick: move |_| {
+                                            if index > 0 {
+                                                contact_ids.write().swap(index - 1, index);
+                                            }
+                                        },
+                                        "↑"
+                                    }
+                                    button {
+                                        class: small_button_class,
+                                        title: "下移",
+                                        disabled: index + 1 == selected_count,
+                                        onclick: move |_| {
+                                            if index + 1 < contact_ids.read().len() {
+                                                contact_ids.write().swap(index, index + 1);
+                                            }
+                                        },
+                                        "↓"
+                                    }
+                                    button {
+                                        class: small_button_class,
+                                        title: "移除",
+                                        onclick: move |_| {
+                                            contact_ids.write().remove(index);
+                                        },
+                                        "✕"
+                                    }
+                                }
+                            }
+                        }
+                        if !available.is_empty() {
+                            select {
+                                class: "{input_class} cursor-pointer",
+                                value: "",
+                                onchange: move |e| {
+                                    let id = e.value();
+                                    if !id.is_empty() {
+                                        contact_ids.write().push(id);
+                                    }
+                                },
+                                option { value: "", "添加会话…" }
+                                for (id, name) in available {
+                                    option { value: "{id}", "{name}" }
+                                }
+                            }
+                        }
+                        div { class: "space-y-1",
+                            label { class: "block text-black text-sm", "会话之间的停顿 (ms)" }
+                            input {
+                                class: input_class,
+                                r#type: "number",
+                                min: "0",
+                                value: "{pause_ms}",
+                                oninput: move |e| pause_ms.set(e.value()),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 ///
 /// 告知用户有可用更新的弹窗。
 ///
@@ -0,0 +1,238 @@
+use dioxus::prelude::*;
+
+use crate::components::baker::Route;
+use crate::components::baker::chat_area::{ChatArea, PendingTyping};
+use crate::components::baker::layout::ReplayHandles;
+use crate::components::baker::replay::{ReplayEnd, ReplayPlayer, replay_range};
+use crate::components::baker::storage::v2::{AppState, Message};
+
+/// 演示模式中按键对应的操作
+enum PresenterCommand {
+    Advance,
+    Back,
+    ToggleFullscreen,
+    Exit,
+}
+
+fn presenter_command(key: &Key) -> Option<PresenterCommand> {
+    match key {
+        Key::Enter | Key::ArrowRight | Key::ArrowDown | Key::PageDown => {
+            Some(PresenterCommand::Advance)
+        }
+        Key::ArrowLeft | Key::ArrowUp | Key::PageUp | Key::Backspace => {
+            Some(PresenterCommand::Back)
+        }
+        Key::Escape => Some(PresenterCommand::Exit),
+        Key::Character(c) if c == " " => Some(PresenterCommand::Advance),
+        Key::Character(c) if c.eq_ignore_ascii_case("f") => {
+            Some(PresenterCommand::ToggleFullscreen)
+        }
+        _ => None,
+    }
+}
+
+///
+/// 演示模式：全屏只显示聊天内容，每按一次键或点击一次播放下一条消息。
+///
+/// 对方的消息仍会先显示“输入中”再显示内容，时长按这个会话上次使用的回放设置计算。
+///
+/// # 参数
+///
+/// - contact_id: 会话编号。
+/// - start_id: 从这条消息开始演示。
+///
+#[component]
+pub(super) fn PresenterPage(contact_id: String, start_id: String) -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let navigator = navigator();
+
+    let contact = app_state
+        .read()
+        .contacts
+        .iter()
+        .find(|x| x.id == contact_id)
+        .cloned();
+    let user_profile = app_state.read().user_profile.clone();
+    let (player_value, first_prev_sender_id) = use_hook(|| {
+        let state = app_state.read();
+        let all_messages = state
+            .messages
+            .get(&contact_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let range = replay_range(all_messages, &start_id, &ReplayEnd::Last);
+        let first_prev_sender_id = range
+            .start
+            .checked_sub(1)
+            .map(|index| all_messages[index].sender_id.clone());
+        let settings = state
+            .replay_settings
+            .get(&contact_id)
+            .cloned()
+            .unwrap_or_default();
+        let mut player = ReplayPlayer::new(
+            all_messages[range].to_vec(),
+            state.user_profile.id.clone(),
+            &settings,
+        );
+        player.paused = true;
+        (player, first_prev_sender_id)
+    });
+
+    let operators = use_signal(move || app_state.read().operators.clone());
+    let stickers = use_memo(move || app_state.read().stickers.clone());
+    let menu_close_token = use_signal(|| 0usize);
+    let player = use_signal(|| Some(player_value));
+    let token = use_signal(|| 0usize);
+    let messages = use_signal(Vec::<Message>::new);
+    let pending = use_signal(|| Option::<PendingTyping>::None);
+    let need_to_scroll_down = use_signal(|| true);
+    // 正在播放的消息结束的位置
+    let mut run_target = use_signal(|| Option::<usize>::None);
+    let mut show_hint = use_signal(|| true);
+
+    let replay = ReplayHandles {
+        player,
+        token,
+        messages,
+        pending,
+        need_to_scroll_down,
+    };
+    let set_paused = move |paused: bool| {
+        let mut player = player;
+        if let Some(player) = player.write().as_mut() {
+            player.paused = paused;
+        }
+    };
+
+    let mut advance = move || {
+        show_hint.set(false);
+        let Some((cursor, next)) = player
+            .read()
+            .as_ref()
+            .map(|player| (player.cursor, player.next_boundary()))
+        else {
+            return;
+        };
+        if let Some(target) = run_target()
+            && cursor < target
+        {
+            // 这条消息还在播放, 直接显示完
+            set_paused(true);
+            replay.seek(target);
+            return;
+        }
+        if next <= cursor {
+            return;
+        }
+        run_target.set(Some(next));
+        set_paused(false);
+        replay.play_until(Some(next));
+    };
+    let mut back = move || {
+        let Some(target) = player
+            .read()
+            .as_ref()
+            .map(|player| player.previous_boundary())
+        else {
+            return;
+        };
+        run_target.set(None);
+        set_paused(true);
+        replay.seek(target);
+    };
+
+    let Some(contact_val) = contact else {
+        navigator.push(Route::BakerLayout {});
+        return rsx! {};
+    };
+
+    rsx! {
+        div {
+            class: "fixed inset-0 bg-[#1a1a1a] flex justify-center outline-none select-none",
+            tabindex: "0",
+            onmounted: move |e| async move {
+                let _ = e.set_focus(true).await;
+            },
+            onkeydown: move |e| {
+                let Some(command) = presenter_command(&e.key()) else {
+                    return;
+                };
+                e.prevent_default();
+                match command {
+                    PresenterCommand::Advance => advance(),
+                    PresenterCommand::Back => back(),
+                    PresenterCommand::ToggleFullscreen => {
+                        let _ = document::eval(
+                            r#"
+                            if (document.fullscreenElement) {
+                                document.exitFullscreen();
+                            } else {
+                                document.documentElement.requestFullscreen?.();
+                            }
+                            "#,
+                        );
+                    }
+                    PresenterCommand::Exit => {
+                        replay.stop();
+                        navigator.push(Route::BakerLayout {});
+                    }
+                }
+            },
+
+            div { class: "relative w-full max-w-[900px] h-full flex flex-col p-4",
+                ChatArea {
+                    contact: contact_val,
+                    operators,
+                    messages,
+                    user_profile,
+                    menu_close_token,
+                    first_prev_sender_id,
+                    force_first_avatar: true,
+                    pending_typing: pending,
+                    need_to_scroll_down,
+                    on_send_message: move |_| {},
+                    on_send_other_message: move |_| {},
+                    on_send_status: move |_| {},
+                    on_send_image: move |_| {},
+                    on_send_sticker: move |_| {},
+                    on_send_sticker_other: move |_| {},
+                    on_send_mission: move |_| {},
+                    stickers,
+                    on_add_sticker: move |_| {},
+                    on_delete_message: move |_| {},
+                    on_edit_message: move |_| {},
+                    on_edit_mission: move |_| {},
+                    on_add_reaction: move |_| {},
+                    on_delete_reaction: move |_| {},
+                    on_insert_message: move |_| {},
+                    on_edit_replay_overrides: move |_| {},
+                    on_start_replay: move |_| {},
+                    on_update_chat_head_style: move |_| {},
+                    on_clear_messages: move |_| {},
+                    on_clear_chat: move |_| {},
+                    on_set_group_ops_list: move |_| {},
+                    on_send_image_other: move |_| {},
+                    is_replaying: true,
+                    hide_input_bar: true,
+                    hide_header: true,
+                    on_exit_replay: move |_| {},
+                }
+                // 挡住聊天区域自身的点击和右键菜单
+                div {
+                    class: "absolute inset-0 z-10 cursor-pointer",
+                    onclick: move |_| advance(),
+                    oncontextmenu: move |e| {
+                        e.prevent_default();
+                        back();
+                    },
+                }
+            }
+            if show_hint() {
+                div { class: "fixed bottom-4 left-1/2 -translate-x-1/2 z-20 px-4 py-2 rounded bg-black/60 text-gray-300 text-xs pointer-events-none",
+                    "空格 / 点击：下一条　← / 右键：上一条　F：全屏　Esc：退出"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::components::baker::chat_render::{
+    ChatSource, RenderAssets, RenderOptions, contact_display_name, load_image_source,
+    render_chat_png,
+};
+use crate::components::baker::storage::parse_state_file;
+use crate::components::baker::storage::v2::{AppState, Contact};
+use anyhow::{Context, anyhow, bail};
+use image::Rgba;
+use std::path::PathBuf;
+
+/// 命令行渲染的子命令名
+pub const RENDER_CHAT_COMMAND: &str = "render-chat";
+
+const USAGE: &str = "用法: baker-dx render-chat <状态文件> <会话 ID 或名字> <输出.png> \
+[--width 宽度] [--scale 倍率] [--background RRGGBB] [--assets 资源目录]";
+
+///
+/// `render-chat` 的参数。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderChatArgs {
+    /// 状态文件，当前的状态结构或旧版本的 `baker_dx_state.json`
+    pub state: PathBuf,
+    /// 会话的 ID 或显示的名字
+    pub contact: String,
+    pub output: PathBuf,
+    pub options: RenderOptions,
+    /// 字体和界面图片所在的目录，默认为当前目录下的 `assets`
+    pub assets_dir: PathBuf,
+}
+
+///
+/// 解析 `render-chat` 之后的参数。
+///
+/// # 参数
+///
+/// - args: 子命令之后的参数。
+///
+pub fn parse_render_chat_args(
+    args: impl IntoIterator<Item = String>,
+) -> anyhow::Result<RenderChatArgs> {
+    let mut positional = Vec::new();
+    let mut options = RenderOptions::default();
+    let mut assets_dir = PathBuf::from("assets");
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            positional.push(arg);
+            continue;
+        }
+        let value = args.next().ok_or_else(|| anyhow!("{arg} 缺少参数值"))?;
+        match arg.as_str() {
+            "--width" => {
+                options.width = value
+                    .parse()
+                    .ok()
+                    .filter(|width| *width > 0)
+                    .ok_or_else(|| anyhow!("无效的宽度 {value}"))?;
+            }
+            "--scale" => {
+                options.scale = value
+                    .parse()
+                    .ok()
+                    .filter(|scale: &f32| scale.is_finite() && *scale > 0.0)
+                    .ok_or_else(|| anyhow!("无效的倍率 {value}"))?;
+            }
+            "--background" => options.background = Some(parse_background(&value)?),
+            "--assets" => assets_dir = PathBuf::from(value),
+            _ => bail!("未知的选项 {arg}"),
+        }
+    }
+    let [state, contact, output] = <[String; 3]>::try_from(positional)
+        .map_err(|_| anyhow!("需要状态文件、会话和输出文件三个参数"))?;
+    Ok(RenderChatArgs {
+        state: PathBuf::from(state),
+        contact,
+        output: PathBuf::from(output),
+        options,
+        assets_dir,
+    })
+}
+
+/// 解析 `RRGGBB` 或 `#RRGGBB` 形式的背景色
+fn parse_background(value: &str) -> anyhow::Result<Rgba<u8>> {
+    let hex = value.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        bail!("无效的背景色 {value}");
+    }
+    let channel = |index: usize| {
+        u8::from_str_radix(&hex[index..index + 2], 16).map_err(|_| anyhow!("无效的背景色 {value}"))
+    };
+    Ok(Rgba([channel(0)?, channel(2)?, channel(4)?, 255]))
+}
+
+///
+/// 按 ID 或显示的名字查找会话，优先匹配 ID。
+///
+/// # 参数
+///
+/// - state: 读取的状态。
+/// - key: 会话的 ID 或名字。
+///
+pub fn find_contact<'a>(state: &'a AppState, key: &str) -> anyhow::Result<&'a Contact> {
+    if let Some(contact) = state.contacts.iter().find(|contact| contact.id == key) {
+        return Ok(contact);
+    }
+    let mut matches = state
+        .contacts
+        .iter()
+        .filter(|contact| contact_display_name(contact, &state.operators) == key);
+    match (matches.next(), matches.next()) {
+        (Some(contact), None) => Ok(contact),
+        (Some(_), Some(_)) => bail!("有多个名为 {key} 的会话，请改用会话 ID"),
+        (None, _) => bail!("找不到会话 {key}"),
+    }
+}
+
+///
+/// 读取状态文件，把指定的会话渲染为 PNG 并写入输出文件。
+///
+/// # 参数
+///
+/// - args: 解析后的参数。
+///
+pub fn render_chat_file(args: &RenderChatArgs) -> anyhow::Result<()> {
+    let raw = std::fs::read_to_string(&args.state)
+        .with_context(|| format!("无法读取状态文件 {}", args.state.display()))?;
+    let state = parse_state_file(&raw)
+        .ok_or_else(|| anyhow!("无法解析状态文件 {}", args.state.display()))?;
+    let contact = find_contact(&state, &args.contact)?;
+    let messages = state
+        .messages
+        .get(&contact.id)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let source = ChatSource {
+        contact,
+        messages,
+        operators: &state.operators,
+        user_profile: &state.user_profile,
+    };
+    let assets = RenderAssets::load(&args.assets_dir)?;
+    let png = render_chat_png(&source, &assets, &args.options, |src| {
+        load_image_source(src, &args.assets_dir)
+    })?;
+    std::fs::write(&args.output, png).with_context(|| format!("无法写入 {}", args.output.display()))
+}
+
+///
+/// 处理命令行参数，不是 `render-chat` 时返回空，由调用方照常启动界面；否则返回进程的退出码。
+///
+/// # 参数
+///
+/// - args: 程序名之后的参数。
+///
+pub fn run(args: impl IntoIterator<Item = String>) -> Option<i32> {
+    let mut args = args.into_iter();
+    if args.next().as_deref() != Some(RENDER_CHAT_COMMAND) {
+        return None;
+    }
+    let args = match parse_render_chat_args(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            return Some(2);
+        }
+    };
+    match render_chat_file(&args) {
+        Ok(()) => {
+            println!("已写入 {}", args.output.display());
+            Some(0)
+        }
+        Err(e) => {
+            eprintln!("渲染失败: {e:#}");
+            Some(1)
+        }
+    }
+}
@@ -0,0 +1,547 @@
+use crate::components::baker::chat_area::{PendingTyping, ReplayTypingPhase};
+use crate::components::baker::storage::v2::{Message, MessageKind, SoundEvent};
+pub use crate::components::baker::storage::v2::{
+    ReplayIntervalMode, ReplayPlaylist, ReplayReactionTiming, ReplaySettings,
+};
+use std::ops::Range;
+
+/// 对方消息从“输入中”切换为显示内容后, 输入状态保留的时间
+pub const REVEAL_MS: u64 = 200;
+/// 话题结束提示的入场动画时长
+pub const TOPIC_ENDED_ANIMATE_MS: u64 = 900;
+/// 回放结束时默认追加的提示
+pub const TOPIC_ENDED_TEXT: &str = "话题结束，暂无新话题";
+/// 回放结束时追加的提示消息的编号, 每次回放只会有一条
+pub const TOPIC_ENDED_MESSAGE_ID: &str = "replay-topic-ended";
+
+impl ReplaySettings {
+    /// 一条消息的输入时长
+    pub fn typing_ms(&self, msg: &Message) -> u64 {
+        match msg.kind {
+            MessageKind::Image | MessageKind::Sticker | MessageKind::Mission => self.fixed_ms,
+            _ => match self.mode {
+                ReplayIntervalMode::Fixed => self.fixed_ms,
+                ReplayIntervalMode::PerChar => {
+                    let len = msg.content.chars().count() as u64;
+                    len.saturating_mul(self.per_char_ms)
+                }
+            },
+        }
+    }
+}
+
+///
+/// 回放在哪里结束。
+///
+#[derive(Clone, Debug, PartialEq, Default)]
+pub enum ReplayEnd {
+    /// 播放到最后一条消息
+    #[default]
+    Last,
+    /// 播放到指定的消息（包含这条消息）
+    Message(String),
+    /// 在下一条状态消息或话题结束提示之前停止
+    NextMarker,
+}
+
+impl ReplayEnd {
+    /// 在下拉框中使用的值
+    pub fn option_value(&self) -> String {
+        match self {
+            ReplayEnd::Last => "last".to_string(),
+            ReplayEnd::NextMarker => "marker".to_string(),
+            ReplayEnd::Message(id) => format!("message:{id}"),
+        }
+    }
+
+    pub fn from_option_value(value: &str) -> Self {
+        match value.strip_prefix("message:") {
+            Some(id) => ReplayEnd::Message(id.to_string()),
+            None if value == "marker" => ReplayEnd::NextMarker,
+            None => ReplayEnd::Last,
+        }
+    }
+}
+
+///
+/// 计算参与回放的消息在会话中的范围。
+///
+/// 找不到起始消息时从头开始；结束消息找不到或在起始消息之前时播放到最后。
+///
+/// # 参数
+///
+/// - messages: 会话中的所有消息。
+/// - start_id: 开始回放的消息。
+/// - end: 回放在哪里结束。
+///
+pub fn replay_range(messages: &[Message], start_id: &str, end: &ReplayEnd) -> Range<usize> {
+    let start = messages.iter().position(|m| m.id == start_id).unwrap_or(0);
+    let stop = match end {
+        ReplayEnd::Last => None,
+        ReplayEnd::Message(end_id) => messages
+            .iter()
+            .position(|m| m.id == *end_id)
+            .filter(|index| *index >= start)
+            .map(|index| index + 1),
+        ReplayEnd::NextMarker => messages
+            .iter()
+            .enumerate()
+            .skip(start + 1)
+            .find(|(_, m)| matches!(m.kind, MessageKind::Status | MessageKind::TopicEnded))
+            .map(|(index, _)| index),
+    };
+    start..stop.unwrap_or(messages.len()).max(start)
+}
+
+///
+/// 回放中的一个动作。
+///
+/// `index` 是消息在参与回放的消息列表中的下标。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplayAction {
+    /// 显示对方的消息（不含回应），并显示“输入中”
+    ShowTyping { index: usize },
+    /// 对方的消息从“输入中”切换为显示内容
+    Reveal { index: usize },
+    /// 清除输入状态
+    ClearPending,
+    /// 直接显示消息（不含回应）
+    Show { index: usize },
+    /// 播放提示音
+    PlaySound { event: SoundEvent },
+    /// 显示消息的回应
+    ApplyReactions { index: usize },
+    /// 追加话题结束的提示
+    TopicEnded { text: String },
+}
+
+impl ReplayAction {
+    /// 这个动作之后聊天区域是否需要滚动到底部
+    pub fn scrolls(&self) -> bool {
+        matches!(
+            self,
+            ReplayAction::Reveal { .. }
+                | ReplayAction::Show { .. }
+                | ReplayAction::TopicEnded { .. }
+        )
+    }
+
+    /// 这个动作是否会修改回放中的消息列表
+    pub fn changes_messages(&self) -> bool {
+        matches!(
+            self,
+            ReplayAction::ShowTyping { .. }
+                | ReplayAction::Show { .. }
+                | ReplayAction::ApplyReactions { .. }
+                | ReplayAction::TopicEnded { .. }
+        )
+    }
+
+    ///
+    /// 将动作作用到回放中的消息列表上。
+    ///
+    /// 声音、滚动和关闭动画等副作用由调用方处理。
+    ///
+    /// # 参数
+    ///
+    /// - source: 参与回放的消息列表，即 `build_timeline` 的输入。
+    /// - user_id: 用户自己的编号，用作话题结束提示的发送者。
+    ///
+    pub fn apply_to_messages(
+        &self,
+        source: &[Message],
+        user_id: &str,
+        messages: &mut Vec<Message>,
+    ) {
+        let shown = |index: usize| Message {
+            animate: true,
+            animate_reactions: false,
+            reactions: Vec::new(),
+            ..source[index].clone()
+        };
+        match self {
+            ReplayAction::ShowTyping { index } | ReplayAction::Show { index } => {
+                messages.push(shown(*index))
+            }
+            ReplayAction::ApplyReactions { index } => {
+                let msg = &source[*index];
+                if let Some(item) = messages.iter_mut().find(|m| m.id == msg.id) {
+                    item.reactions = msg.reactions.clone();
+                    item.animate_reactions = true;
+                }
+            }
+            ReplayAction::TopicEnded { text } => messages.push(Message {
+                id: TOPIC_ENDED_MESSAGE_ID.to_string(),
+                sender_id: user_id.to_string(),
+                content: text.clone(),
+                kind: MessageKind::TopicEnded,
+                animate: true,
+                animate_reactions: false,
+                reactions: Vec::new(),
+                replay: None,
+                mission: None,
+            }),
+            ReplayAction::Reveal { .. }
+            | ReplayAction::ClearPending
+            | ReplayAction::PlaySound { .. } => {}
+        }
+    }
+
+    /// 这个动作之后的输入状态
+    pub fn pending_after(
+        &self,
+        source: &[Message],
+        current: Option<PendingTyping>,
+    ) -> Option<PendingTyping> {
+        match self {
+            ReplayAction::ShowTyping { index } => Some(PendingTyping {
+                id: source[*index].id.clone(),
+                phase: ReplayTypingPhase::Typing,
+            }),
+            ReplayAction::Reveal { index } => Some(PendingTyping {
+                id: source[*index].id.clone(),
+                phase: ReplayTypingPhase::Reveal,
+            }),
+            ReplayAction::ClearPending => None,
+            _ => current,
+        }
+    }
+}
+
+///
+/// 带有绝对时间的回放动作。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayEvent {
+    /// 相对回放开始的时间
+    pub at_ms: u64,
+    pub action: ReplayAction,
+}
+
+///
+/// 回放的时间线，按时间排序。
+///
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct ReplayTimeline {
+    pub events: Vec<ReplayEvent>,
+}
+
+impl ReplayTimeline {
+    /// 最后一个动作的时间
+    pub fn duration_ms(&self) -> u64 {
+        self.events.last().map(|event| event.at_ms).unwrap_or(0)
+    }
+}
+
+///
+/// 根据消息列表和回放设置生成时间线。
+///
+/// # 参数
+///
+/// - messages: 参与回放的消息，从回放的第一条开始。
+/// - user_id: 用户自己的编号，用于区分自己和对方的消息。
+///
+/// 消息自带的回放设置（`Message::replay`）优先于 `settings`。
+///
+pub fn build_timeline(
+    messages: &[Message],
+    user_id: &str,
+    settings: &ReplaySettings,
+) -> ReplayTimeline {
+    let mut events = Vec::new();
+    let mut now = 0u64;
+    let mut push = |at_ms: u64, action: ReplayAction| events.push(ReplayEvent { at_ms, action });
+
+    for (index, msg) in messages.iter().enumerate() {
+        let overrides = msg.replay.clone().unwrap_or_default();
+        now += settings.gap_ms + overrides.pause_before_ms;
+        let is_self = msg.sender_id == user_id;
+        let is_status = matches!(msg.kind, MessageKind::Status);
+        let typing_ms = overrides
+            .typing_ms
+            .unwrap_or_else(|| settings.typing_ms(msg));
+        let show_typing = !overrides.skip_typing && (!is_self || settings.self_typing);
+        let sound = match (is_status, is_self) {
+            (true, _) => SoundEvent::Status,
+            (false, true) => SoundEvent::Outgoing,
+            (false, false) => SoundEvent::Incoming,
+        };
+
+        // 状态消息没有输入过程
+        let shown_at = if is_status {
+            push(now, ReplayAction::Show { index });
+            push(now, ReplayAction::PlaySound { event: sound });
+            now
+        } else if show_typing {
+            push(now, ReplayAction::ShowTyping { index });
+            now += typing_ms;
+            push(now, ReplayAction::Reveal { index });
+            push(now, ReplayAction::PlaySound { event: sound });
+            now += REVEAL_MS;
+            push(now, ReplayAction::ClearPending);
+            now - REVEAL_MS
+        } else {
+            now += typing_ms;
+            push(now, ReplayAction::Show { index });
+            push(now, ReplayAction::PlaySound { event: sound });
+            now
+        };
+
+        if !msg.reactions.is_empty() {
+            // 状态消息的回应默认与消息同时显示
+            let reactions_at = match (overrides.reactions_after_ms, &settings.reaction_timing) {
+                (Some(after_ms), _) => now + after_ms,
+                (None, ReplayReactionTiming::WithMessage) => shown_at,
+                (None, ReplayReactionTiming::AfterMessage) if is_status => now,
+                (None, ReplayReactionTiming::AfterMessage) => now + settings.gap_ms,
+            };
+            push(reactions_at, ReplayAction::ApplyReactions { index });
+            now = now.max(reactions_at);
+        }
+        if is_status {
+            now += settings.status_pause_ms;
+        }
+        now += overrides.pause_after_ms;
+    }
+
+    if !settings.topic_ended_text.is_empty() {
+        push(
+            now,
+            ReplayAction::TopicEnded {
+                text: settings.topic_ended_text.clone(),
+            },
+        );
+    }
+    // 与消息同时出现的回应可能早于“输入中”的清除, 同一时刻的动作保持原有顺序
+    events.sort_by_key(|event| event.at_ms);
+    ReplayTimeline { events }
+}
+
+/// 可选的回放速度
+pub const REPLAY_SPEEDS: [f64; 5] = [0.5, 0.75, 1.0, 1.5, 2.0];
+
+///
+/// 回放的播放状态：时间线、当前位置、暂停与速度。
+///
+/// 位置 `cursor` 表示已经执行了时间线中的前多少个动作。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayPlayer {
+    pub timeline: ReplayTimeline,
+    /// 参与回放的消息
+    pub source: Vec<Message>,
+    pub user_id: String,
+    pub cursor: usize,
+    pub paused: bool,
+    /// 速度倍率，作用于所有间隔
+    pub speed: f64,
+    /// 下一个动作已经等待的进度，暂停或变速之后只需等待剩下的部分
+    wait: Option<ReplayWait>,
+}
+
+/// 对时间线中某个动作的等待进度
+#[derive(Clone, Debug, PartialEq)]
+struct ReplayWait {
+    /// 等待的动作在时间线中的位置
+    cursor: usize,
+    /// 此前已经等过的时间线毫秒数，按当时的速度换算
+    waited_ms: f64,
+    /// 正在等待时开始等待的时刻
+    started_at_ms: Option<i64>,
+}
+
+impl ReplayPlayer {
+    pub fn new(source: Vec<Message>, user_id: String, settings: &ReplaySettings) -> Self {
+        Self {
+            timeline: build_timeline(&source, &user_id, settings),
+            source,
+            user_id,
+            cursor: 0,
+            paused: false,
+            speed: 1.0,
+            wait: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.timeline.events.len()
+    }
+
+    /// 下一个动作，以及按当前速度执行它之前需要等待的时间
+    pub fn next_event(&self) -> Option<(u64, &ReplayEvent)> {
+        let event = self.timeline.events.get(self.cursor)?;
+        let previous_ms = match self.cursor {
+            0 => 0,
+            cursor => self.timeline.events[cursor - 1].at_ms,
+        };
+        let waited_ms = match &self.wait {
+            Some(wait) if wait.cursor == self.cursor => wait.waited_ms,
+            _ => 0.0,
+        };
+        let remaining_ms = ((event.at_ms - previous_ms) as f64 - waited_ms).max(0.0);
+        let delay = remaining_ms / self.speed.max(0.01);
+        Some((delay.round() as u64, event))
+    }
+
+    /// 跳转到时间线中的某个位置，放弃已经等待的进度
+    pub fn seek(&mut self, cursor: usize) {
+        self.cursor = cursor.min(self.timeline.events.len());
+        self.wait = None;
+    }
+
+    /// 记录从 `now_ms` 开始等待下一个动作
+    pub fn begin_wait(&mut self, now_ms: i64) {
+        match &mut self.wait {
+            Some(wait) if wait.cursor == self.cursor => wait.started_at_ms = Some(now_ms),
+            wait => {
+                *wait = Some(ReplayWait {
+                    cursor: self.cursor,
+                    waited_ms: 0.0,
+                    started_at_ms: Some(now_ms),
+                })
+            }
+        }
+    }
+
+    /// 在 `now_ms` 中断等待，按当前速度把已经过去的时间记入进度
+    pub fn interrupt_wait(&mut self, now_ms: i64) {
+        let speed = self.speed.max(0.01);
+        let Some(wait) = &mut self.wait else {
+            return;
+        };
+        if let Some(started_at_ms) = wait.started_at_ms.take() {
+            wait.waited_ms += (now_ms - started_at_ms).max(0) as f64 * speed;
+        }
+    }
+
+    /// 执行完第 `index` 条消息的所有动作之后的位置
+    pub fn message_end(&self, index: usize) -> usize {
+        self.timeline
+            .events
+            .iter()
+            .position(|event| match event.action {
+                ReplayAction::ShowTyping { index: i } | ReplayAction::Show { index: i } => {
+                    i > index
+                }
+                ReplayAction::TopicEnded { .. } => true,
+                _ => false,
+            })
+            .unwrap_or(self.timeline.events.len())
+    }
+
+    /// 前 `count` 条消息播放完之后的位置
+    pub fn position_after(&self, count: usize) -> usize {
+        match count {
+            0 => 0,
+            count => self.message_end(count - 1),
+        }
+    }
+
+    /// 每条消息开始之前的位置，以及回放结束的位置
+    fn boundaries(&self) -> Vec<usize> {
+        let mut boundaries = vec![0];
+        boundaries.extend((0..self.source.len()).map(|index| self.message_end(index)));
+        boundaries.push(self.timeline.events.len());
+        boundaries.dedup();
+        boundaries
+    }
+
+    /// 下一条消息播放完之后的位置
+    pub fn next_boundary(&self) -> usize {
+        self.boundaries()
+            .into_iter()
+            .find(|boundary| *boundary > self.cursor)
+            .unwrap_or(self.timeline.events.len())
+    }
+
+    /// 当前消息开始之前的位置；正好在消息之间时则为上一条消息开始之前
+    pub fn previous_boundary(&self) -> usize {
+        self.boundaries()
+            .into_iter()
+            .rev()
+            .find(|boundary| *boundary < self.cursor)
+            .unwrap_or(0)
+    }
+
+    /// 已经开始显示的消息数
+    pub fn shown_count(&self) -> usize {
+        self.timeline.events[..self.cursor]
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event.action,
+                    ReplayAction::ShowTyping { .. } | ReplayAction::Show { .. }
+                )
+            })
+            .count()
+    }
+
+    /// 当前位置的消息列表和输入状态，不带入场动画
+    pub fn snapshot(&self) -> (Vec<Message>, Option<PendingTyping>) {
+        let mut messages = Vec::new();
+        let mut pending = None;
+        for event in &self.timeline.events[..self.cursor] {
+            event
+                .action
+                .apply_to_messages(&self.source, &self.user_id, &mut messages);
+            pending = event.action.pending_after(&self.source, pending);
+        }
+        for msg in &mut messages {
+            msg.animate = false;
+            msg.animate_reactions = false;
+        }
+        (messages, pending)
+    }
+}
+
+///
+/// 播放列表的进度：依次回放每个会话，整体可以暂停或取消。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlaylistSession {
+    pub contact_ids: Vec<String>,
+    /// 正在回放的会话在 `contact_ids` 中的下标
+    pub index: usize,
+    pub pause_ms: u64,
+    pub paused: bool,
+}
+
+impl PlaylistSession {
+    ///
+    /// 按播放列表开始新的进度，跳过已经不存在的会话；没有可回放的会话时返回 `None`。
+    ///
+    /// # 参数
+    ///
+    /// - playlist: 播放列表。
+    /// - exists: 判断会话是否存在。
+    ///
+    pub fn new(playlist: &ReplayPlaylist, exists: impl Fn(&str) -> bool) -> Option<Self> {
+        let contact_ids = playlist
+            .contact_ids
+            .iter()
+            .filter(|id| exists(id))
+            .cloned()
+            .collect::<Vec<_>>();
+        if contact_ids.is_empty() {
+            return None;
+        }
+        Some(Self {
+            contact_ids,
+            index: 0,
+            pause_ms: playlist.pause_ms,
+            paused: false,
+        })
+    }
+
+    /// 正在回放的会话
+    pub fn current(&self) -> Option<&str> {
+        self.contact_ids.get(self.index).map(String::as_str)
+    }
+
+    /// 切换到下一个会话；已经是最后一个时返回 `None`
+    pub fn advance(&mut self) -> Option<&str> {
+        self.index = (self.index + 1).min(self.contact_ids.len());
+        self.current()
+    }
+}
@@ -0,0 +1,186 @@
+use crate::components::baker::replay::{PlaylistSession, REPLAY_SPEEDS, ReplayPlayer};
+use dioxus::prelude::*;
+
+/// 把毫秒写成“分:秒”
+fn format_duration(ms: u64) -> String {
+    let secs = ms.div_ceil(1000);
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+///
+/// 回放控制条。
+///
+/// # 参数
+///
+/// - player: 当前回放的播放状态。
+/// - on_toggle_pause: 处理暂停/继续的事件；回放结束后则为从头重播。
+/// - on_step_back: 处理后退一条消息的事件。
+/// - on_step_forward: 处理前进一条消息的事件。
+/// - on_seek: 处理跳转的事件，参数为跳转后已显示的消息数。
+/// - on_speed: 处理修改速度倍率的事件。
+/// - muted: 回放时是否静音。
+/// - on_toggle_mute: 处理切换回放静音的事件。
+/// - on_exit: 处理退出回放的事件。
+///
+#[component]
+pub fn ReplayControlBar(
+    player: ReadSignal<Option<ReplayPlayer>>,
+    on_toggle_pause: EventHandler<()>,
+    on_step_back: EventHandler<()>,
+    on_step_forward: EventHandler<()>,
+    on_seek: EventHandler<usize>,
+    on_speed: EventHandler<f64>,
+    muted: bool,
+    on_toggle_mute: EventHandler<()>,
+    on_exit: EventHandler<()>,
+) -> Element {
+    let Some((shown, total, paused, finished, speed, duration_ms)) =
+        player.read().as_ref().map(|p| {
+            (
+                p.shown_count(),
+                p.source.len(),
+                p.paused,
+                p.is_finished(),
+                p.speed,
+                p.timeline.duration_ms(),
+            )
+        })
+    else {
+        return rsx! {};
+    };
+
+    let play_label = if finished {
+        "重播"
+    } else if paused {
+        "继续"
+    } else {
+        "暂停"
+    };
+    // 按当前速度播放完整个回放所需的时间
+    let duration = format_duration((duration_ms as f64 / speed.max(0.01)).round() as u64);
+    let button_class = "px-3 py-1 rounded text-sm text-white bg-[#3a3a3a] hover:bg-[#4a4a4a] transition-colors cursor-pointer";
+
+    rsx! {
+        div { class: "flex items-center gap-3 px-4 py-2 bg-[#2b2b2b]/90 border border-gray-600 backdrop-blur-sm select-none",
+            button {
+                class: button_class,
+                title: "后退一条",
+                onclick: move |_| on_step_back.call(()),
+                "⏮"
+            }
+            button {
+                class: "px-4 py-1 rounded text-sm font-medium text-black bg-[#fdfc00] hover:bg-[#fdfc00]/60 transition-colors cursor-pointer",
+                onclick: move |_| on_toggle_pause.call(()),
+                {play_label}
+            }
+            button {
+                class: button_class,
+                title: "前进一条",
+                onclick: move |_| on_step_forward.call(()),
+                "⏭"
+            }
+            input {
+                class: "flex-1 accent-[#fdfc00] cursor-pointer",
+                r#type: "range",
+                min: "0",
+                max: "{total}",
+                value: "{shown}",
+                onchange: move |e| {
+                    if let Ok(target) = e.value().parse::<usize>() {
+                        on_seek.call(target);
+                    }
+                },
+            }
+            span { class: "text-gray-300 text-xs tabular-nums w-16 text-right", "{shown} / {total}" }
+            span {
+                class: "text-gray-400 text-xs tabular-nums",
+                title: "按当前速度回放全部消息的时长",
+                "共 {duration}"
+            }
+            select {
+                class: "bg-[#222] border border-gray-600 rounded px-2 py-1 text-white text-xs focus:outline-none cursor-pointer",
+                value: "{speed}",
+                onchange: move |e| {
+                    if let Ok(speed) = e.value().parse::<f64>() {
+                        on_speed.call(speed);
+                    }
+                },
+                for option_speed in REPLAY_SPEEDS {
+                    option {
+                        value: "{option_speed}",
+                        selected: option_speed == speed,
+                        "{option_speed}x"
+                    }
+                }
+            }
+            button {
+                class: button_class,
+                title: if muted { "打开回放声音" } else { "回放静音" },
+                onclick: move |_| on_toggle_mute.call(()),
+                if muted {
+                    "🔇"
+                } else {
+                    "🔊"
+                }
+            }
+            button {
+                class: button_class,
+                onclick: move |_| on_exit.call(()),
+                "退出回放"
+            }
+        }
+    }
+}
+
+///
+/// 播放列表的控制条，显示在回放控制条上方。
+///
+/// # 参数
+///
+/// - session: 播放列表的进度。
+/// - contact_name: 正在回放的会话的名字。
+/// - on_toggle_pause: 处理整体暂停/继续的事件。
+/// - on_skip: 处理直接切换到下一个会话的事件。
+/// - on_exit: 处理结束整个播放列表的事件。
+///
+#[component]
+pub fn PlaylistBar(
+    session: PlaylistSession,
+    contact_name: String,
+    on_toggle_pause: EventHandler<()>,
+    on_skip: EventHandler<()>,
+    on_exit: EventHandler<()>,
+) -> Element {
+    let position = session.index + 1;
+    let total = session.contact_ids.len();
+    let is_last = position >= total;
+    let play_label = if session.paused {
+        "继续列表"
+    } else {
+        "暂停列表"
+    };
+    let button_class = "px-3 py-1 rounded text-sm text-white bg-[#3a3a3a] hover:bg-[#4a4a4a] disabled:opacity-40 transition-colors cursor-pointer";
+
+    rsx! {
+        div { class: "flex items-center gap-3 px-4 py-2 bg-[#2b2b2b]/90 border border-gray-600 backdrop-blur-sm select-none",
+            span { class: "text-gray-300 text-xs tabular-nums", "播放列表 {position} / {total}" }
+            span { class: "flex-1 text-white text-sm truncate", "{contact_name}" }
+            button {
+                class: "px-4 py-1 rounded text-sm font-medium text-black bg-[#fdfc00] hover:bg-[#fdfc00]/60 transition-colors cursor-pointer",
+                onclick: move |_| on_toggle_pause.call(()),
+                {play_label}
+            }
+            button {
+                class: button_class,
+                disabled: is_last,
+                onclick: move |_| on_skip.call(()),
+                "下一个会话"
+            }
+            button {
+                class: button_class,
+                onclick: move |_| on_exit.call(()),
+                "结束列表"
+            }
+        }
+    }
+}
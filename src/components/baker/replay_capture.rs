@@ -0,0 +1,598 @@
+use dioxus::prelude::*;
+
+use crate::components::baker::capture_pages::zip_store;
+use crate::components::baker::chat_area::{ChatArea, PendingTyping};
+use crate::components::baker::layout::sleep_ms;
+use crate::components::baker::modals::Modal;
+use crate::components::baker::replay::{ReplayEnd, ReplayPlayer, ReplaySettings, replay_range};
+use crate::components::baker::replay_export::{
+    AnimationFormat, EXPORT_FRAME_RATES, RenderedFrame, encode_animation, encode_png_sequence,
+    export_frames, frame_ticks, normalize_frames,
+};
+use crate::components::baker::save::save_files;
+use crate::components::baker::storage::v2::{AppState, Message};
+use crate::components::baker::subtitles::{SubtitleFormat, render_subtitles, subtitle_cues};
+use crate::components::baker::{
+    Route, bytes_from_data_url, capture, data_url_from_bytes, download_image, message_preview,
+};
+use anyhow::Context;
+
+/// 修改画面后等待界面渲染和滚动完成的时间
+pub(super) const RENDER_SETTLE_MS: u64 = 120;
+
+/// 下载成功后的提示
+const DOWNLOADED_MESSAGE: &str = "已下载到用户的下载目录中。";
+
+/// 导出选项
+#[derive(Clone, Copy)]
+struct ExportOptions {
+    speed: f64,
+    hold_ms: u64,
+    fps: u32,
+    scale: f64,
+    format: AnimationFormat,
+}
+
+/// 渲染每一帧时改动的信号
+#[derive(Clone, Copy)]
+struct FrameTarget {
+    messages: Signal<Vec<Message>>,
+    pending: Signal<Option<PendingTyping>>,
+    need_to_scroll_down: Signal<bool>,
+    progress: Signal<Option<(usize, usize)>>,
+}
+
+/// 逐帧设置聊天区域的内容并截图
+async fn render_frames(
+    player: &ReplayPlayer,
+    options: ExportOptions,
+    mut target: FrameTarget,
+) -> anyhow::Result<Vec<RenderedFrame>> {
+    let frames = export_frames(player, options.hold_ms);
+    let ticks = frame_ticks(&frames, options.fps);
+    let total = ticks.iter().filter(|ticks| **ticks > 0).count();
+
+    let mut probe = player.clone();
+    let mut rendered = Vec::with_capacity(total);
+    for (frame, ticks) in frames.iter().zip(ticks) {
+        if ticks == 0 {
+            continue;
+        }
+        target.progress.set(Some((rendered.len(), total)));
+        probe.cursor = frame.cursor;
+        let (messages, pending) = probe.snapshot();
+        target.messages.set(messages);
+        target.pending.set(pending);
+        target.need_to_scroll_down.set(true);
+        sleep_ms(RENDER_SETTLE_MS).await;
+
+        let src = capture("#chat_area", options.scale)
+            .await
+            .context("截图失败")?;
+        let bytes = bytes_from_data_url(&src).context("无法读取截图")?;
+        let image = image::load_from_memory(&bytes)?.to_rgba8();
+        rendered.push(RenderedFrame { image, ticks });
+    }
+    normalize_frames(&mut rendered);
+    Ok(rendered)
+}
+
+/// 导出的结果
+struct ExportResult {
+    /// 保存成功后的提示，取消保存时为空
+    message: Option<String>,
+    /// 可以预览的动画
+    preview: Option<String>,
+}
+
+/// 导出整个回放并下载
+async fn export_replay(
+    source: Vec<Message>,
+    user_id: String,
+    settings: ReplaySettings,
+    options: ExportOptions,
+    target: FrameTarget,
+) -> anyhow::Result<ExportResult> {
+    let format = options.format;
+    let mut player = ReplayPlayer::new(source, user_id, &settings);
+    player.speed = options.speed;
+    let frames = render_frames(&player, options, target).await?;
+
+    if format == AnimationFormat::PngSequence {
+        // 整个序列打包为一个文件，避免逐帧下载
+        let sequence = encode_png_sequence(&frames)?;
+        let files = vec![("replay.zip".to_string(), zip_store(&sequence)?)];
+        let outcome = save_files(files, "application/zip").await?;
+        return Ok(ExportResult {
+            message: outcome.message(),
+            preview: None,
+        });
+    }
+
+    let bytes = encode_animation(&frames, options.fps, format)?;
+    let url = data_url_from_bytes(format.mime(), bytes);
+    download_image(
+        &url,
+        format.extension(),
+        &format!("replay.{}", format.extension()),
+    )
+    .await?;
+    Ok(ExportResult {
+        message: Some(DOWNLOADED_MESSAGE.to_string()),
+        preview: Some(url),
+    })
+}
+
+///
+/// 将回放导出为动图或 PNG 序列的页面。
+///
+/// 按回放时间线逐个画面在屏幕外渲染聊天区域并截图，再按帧率编码。
+///
+/// # 参数
+///
+/// - contact_id: 会话编号。
+///
+#[component]
+pub(super) fn ReplayCapturePage(contact_id: String) -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let navigator = navigator();
+
+    let contact = app_state
+        .read()
+        .contacts
+        .iter()
+        .find(|x| x.id == contact_id)
+        .cloned();
+    let all_messages = app_state
+        .read()
+        .messages
+        .get(&contact_id)
+        .cloned()
+        .unwrap_or_default();
+    let settings = app_state
+        .read()
+        .replay_settings
+        .get(&contact_id)
+        .cloned()
+        .unwrap_or_default();
+    let user_profile = app_state.read().user_profile.clone();
+
+    let operators = use_signal(move || app_state.read().operators.clone());
+    let stickers = use_memo(move || app_state.read().stickers.clone());
+    let menu_close_token = use_signal(|| 0usize);
+    let need_to_scroll_down = use_signal(|| true);
+    let frame_messages = use_signal(Vec::<Message>::new);
+    let frame_pending = use_signal(|| Option::<PendingTyping>::None);
+
+    let mut width = use_signal(|| 800i64);
+    let mut height = use_signal(|| 900i64);
+    let mut scale = use_signal(|| 1.0f64);
+    let mut fps = use_signal(|| 15u32);
+    let mut speed = use_signal(|| 1.0f64);
+    let mut hold_ms = use_signal(|| "2000".to_string());
+    let mut format = use_signal(|| AnimationFormat::Gif);
+    let mut subtitle_format = use_signal(|| SubtitleFormat::Srt);
+    let mut start_id = use_signal(|| all_messages.first().map(|m| m.id.clone()));
+    let mut end = use_signal(ReplayEnd::default);
+
+    let progress = use_signal(|| Option::<(usize, usize)>::None);
+    let mut preview = use_signal(|| Option::<String>::None);
+    let mut error_message = use_signal(|| Option::<String>::None);
+    let mut success_message = use_signal(|| Option::<String>::None);
+
+    let Some(contact_val) = contact else {
+        navigator.push(Route::BakerLayout {});
+        return rsx! {};
+    };
+
+    let range = replay_range(
+        &all_messages,
+        start_id().as_deref().unwrap_or_default(),
+        &end(),
+    );
+    let first_prev_sender_id = range
+        .start
+        .checked_sub(1)
+        .map(|index| all_messages[index].sender_id.clone());
+    let message_options = all_messages
+        .iter()
+        .enumerate()
+        .map(|(index, msg)| {
+            (
+                msg.id.clone(),
+                format!("{}. {}", index + 1, message_preview(msg)),
+            )
+        })
+        .collect::<Vec<_>>();
+    let end_options = message_options[range.start.min(message_options.len())..].to_vec();
+    let exporting = progress().is_some();
+
+    let export_subtitles = {
+        let source = all_messages[range.clone()].to_vec();
+        let user_profile = user_profile.clone();
+        let settings = settings.clone();
+        move |_| {
+            let mut player = ReplayPlayer::new(source.clone(), user_profile.id.clone(), &settings);
+            player.speed = speed();
+            let hold = hold_ms().trim().parse::<u64>().unwrap_or(2000);
+            let cues = subtitle_cues(&player, hold, |sender_id| {
+                if sender_id == user_profile.id {
+                    return user_profile.name.clone();
+                }
+                operators
+                    .read()
+                    .iter()
+                    .find(|op| op.id == sender_id)
+                    .map(|op| op.name.clone())
+                    .unwrap_or_default()
+            });
+            let format = subtitle_format();
+            let text = render_subtitles(&cues, format);
+            let url = data_url_from_bytes(
+                &format!("{};charset=utf-8", format.mime()),
+                text.into_bytes(),
+            );
+            spawn(async move {
+                let filename = format!("replay.{}", format.extension());
+                match download_image(&url, format.extension(), &filename).await {
+                    Ok(()) => success_message.set(Some(DOWNLOADED_MESSAGE.to_string())),
+                    Err(err) => {
+                        error!("export subtitles failed: {err:?}");
+                        error_message.set(Some(err.to_string()));
+                    }
+                }
+            });
+        }
+    };
+
+    let start_export = {
+        let source = all_messages[range].to_vec();
+        let user_id = user_profile.id.clone();
+        move |_| {
+            if progress().is_some() {
+                return;
+            }
+            let source = source.clone();
+            let user_id = user_id.clone();
+            let settings = settings.clone();
+            let options = ExportOptions {
+                speed: speed(),
+                hold_ms: hold_ms().trim().parse::<u64>().unwrap_or(2000),
+                fps: fps(),
+                scale: scale(),
+                format: format(),
+            };
+            let target = FrameTarget {
+                messages: frame_messages,
+                pending: frame_pending,
+                need_to_scroll_down,
+                progress,
+            };
+            error_message.set(None);
+            preview.set(None);
+            spawn(async move {
+                let result = export_replay(source, user_id, settings, options, target).await;
+                let mut progress = target.progress;
+                progress.set(None);
+                match result {
+                    Ok(result) => {
+                        preview.set(result.preview);
+                        success_message.set(result.message);
+                    }
+                    Err(err) => {
+                        error!("export replay failed: {err:?}");
+                        error_message.set(Some(err.to_string()));
+                    }
+                }
+            });
+        }
+    };
+
+    let input_class = "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30 resize-none";
+
+    rsx! {
+        if let Some(message) = success_message() {
+            Modal {
+                title: "操作成功",
+                content_confirmation_button: "好",
+                on_close: move |_| success_message.set(None),
+                on_confirm: move |_| success_message.set(None),
+
+                {
+                    rsx! {
+                        p { class: "text-black break-all", "{message}" }
+                    }
+                }
+            }
+        }
+        if let Some(message) = error_message() {
+            Modal {
+                title: "导出失败",
+                content_confirmation_button: "好",
+                on_close: move |_| error_message.set(None),
+                on_confirm: move |_| error_message.set(None),
+
+                {
+                    rsx! {
+                        p { class: "text-black break-all", "{message}" }
+                    }
+                }
+            }
+        }
+        div {
+            class: "flex flex-col",
+            style: "transform: translateX(-325000px) translateY(-325000px); overflow: hidden",
+            position: "absolute",
+            width: "{width}px",
+            height: "{height}px",
+            ChatArea {
+                contact: contact_val,
+                operators,
+                messages: frame_messages,
+                user_profile,
+                menu_close_token,
+                first_prev_sender_id,
+                force_first_avatar: true,
+                pending_typing: frame_pending,
+                need_to_scroll_down,
+                on_send_message: move |_| {},
+                on_send_other_message: move |_| {},
+                on_send_status: move |_| {},
+                on_send_image: move |_| {},
+                on_send_sticker: move |_| {},
+                on_send_sticker_other: move |_| {},
+                on_send_mission: move |_| {},
+                stickers,
+                on_add_sticker: move |_| {},
+                on_delete_message: move |_| {},
+                on_edit_message: move |_| {},
+                on_edit_mission: move |_| {},
+                on_add_reaction: move |_| {},
+                on_delete_reaction: move |_| {},
+                on_insert_message: move |_| {},
+                on_edit_replay_overrides: move |_| {},
+                on_start_replay: move |_| {},
+                on_update_chat_head_style: move |_| {},
+                on_clear_messages: move |_| {},
+                on_clear_chat: move |_| {},
+                on_set_group_ops_list: move |_| {},
+                on_send_image_other: move |_| {},
+                is_replaying: true,
+                hide_input_bar: false,
+                hide_header: false,
+                on_exit_replay: move |_| {},
+            }
+        }
+        div {
+            width: "100%",
+            height: "100vh",
+            display: "flex",
+            flex_direction: "column",
+            position: "relative",
+            overflow: "hidden",
+            div { class: "h-14 shrink-0 flex items-center gap-3 px-6 border-b border-gray-600 bg-[#1f1f1f]/80 backdrop-blur-sm",
+                button {
+                    class: "text-gray-300 hover:text-white text-lg px-2 py-1 rounded-lg hover:bg-white/5 transition-colors",
+                    onclick: move |_| {
+                        navigator.push(Route::BakerLayout {});
+                    },
+                    "←"
+                }
+                h1 { class: "text-white text-lg font-bold", "导出回放动画" }
+            }
+            div {
+                width: "100%",
+                flex: "1",
+                display: "flex",
+                gap: "16px",
+                padding: "16px",
+                box_sizing: "border-box",
+                overflow_x: "hidden",
+                overflow_y: "auto",
+                div {
+                    flex: "1 1 auto",
+                    min_width: "200px",
+                    min_height: "200px",
+                    class: "space-y-4",
+                    div { class: "space-y-1",
+                        label { class: "block text-white text-sm", "从这条消息开始" }
+                        select {
+                            class: input_class,
+                            onchange: move |e| start_id.set(Some(e.value())),
+                            for (id, label) in message_options {
+                                option {
+                                    selected: start_id().as_ref() == Some(&id),
+                                    value: "{id}",
+                                    "{label}"
+                                }
+                            }
+                        }
+                    }
+                    div { class: "space-y-1",
+                        label { class: "block text-white text-sm", "回放终点" }
+                        select {
+                            class: input_class,
+                            value: end().option_value(),
+                            onchange: move |e| end.set(ReplayEnd::from_option_value(&e.value())),
+                            option { value: ReplayEnd::Last.option_value(), "播放到最后" }
+                            option { value: ReplayEnd::NextMarker.option_value(),
+                                "在下一条状态或话题结束提示前停止"
+                            }
+                            for (id, label) in end_options {
+                                option { value: ReplayEnd::Message(id).option_value(),
+                                    "播放到：{label}"
+                                }
+                            }
+                        }
+                    }
+                    div { class: "flex gap-3",
+                        div { class: "flex-1 space-y-1",
+                            label { class: "block text-white text-sm", "宽度" }
+                            input {
+                                class: input_class,
+                                r#type: "number",
+                                min: "200",
+                                step: "100",
+                                value: "{width}",
+                                oninput: move |e| width.set(e.value().parse().unwrap_or(0)),
+                            }
+                        }
+                        div { class: "flex-1 space-y-1",
+                            label { class: "block text-white text-sm", "高度" }
+                            input {
+                                class: input_class,
+                                r#type: "number",
+                                min: "200",
+                                step: "100",
+                                value: "{height}",
+                                oninput: move |e| height.set(e.value().parse().unwrap_or(0)),
+                            }
+                        }
+                    }
+                    div { class: "space-y-1",
+                        label { class: "block text-white text-sm",
+                            "缩放倍率（如2x即为以两倍的分辨率截图）"
+                        }
+                        input {
+                            class: input_class,
+                            r#type: "number",
+                            min: "0.1",
+                            max: "4.0",
+                            step: "0.1",
+                            value: "{scale}",
+                            oninput: move |e| {
+                                if let Ok(num) = e.value().parse::<f64>() {
+                                    scale.set(num.clamp(0.1, 4.0))
+                                }
+                            },
+                        }
+                    }
+                    div { class: "flex gap-3",
+                        div { class: "flex-1 space-y-1",
+                            label { class: "block text-white text-sm", "格式" }
+                            select {
+                                class: input_class,
+                                onchange: move |e| {
+                                    if let Some(value) = AnimationFormat::ALL
+                                        .into_iter()
+                                        .find(|f| f.label() == e.value())
+                                    {
+                                        format.set(value);
+                                    }
+                                },
+                                for option_format in AnimationFormat::ALL {
+                                    option {
+                                        value: option_format.label(),
+                                        selected: option_format == format(),
+                                        {option_format.label()}
+                                    }
+                                }
+                            }
+                        }
+                        div { class: "flex-1 space-y-1",
+                            label { class: "block text-white text-sm", "帧率" }
+                            select {
+                                class: input_class,
+                                onchange: move |e| {
+                                    if let Ok(value) = e.value().parse::<u32>() {
+                                        fps.set(value);
+                                    }
+                                },
+                                for option_fps in EXPORT_FRAME_RATES {
+                                    option {
+                                        value: "{option_fps}",
+                                        selected: option_fps == fps(),
+                                        "{option_fps} fps"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    div { class: "flex gap-3",
+                        div { class: "flex-1 space-y-1",
+                            label { class: "block text-white text-sm", "速度倍率" }
+                            input {
+                                class: input_class,
+                                r#type: "number",
+                                min: "0.25",
+                                max: "4.0",
+                                step: "0.25",
+                                value: "{speed}",
+                                oninput: move |e| {
+                                    if let Ok(num) = e.value().parse::<f64>() {
+                                        speed.set(num.clamp(0.25, 4.0))
+                                    }
+                                },
+                            }
+                        }
+                        div { class: "flex-1 space-y-1",
+                            label { class: "block text-white text-sm", "结尾停留 (ms)" }
+                            input {
+                                class: input_class,
+                                r#type: "number",
+                                min: "0",
+                                value: "{hold_ms}",
+                                oninput: move |e| hold_ms.set(e.value()),
+                            }
+                        }
+                    }
+                    p { class: "text-gray-400 text-xs",
+                        "输入时长等按这个会话上次使用的回放设置计算。"
+                    }
+                    div { class: "flex gap-3 items-end pt-6",
+                        div { class: "flex-1 space-y-1",
+                            label { class: "block text-white text-sm", "字幕格式" }
+                            select {
+                                class: input_class,
+                                onchange: move |e| {
+                                    if let Some(value) = SubtitleFormat::ALL
+                                        .into_iter()
+                                        .find(|f| f.label() == e.value())
+                                    {
+                                        subtitle_format.set(value);
+                                    }
+                                },
+                                for option_format in SubtitleFormat::ALL {
+                                    option {
+                                        value: option_format.label(),
+                                        selected: option_format == subtitle_format(),
+                                        {option_format.label()}
+                                    }
+                                }
+                            }
+                        }
+                        button {
+                            class: "flex-1 bg-[#3a3a3a] hover:bg-[#4a4a4a] text-white py-3 rounded text-sm font-medium transition-colors",
+                            onclick: export_subtitles,
+                            "导出字幕"
+                        }
+                    }
+                    p { class: "text-gray-400 text-xs",
+                        "字幕在每条消息显示内容时出现，速度和结尾停留与上面的设置相同，可与同样设置下的录屏对齐。"
+                    }
+                    div { class: "space-y-1 pt-6",
+                        button {
+                            class: "w-full bg-blue-600 hover:bg-blue-500 disabled:opacity-50 text-white py-2 rounded text-sm font-medium transition-colors",
+                            disabled: exporting,
+                            onclick: start_export,
+                            if let Some((done, total)) = progress() {
+                                "正在渲染 {done} / {total} 帧……"
+                            } else {
+                                "导出并下载"
+                            }
+                        }
+                    }
+                }
+                div {
+                    flex: "0 1 40%",
+                    width: "40%",
+                    min_width: "500px",
+                    min_height: 0,
+                    if let Some(src) = preview() {
+                        img { class: "w-full", src }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use crate::components::baker::capture_pages::page_filename;
+use crate::components::baker::replay::ReplayPlayer;
+use anyhow::{Context, ensure};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{Delay, ExtendedColorType, Frame, ImageEncoder, RgbaImage};
+
+/// 可选的帧率
+pub const EXPORT_FRAME_RATES: [u32; 4] = [10, 15, 24, 30];
+
+///
+/// 回放导出的格式。
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnimationFormat {
+    Gif,
+    Apng,
+    WebP,
+    /// 按帧率展开的编号 PNG 序列，方便导入视频剪辑软件
+    PngSequence,
+}
+
+impl AnimationFormat {
+    pub const ALL: [AnimationFormat; 4] = [
+        AnimationFormat::Gif,
+        AnimationFormat::Apng,
+        AnimationFormat::WebP,
+        AnimationFormat::PngSequence,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "GIF",
+            AnimationFormat::Apng => "APNG",
+            AnimationFormat::WebP => "WebP",
+            AnimationFormat::PngSequence => "PNG 序列",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "gif",
+            AnimationFormat::Apng | AnimationFormat::PngSequence => "png",
+            AnimationFormat::WebP => "webp",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "image/gif",
+            AnimationFormat::Apng => "image/apng",
+            AnimationFormat::WebP => "image/webp",
+            AnimationFormat::PngSequence => "image/png",
+        }
+    }
+}
+
+///
+/// 导出的一帧：执行完时间线的前 `cursor` 个动作之后的画面。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportFrame {
+    pub cursor: usize,
+    /// 相对回放开始的时间，已按速度换算
+    pub start_ms: u64,
+    /// 画面持续的时间，已按速度换算
+    pub duration_ms: u64,
+}
+
+///
+/// 把回放拆成画面互不相同的帧。
+///
+/// 同一时刻的动作合并为一帧；不改变画面的动作（例如提示音）不会产生新的帧。
+///
+/// # 参数
+///
+/// - player: 回放的播放状态，只使用其中的时间线和速度。
+/// - hold_ms: 最后一帧停留的时间。
+///
+pub fn export_frames(player: &ReplayPlayer, hold_ms: u64) -> Vec<ExportFrame> {
+    let speed = player.speed.max(0.01);
+    let scaled = |ms: u64| (ms as f64 / speed).round() as u64;
+    let events = &player.timeline.events;
+
+    let mut candidates = vec![(0, 0)];
+    for (index, event) in events.iter().enumerate() {
+        let last_at_this_time = events
+            .get(index + 1)
+            .is_none_or(|next| next.at_ms != event.at_ms);
+        if last_at_this_time {
+            candidates.push((index + 1, scaled(event.at_ms)));
+        }
+    }
+
+    let mut probe = player.clone();
+    let mut frames: Vec<ExportFrame> = Vec::new();
+    let mut previous = None;
+    for (cursor, start_ms) in candidates {
+        probe.cursor = cursor;
+        let state = probe.snapshot();
+        if previous.as_ref() == Some(&state) {
+            continue;
+        }
+        previous = Some(state);
+        match frames.last_mut() {
+            // 时长为 0 的画面直接被下一帧取代
+            Some(last) if last.start_ms == start_ms => last.cursor = cursor,
+            _ => frames.push(ExportFrame {
+                cursor,
+                start_ms,
+                duration_ms: 0,
+            }),
+        }
+    }
+
+    for index in 0..frames.len() {
+        frames[index].duration_ms = match frames.get(index + 1) {
+            Some(next) => next.start_ms - frames[index].start_ms,
+            None => hold_ms,
+        };
+    }
+    frames
+}
+
+///
+/// 按帧率把每帧的时长换算为帧数。
+///
+/// 按累计时间取整，误差不会随帧数累积；短于一帧的画面会得到 0，导出时被跳过。
+///
+pub fn frame_ticks(frames: &[ExportFrame], fps: u32) -> Vec<u32> {
+    let fps = u64::from(fps.max(1));
+    let tick_at = |ms: u64| (ms * fps + 500) / 1000;
+    frames
+        .iter()
+        .map(|frame| {
+            let end = tick_at(frame.start_ms + frame.duration_ms) - tick_at(frame.start_ms);
+            end.min(u64::from(u32::MAX)) as u32
+        })
+        .collect()
+}
+
+///
+/// 渲染好的一帧及其持续的帧数。
+///
+#[derive(Clone, Debug)]
+pub struct RenderedFrame {
+    pub image: RgbaImage,
+    pub ticks: u32,
+}
+
+///
+/// 把尺寸不一的帧补齐到相同的大小。
+///
+/// 截图的高度可能因为取整相差一两个像素，补齐的部分为透明，画面靠左上对齐。
+///
+pub fn normalize_frames(frames: &mut [RenderedFrame]) {
+    let width = frames.iter().map(|f| f.image.width()).max().unwrap_or(0);
+    let height = frames.iter().map(|f| f.image.height()).max().unwrap_or(0);
+    for frame in frames {
+        if frame.image.dimensions() != (width, height) {
+            let mut canvas = RgbaImage::new(width, height);
+            image::imageops::replace(&mut canvas, &frame.image, 0, 0);
+            frame.image = canvas;
+        }
+    }
+}
+
+///
+/// 把帧编码为动画。
+///
+/// PNG 序列请使用 `encode_png_sequence`。
+///
+/// # 参数
+///
+/// - frames: 尺寸相同的帧，帧数为 0 的会被跳过。
+/// - fps: 帧率，决定每帧的延迟。
+///
+pub fn encode_animation(
+    frames: &[RenderedFrame],
+    fps: u32,
+    format: AnimationFormat,
+) -> anyhow::Result<Vec<u8>> {
+    let frames = frames.iter().filter(|f| f.ticks > 0).collect::<Vec<_>>();
+    ensure!(!frames.is_empty(), "没有可以导出的帧");
+    let fps = fps.max(1);
+    match format {
+        AnimationFormat::Gif => encode_gif(&frames, fps),
+        AnimationFormat::Apng => encode_apng(&frames, fps),
+        AnimationFormat::WebP => encode_webp(&frames, fps),
+        AnimationFormat::PngSequence => anyhow::bail!("PNG 序列不是单个文件"),
+    }
+}
+
+///
+/// 把帧按帧率展开为编号的 PNG 序列，如 `replay-001.png`。
+///
+/// 每个画面只编码一次，按它持续的帧数以连续的编号重复。
+///
+/// # 参数
+///
+/// - frames: 渲染好的帧，帧数为 0 的会被跳过。
+///
+pub fn encode_png_sequence(frames: &[RenderedFrame]) -> anyhow::Result<Vec<(String, Vec<u8>)>> {
+    let total = frames.iter().map(|f| f.ticks as usize).sum::<usize>();
+    let mut sequence = Vec::with_capacity(total);
+    for frame in frames.iter().filter(|f| f.ticks > 0) {
+        let png = encode_png(&frame.image)?;
+        for _ in 0..frame.ticks {
+            let filename = page_filename("replay", sequence.len(), total, "png");
+            sequence.push((filename, png.clone()));
+        }
+    }
+    Ok(sequence)
+}
+
+/// 编码一张 PNG
+pub fn encode_png(image: &RgbaImage) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    PngEncoder::new(&mut out).write_image(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        ExtendedColorType::Rgba8,
+    )?;
+    Ok(out)
+}
+
+fn frame_delay_ms(ticks: u32, fps: u32) -> u64 {
+    u64::from(ticks) * 1000 / u64::from(fps)
+}
+
+fn encode_gif(frames: &[&RenderedFrame], fps: u32) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    {
+        // 调色板量化较慢，用较快的档位
+        let mut encoder = GifEncoder::new_with_speed(&mut out, 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder.encode_frames(frames.iter().map(|frame| {
+            let delay = Delay::from_numer_denom_ms(frame.ticks.saturating_mul(1000), fps);
+            Frame::from_parts(frame.image.clone(), 0, 0, delay)
+        }))?;
+    }
+    Ok(out)
+}
+
+fn encode_apng(frames: &[&RenderedFrame], fps: u32) -> anyhow::Result<Vec<u8>> {
+    let (width, height) = frames[0].image.dimensions();
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frames.len() as u32, 0)?;
+    let mut writer = encoder.write_header()?;
+    for frame in frames {
+        // APNG 的延迟是 u16 分数，过长的画面换算成毫秒
+        match u16::try_from(frame.ticks) {
+            Ok(ticks) if fps <= u32::from(u16::MAX) => writer.set_frame_delay(ticks, fps as u16)?,
+            _ => {
+                let ms = frame_delay_ms(frame.ticks, fps).min(u64::from(u16::MAX));
+                writer.set_frame_delay(ms as u16, 1000)?
+            }
+        }
+        writer.write_image_data(frame.image.as_raw())?;
+    }
+    writer.finish()?;
+    Ok(out)
+}
+
+/// WebP 的 24 位小端整数
+fn push_u24(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.min(0xff_ffff).to_le_bytes()[..3]);
+}
+
+fn push_chunk(buf: &mut Vec<u8>, fourcc: &[u8; 4], data: &[u8]) {
+    buf.extend_from_slice(fourcc);
+    buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        buf.push(0);
+    }
+}
+
+/// 单帧无损 WebP 中的图像数据块 (ALPH / VP8 / VP8L)，用于放进 ANMF 块
+fn webp_frame_data(image: &RgbaImage) -> anyhow::Result<Vec<u8>> {
+    let mut encoded = Vec::new();
+    WebPEncoder::new_lossless(&mut encoded).encode(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        ExtendedColorType::Rgba8,
+    )?;
+
+    let mut data = Vec::new();
+    let mut rest = encoded.get(12..).context("WebP 文件头不完整")?;
+    while rest.len() >= 8 {
+        let fourcc = &rest[..4];
+        let size = u32::from_le_bytes(rest[4..8].try_into()?) as usize;
+        let padded = size + size % 2;
+        let chunk = rest.get(..8 + padded).context("WebP 数据块不完整")?;
+        if matches!(fourcc, b"ALPH" | b"VP8 " | b"VP8L") {
+            data.extend_from_slice(chunk);
+        }
+        rest = &rest[chunk.len()..];
+    }
+    ensure!(!data.is_empty(), "WebP 编码结果中没有图像数据");
+    Ok(data)
+}
+
+/// image 只能编码静态 WebP，动画容器 (VP8X + ANIM + ANMF) 在这里拼装
+fn encode_webp(frames: &[&RenderedFrame], fps: u32) -> anyhow::Result<Vec<u8>> {
+    let (width, height) = frames[0].image.dimensions();
+    ensure!(
+        (1..=1 << 24).contains(&width) && (1..=1 << 24).contains(&height),
+        "图片尺寸超出 WebP 的限制"
+    );
+
+    let mut body = b"WEBP".to_vec();
+
+    let mut vp8x = vec![0b0001_0010, 0, 0, 0];
+    push_u24(&mut vp8x, width - 1);
+    push_u24(&mut vp8x, height - 1);
+    push_chunk(&mut body, b"VP8X", &vp8x);
+
+    // 背景色和循环次数, 0 为无限循环
+    let mut anim = 0u32.to_le_bytes().to_vec();
+    anim.extend_from_slice(&0u16.to_le_bytes());
+    push_chunk(&mut body, b"ANIM", &anim);
+
+    for frame in frames {
+        let mut anmf = Vec::new();
+        push_u24(&mut anmf, 0);
+        push_u24(&mut anmf, 0);
+        push_u24(&mut anmf, width - 1);
+        push_u24(&mut anmf, height - 1);
+        push_u24(&mut anmf, frame_delay_ms(frame.ticks, fps) as u32);
+        // 不与上一帧混合, 不清除
+        anmf.push(0b0000_0010);
+        anmf.extend_from_slice(&webp_frame_data(&frame.image)?);
+        push_chunk(&mut body, b"ANMF", &anmf);
+    }
+
+    let mut out = b"RIFF".to_vec();
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(&body);
+    Ok(out)
+}
@@ -0,0 +1,258 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::components::baker::capture_pages::unique_filenames_besides;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::components::baker::storage::v2::AppState;
+use dioxus::prelude::*;
+#[cfg(not(target_arch = "wasm32"))]
+use std::cell::RefCell;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+
+/// 桌面端由应用直接写入文件，网页端交给浏览器下载
+pub(super) const NATIVE_SAVE: bool = cfg!(not(target_arch = "wasm32"));
+
+///
+/// 保存导出文件的结果。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum SaveOutcome {
+    /// 交给浏览器下载
+    #[cfg(target_arch = "wasm32")]
+    Downloaded,
+    /// 写入了这个位置：一个文件时为文件本身，多个文件时为所在的文件夹
+    Saved(String),
+    /// 在保存对话框中取消
+    Cancelled,
+}
+
+impl SaveOutcome {
+    /// 保存成功后的提示，取消时为空
+    pub(super) fn message(&self) -> Option<String> {
+        match self {
+            #[cfg(target_arch = "wasm32")]
+            SaveOutcome::Downloaded => {
+                Some("已交给浏览器下载，通常在用户的下载目录中。".to_string())
+            }
+            SaveOutcome::Saved(location) => Some(format!("已保存到 {location}")),
+            SaveOutcome::Cancelled => None,
+        }
+    }
+}
+
+///
+/// 保存导出的文件。
+///
+/// 桌面端按导出设置弹出保存对话框（多个文件时选择文件夹），或直接写入导出的文件夹，
+/// 并记住这次使用的文件夹；写入文件夹时与已有的文件重名的会改名，不覆盖它们。
+/// 网页端逐个交给浏览器下载。
+///
+/// # 参数
+///
+/// - files: 文件名和内容。
+/// - mime: 文件的类型，网页端下载时使用。
+///
+#[cfg(target_arch = "wasm32")]
+pub(super) async fn save_files(
+    files: Vec<(String, Vec<u8>)>,
+    mime: &str,
+) -> anyhow::Result<SaveOutcome> {
+    use crate::components::baker::layout::sleep_ms;
+    use crate::components::baker::{data_url_from_bytes, download_image};
+
+    anyhow::ensure!(!files.is_empty(), "没有要保存的文件");
+    for (index, (filename, bytes)) in files.into_iter().enumerate() {
+        if index > 0 {
+            // 浏览器会拦截过于密集的下载
+            sleep_ms(300).await;
+        }
+        let extension = filename
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_string())
+            .unwrap_or_default();
+        download_image(&data_url_from_bytes(mime, bytes), &extension, &filename).await?;
+    }
+    Ok(SaveOutcome::Downloaded)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) async fn save_files(
+    files: Vec<(String, Vec<u8>)>,
+    _mime: &str,
+) -> anyhow::Result<SaveOutcome> {
+    use anyhow::Context;
+
+    anyhow::ensure!(!files.is_empty(), "没有要保存的文件");
+    let app_state = try_consume_context::<Signal<AppState>>();
+    let settings = app_state
+        .map(|state| state.read().export.clone())
+        .unwrap_or_default();
+
+    let folder = if !settings.ask && !settings.dir.is_empty() {
+        PathBuf::from(&settings.dir)
+    } else if let [(filename, _)] = files.as_slice() {
+        let mut dialog = rfd::AsyncFileDialog::new().set_file_name(filename);
+        if let Some((_, extension)) = filename.rsplit_once('.') {
+            dialog = dialog.add_filter(extension.to_uppercase(), &[extension]);
+        }
+        if !settings.dir.is_empty() {
+            dialog = dialog.set_directory(&settings.dir);
+        }
+        let Some(handle) = dialog.save_file().await else {
+            return Ok(SaveOutcome::Cancelled);
+        };
+        // 对话框中可能改了文件名
+        let path = handle.path().to_path_buf();
+        tokio::fs::write(&path, &files[0].1)
+            .await
+            .with_context(|| format!("无法写入 {}", path.display()))?;
+        remember_dir(app_state, path.parent());
+        return Ok(SaveOutcome::Saved(path.display().to_string()));
+    } else {
+        let Some(folder) = pick_folder(&settings.dir).await else {
+            return Ok(SaveOutcome::Cancelled);
+        };
+        folder
+    };
+
+    tokio::fs::create_dir_all(&folder)
+        .await
+        .with_context(|| format!("无法创建文件夹 {}", folder.display()))?;
+    let (names, contents): (Vec<_>, Vec<_>) = files.into_iter().unzip();
+    let names = unique_filenames_besides(names, existing_filenames(&folder).await);
+    for (filename, bytes) in names.iter().zip(contents) {
+        let path = folder.join(filename);
+        tokio::fs::write(&path, bytes)
+            .await
+            .with_context(|| format!("无法写入 {}", path.display()))?;
+    }
+    remember_dir(app_state, Some(&folder));
+    let location = match names.as_slice() {
+        [filename] => folder.join(filename),
+        _ => folder,
+    };
+    Ok(SaveOutcome::Saved(location.display().to_string()))
+}
+
+/// 文件夹中已有的文件名，无法读取时为空
+#[cfg(not(target_arch = "wasm32"))]
+async fn existing_filenames(folder: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(folder).await else {
+        return names;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        names.push(entry.file_name().to_string_lossy().into_owned());
+    }
+    names
+}
+
+/// 记住这次保存的文件夹，下次从这里开始
+#[cfg(not(target_arch = "wasm32"))]
+fn remember_dir(app_state: Option<Signal<AppState>>, dir: Option<&Path>) {
+    let (Some(mut app_state), Some(dir)) = (app_state, dir) else {
+        return;
+    };
+    let dir = dir.display().to_string();
+    if app_state.peek().export.dir != dir {
+        app_state.write().export.dir = dir;
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn pick_folder(start: &str) -> Option<PathBuf> {
+    let mut dialog = rfd::AsyncFileDialog::new().set_title("选择保存的文件夹");
+    if !start.is_empty() {
+        dialog = dialog.set_directory(start);
+    }
+    dialog
+        .pick_folder()
+        .await
+        .map(|handle| handle.path().to_path_buf())
+}
+
+///
+/// 选择导出的文件夹。
+///
+/// # 参数
+///
+/// - start: 对话框一开始显示的文件夹，为空时由系统决定。
+///
+/// 取消或在网页端时返回 `None`。
+///
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) async fn pick_export_dir(start: &str) -> Option<String> {
+    pick_folder(start)
+        .await
+        .map(|dir| dir.display().to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(super) async fn pick_export_dir(_start: &str) -> Option<String> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    /// Linux 上剪贴板的内容由 Clipboard 对象提供，保留它直到下次复制
+    static CLIPBOARD: RefCell<Option<arboard::Clipboard>> = const { RefCell::new(None) };
+}
+
+///
+/// 把一张 PNG 复制到剪贴板。
+///
+/// # 参数
+///
+/// - png: 图片的内容。
+///
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) async fn copy_image(png: Vec<u8>) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    let image = image::load_from_memory(&png)
+        .context("无法读取截图")?
+        .to_rgba8();
+    let (width, height) = image.dimensions();
+    CLIPBOARD.with(|cell| {
+        let mut clipboard = cell.borrow_mut();
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new().context("无法打开剪贴板")?);
+        }
+        if let Some(clipboard) = clipboard.as_mut() {
+            clipboard
+                .set_image(arboard::ImageData {
+                    width: width as usize,
+                    height: height as usize,
+                    bytes: image.into_raw().into(),
+                })
+                .context("无法把图片复制到剪贴板")?;
+        }
+        Ok(())
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(super) async fn copy_image(png: Vec<u8>) -> anyhow::Result<()> {
+    use crate::components::baker::data_url_from_bytes;
+
+    let eval = document::eval(
+        r#"
+            const src = await dioxus.recv();
+            if (!navigator.clipboard?.write || typeof ClipboardItem === "undefined") {
+                return "浏览器不支持复制图片";
+            }
+            try {
+                const blob = await (await fetch(src)).blob();
+                await navigator.clipboard.write([new ClipboardItem({ [blob.type]: blob })]);
+                return null;
+            } catch (err) {
+                return String(err?.message ?? err);
+            }
+        "#,
+    );
+    eval.send(data_url_from_bytes("image/png", png))?;
+    let value = eval.await.map_err(|err| anyhow::anyhow!(err.to_string()))?;
+    if let Some(reason) = value.as_str() {
+        anyhow::bail!("无法把图片复制到剪贴板：{reason}");
+    }
+    Ok(())
+}
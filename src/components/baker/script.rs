@@ -0,0 +1,419 @@
+//! 会话脚本：每行一条消息的纯文本格式，用来快速编写、导入和导出会话。
+//!
+//! ```text
+//! # 注释
+//! 名字: 文字
+//! | 上一条消息的下一行
+//! + 名字: 对上一条消息的回应
+//! > 自己发送的状态
+//! 名字: [状态] 其他成员发送的状态
+//! --- 话题结束的文字
+//! 名字: [图片] 图片的路径或网址
+//! 名字: [表情] 表情的路径或网址
+//! 名字: [任务] {"title":"标题","objectives":[{"text":"目标","done":false}]}
+//! = {"typing_ms":1200}
+//! ```
+//!
+//! 名字为干员或自己的名字，无法直接写出的名字（重名、带冒号等）和已不在干员列表中的
+//! 发送者写作 `@编号`；以 `=` 开头的行是上一条消息的回放设置。
+//!
+//! 每一行都以 `\r\n` 结尾时按 Windows 的换行读取，否则行中的 `\r` 属于消息的内容。
+
+use crate::components::baker::storage::v2::{
+    Message, MessageKind, MessageReaction, MessageReplayOverrides, Mission, Operator, UserProfile,
+};
+use std::fmt;
+use uuid::Uuid;
+
+const COMMENT: &str = "#";
+const CONTINUATION: &str = "|";
+const REACTION: &str = "+";
+const SELF_STATUS: &str = ">";
+const SELF_TOPIC_ENDED: &str = "---";
+const REPLAY: &str = "=";
+const ID_PREFIX: &str = "@";
+const ESCAPE: &str = "\\";
+/// 行首的这些写法有特殊含义，不能作为名字的开头
+const LINE_MARKERS: [&str; 7] = [
+    COMMENT,
+    CONTINUATION,
+    REACTION,
+    SELF_STATUS,
+    SELF_TOPIC_ENDED,
+    REPLAY,
+    ID_PREFIX,
+];
+
+const STATUS_MARKER: &str = "[状态] ";
+const TOPIC_ENDED_MARKER: &str = "[话题结束] ";
+const IMAGE_MARKER: &str = "[图片] ";
+const STICKER_MARKER: &str = "[表情] ";
+const MISSION_MARKER: &str = "[任务] ";
+const MARKERS: [(&str, MessageKind); 5] = [
+    (STATUS_MARKER, MessageKind::Status),
+    (TOPIC_ENDED_MARKER, MessageKind::TopicEnded),
+    (IMAGE_MARKER, MessageKind::Image),
+    (STICKER_MARKER, MessageKind::Sticker),
+    (MISSION_MARKER, MessageKind::Mission),
+];
+
+///
+/// 脚本中无法解析的一行。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptError {
+    /// 从 1 开始的行号
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "第 {} 行：{}", self.line, self.message)
+    }
+}
+
+/// 可以出现在脚本中的成员：自己和全部干员
+struct Speakers<'a> {
+    members: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Speakers<'a> {
+    fn new(operators: &'a [Operator], user_profile: &'a UserProfile) -> Self {
+        let members = std::iter::once((user_profile.id.as_str(), user_profile.name.as_str()))
+            .chain(
+                operators
+                    .iter()
+                    .map(|operator| (operator.id.as_str(), operator.name.as_str())),
+            )
+            .collect();
+        Self { members }
+    }
+
+    /// 脚本中表示这个成员的写法
+    fn label(&self, sender_id: &str) -> String {
+        let name = self
+            .members
+            .iter()
+            .find(|(id, _)| *id == sender_id)
+            .map(|(_, name)| *name);
+        match name {
+            Some(name) if self.is_plain_name(name) => name.to_string(),
+            _ => format!("{ID_PREFIX}{sender_id}"),
+        }
+    }
+
+    /// 名字能否原样写出并唯一地找回这个成员
+    fn is_plain_name(&self, name: &str) -> bool {
+        !name.is_empty()
+            && name.trim() == name
+            && !name.contains([':', '：'])
+            && !LINE_MARKERS.iter().any(|marker| name.starts_with(marker))
+            && self.members.iter().filter(|(_, n)| *n == name).count() == 1
+    }
+
+    fn resolve(&self, label: &str) -> Result<String, String> {
+        // 名字不能以 `@` 开头，`@编号` 原样使用，已不在干员列表中的发送者也能读回
+        if let Some(id) = label.strip_prefix(ID_PREFIX) {
+            return Ok(id.to_string());
+        }
+        let mut found = self.members.iter().filter(|(_, name)| *name == label);
+        match (found.next(), found.next()) {
+            (Some((id, _)), None) => Ok(id.to_string()),
+            (Some(_), Some(_)) => Err(format!(
+                "有多个成员叫“{label}”，请改用 {ID_PREFIX}编号 指定"
+            )),
+            (None, _) => Err(format!("找不到名为“{label}”的干员")),
+        }
+    }
+}
+
+/// 按 `\n` 分行，每一行都以 `\r\n` 结尾时去掉行尾的 `\r`
+fn split_lines(script: &str) -> Vec<&str> {
+    let mut lines = script.split('\n').collect::<Vec<_>>();
+    // 最后一个换行之后的部分没有行尾
+    let last = lines.pop().unwrap_or_default();
+    if !lines.is_empty() && lines.iter().all(|line| line.ends_with('\r')) {
+        for line in &mut lines {
+            *line = &line[..line.len() - 1];
+        }
+    }
+    if !last.is_empty() {
+        lines.push(last);
+    }
+    lines
+}
+
+/// 去掉标记后紧跟的一个空格
+fn after_marker<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(marker)?;
+    Some(rest.strip_prefix(' ').unwrap_or(rest))
+}
+
+/// 在第一个冒号处分开名字和内容
+fn split_speaker(line: &str) -> Option<(&str, &str)> {
+    let (index, colon) = line.char_indices().find(|(_, c)| matches!(c, ':' | '：'))?;
+    let rest = &line[index + colon.len_utf8()..];
+    Some((line[..index].trim(), rest.strip_prefix(' ').unwrap_or(rest)))
+}
+
+/// 正文的类型和内容，以标记开头的文字需要转义
+fn parse_body(body: &str) -> (MessageKind, String) {
+    if let Some(text) = body.strip_prefix(ESCAPE) {
+        return (MessageKind::Normal, text.to_string());
+    }
+    MARKERS
+        .iter()
+        .find_map(|(marker, kind)| Some((kind.clone(), body.strip_prefix(marker)?.to_string())))
+        .unwrap_or((MessageKind::Normal, body.to_string()))
+}
+
+fn new_message(sender_id: String, kind: MessageKind, content: String) -> Message {
+    Message {
+        id: Uuid::new_v4().to_string(),
+        sender_id,
+        content,
+        kind,
+        animate: false,
+        animate_reactions: false,
+        reactions: Vec::new(),
+        replay: None,
+        mission: None,
+    }
+}
+
+///
+/// 解析会话脚本。
+///
+/// # 参数
+///
+/// - script: 脚本的内容。
+/// - operators: 可以出现的干员。
+/// - user_profile: 自己。
+///
+/// 每条消息使用新的编号；有无法解析的行时返回全部出错的行。
+///
+pub fn parse_script(
+    script: &str,
+    operators: &[Operator],
+    user_profile: &UserProfile,
+) -> Result<Vec<Message>, Vec<ScriptError>> {
+    let speakers = Speakers::new(operators, user_profile);
+    let mut messages = Vec::<Message>::new();
+    let mut errors = Vec::new();
+    // 续行接在上一条消息还是上一个回应之后
+    let mut continues_reaction = false;
+
+    for (index, raw) in split_lines(script).into_iter().enumerate() {
+        let line = raw.trim_start();
+        let mut fail = |message: String| {
+            errors.push(ScriptError {
+                line: index + 1,
+                message,
+            })
+        };
+        if line.trim_end().is_empty() || line.starts_with(COMMENT) {
+            continue;
+        }
+
+        if let Some(text) = after_marker(line, CONTINUATION) {
+            let Some(last) = messages.last_mut() else {
+                fail("续行前面没有消息".to_string());
+                continue;
+            };
+            let target = match last.reactions.last_mut() {
+                Some(reaction) if continues_reaction => &mut reaction.content,
+                _ => &mut last.content,
+            };
+            target.push('\n');
+            target.push_str(text);
+        } else if let Some(rest) = after_marker(line, REACTION) {
+            let Some(last) = messages.last_mut() else {
+                fail("回应前面没有消息".to_string());
+                continue;
+            };
+            let (sender_id, content) = match split_speaker(rest) {
+                Some(("", content)) => (String::new(), content),
+                Some((label, content)) => match speakers.resolve(label) {
+                    Ok(id) => (id, content),
+                    Err(message) => {
+                        fail(message);
+                        continue;
+                    }
+                },
+                None => (String::new(), rest),
+            };
+            last.reactions.push(MessageReaction {
+                content: content.to_string(),
+                sender_id,
+            });
+            continues_reaction = true;
+        } else if let Some(json) = after_marker(line, REPLAY) {
+            let Some(last) = messages.last_mut() else {
+                fail("回放设置前面没有消息".to_string());
+                continue;
+            };
+            match serde_json::from_str::<MessageReplayOverrides>(json) {
+                Ok(overrides) => last.replay = Some(overrides),
+                Err(err) => fail(format!("无法读取回放设置：{err}")),
+            }
+        } else if let Some(text) = after_marker(line, SELF_STATUS) {
+            messages.push(new_message(
+                user_profile.id.clone(),
+                MessageKind::Status,
+                text.to_string(),
+            ));
+            continues_reaction = false;
+        } else if let Some(text) = after_marker(line, SELF_TOPIC_ENDED) {
+            messages.push(new_message(
+                user_profile.id.clone(),
+                MessageKind::TopicEnded,
+                text.to_string(),
+            ));
+            continues_reaction = false;
+        } else if let Some((label, body)) = split_speaker(line) {
+            match speakers.resolve(label) {
+                Ok(sender_id) => {
+                    let (kind, content) = parse_body(body);
+                    // 任务的内容写作一行 JSON
+                    let mission = if kind == MessageKind::Mission {
+                        match serde_json::from_str::<Mission>(&content) {
+                            Ok(mission) => Some(mission),
+                            Err(err) => {
+                                fail(format!("无法读取任务：{err}"));
+                                continue;
+                            }
+                        }
+                    } else {
+                        None
+                    };
+                    let mut msg = new_message(sender_id, kind, content);
+                    if mission.is_some() {
+                        msg.content.clear();
+                        msg.mission = mission;
+                    }
+                    messages.push(msg);
+                    continues_reaction = false;
+                }
+                Err(message) => fail(message),
+            }
+        } else {
+            fail("缺少“名字: 内容”中的冒号".to_string());
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(messages)
+    } else {
+        Err(errors)
+    }
+}
+
+/// 写出一段可能有多行的内容，后续的行写作续行
+fn push_lines(out: &mut String, head: &str, content: &str) {
+    let mut lines = content.split('\n');
+    out.push_str(head);
+    out.push_str(lines.next().unwrap_or_default());
+    out.push('\n');
+    for line in lines {
+        out.push_str(CONTINUATION);
+        if !line.is_empty() {
+            out.push(' ');
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+}
+
+///
+/// 把消息写成会话脚本，用 [`parse_script`] 读回时得到相同的消息（编号除外）。
+///
+/// # 参数
+///
+/// - messages: 要写出的消息。
+/// - operators: 全部干员，用来写出名字。
+/// - user_profile: 自己。
+///
+pub fn format_script(
+    messages: &[Message],
+    operators: &[Operator],
+    user_profile: &UserProfile,
+) -> String {
+    let speakers = Speakers::new(operators, user_profile);
+    let mut out = String::new();
+    for msg in messages {
+        let from_self = msg.sender_id == user_profile.id;
+        let speaker = || format!("{}: ", speakers.label(&msg.sender_id));
+        let head = match msg.kind {
+            MessageKind::Status if from_self => format!("{SELF_STATUS} "),
+            MessageKind::TopicEnded if from_self => format!("{SELF_TOPIC_ENDED} "),
+            MessageKind::Status => format!("{}{STATUS_MARKER}", speaker()),
+            MessageKind::TopicEnded => format!("{}{TOPIC_ENDED_MARKER}", speaker()),
+            MessageKind::Image => format!("{}{IMAGE_MARKER}", speaker()),
+            MessageKind::Sticker => format!("{}{STICKER_MARKER}", speaker()),
+            MessageKind::Mission => format!("{}{MISSION_MARKER}", speaker()),
+            MessageKind::Normal => {
+                let escaped = msg.content.starts_with(ESCAPE)
+                    || MARKERS
+                        .iter()
+                        .any(|(marker, _)| msg.content.starts_with(marker));
+                if escaped {
+                    format!("{}{ESCAPE}", speaker())
+                } else {
+                    speaker()
+                }
+            }
+        };
+        if msg.kind == MessageKind::Mission {
+            let mission = msg.mission.clone().unwrap_or_default();
+            let json = serde_json::to_string(&mission).unwrap_or_default();
+            push_lines(&mut out, &head, &json);
+        } else {
+            push_lines(&mut out, &head, &msg.content);
+        }
+
+        if let Some(overrides) = &msg.replay
+            && let Ok(json) = serde_json::to_string(overrides)
+        {
+            out.push_str(&format!("{REPLAY} {json}\n"));
+        }
+        for reaction in &msg.reactions {
+            let head = if reaction.sender_id.is_empty() {
+                format!("{REACTION} : ")
+            } else {
+                format!("{REACTION} {}: ", speakers.label(&reaction.sender_id))
+            };
+            push_lines(&mut out, &head, &reaction.content);
+        }
+    }
+    // 每一行都以 `\r` 结尾时会被当作 Windows 的换行去掉，加一行注释保留内容中的 `\r`
+    if !out.is_empty() && out.split_terminator('\n').all(|line| line.ends_with('\r')) {
+        out.insert_str(0, &format!("{COMMENT}\n"));
+    }
+    out
+}
+
+///
+/// 脚本中发言的干员，按第一次出现的顺序排列，不含自己。
+///
+/// # 参数
+///
+/// - messages: 解析出的消息。
+/// - user_id: 自己的编号。
+///
+pub fn operator_ids(messages: &[Message], user_id: &str) -> Vec<String> {
+    let mut ids = Vec::<String>::new();
+    let senders = messages.iter().flat_map(|msg| {
+        std::iter::once(msg.sender_id.as_str()).chain(
+            msg.reactions
+                .iter()
+                .map(|reaction| reaction.sender_id.as_str()),
+        )
+    });
+    for id in senders {
+        if !id.is_empty() && id != user_id && !ids.iter().any(|known| known == id) {
+            ids.push(id.to_string());
+        }
+    }
+    ids
+}
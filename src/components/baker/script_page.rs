@@ -0,0 +1,475 @@
+use dioxus::prelude::*;
+
+use crate::components::baker::Route;
+use crate::components::baker::capture_pages::{DEFAULT_FILENAME_TEMPLATE, capture_filename};
+use crate::components::baker::chat_render::contact_display_name;
+use crate::components::baker::modals::Modal;
+use crate::components::baker::save::save_files;
+use crate::components::baker::script::{format_script, operator_ids, parse_script};
+use crate::components::baker::storage::v2::{AppState, ChatHeadStyle, Contact, Message};
+use crate::dioxus_elements::FileData;
+use uuid::Uuid;
+
+const SCRIPT_PLACEHOLDER: &str = "# 以 # 开头的行是注释
+佩丽卡: 管理员，在吗？
+| 这一行接在上一条消息后面
++ 陈千语: 👍
+> 对方已上线
+我的名字: 在的
+佩丽卡: [图片] /home/me/pictures/map.png
+佩丽卡: [表情] https://example.com/wave.png
+--- 本次话题已结束";
+
+///
+/// 把脚本中本地图片的路径读取为 data URL，网址和 data URL 保持不变。
+///
+/// # 参数
+///
+/// - messages: 解析出的消息。
+///
+/// 网页端无法读取本地文件，图片只能写作网址。
+///
+#[cfg(not(target_arch = "wasm32"))]
+fn load_local_images(messages: &mut [Message]) -> anyhow::Result<()> {
+    use crate::components::baker::storage::v2::MessageKind;
+    use crate::components::baker::{data_url_from_bytes, mime_from_filename};
+    use anyhow::Context;
+    use std::path::Path;
+
+    for msg in messages {
+        if !matches!(msg.kind, MessageKind::Image | MessageKind::Sticker)
+            || msg.content.starts_with("data:")
+            || msg.content.contains("://")
+        {
+            continue;
+        }
+        let path = Path::new(&msg.content);
+        // 不存在的路径可能是应用内的资源地址
+        if !path.is_file() {
+            continue;
+        }
+        let bytes = std::fs::read(path).with_context(|| format!("无法读取图片 {}", msg.content))?;
+        msg.content = data_url_from_bytes(mime_from_filename(&msg.content), bytes);
+    }
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_local_images(_messages: &mut [Message]) -> anyhow::Result<()> {
+    Ok(())
+}
+
+///
+/// 用脚本编辑已有会话的页面。
+///
+/// # 参数
+///
+/// - contact_id: 会话的 ID。
+///
+#[component]
+pub(super) fn ScriptPage(contact_id: String) -> Element {
+    rsx! {
+        ScriptEditor { contact_id: Some(contact_id) }
+    }
+}
+
+///
+/// 用脚本新建会话的页面。
+///
+#[component]
+pub(super) fn NewScriptPage() -> Element {
+    rsx! {
+        ScriptEditor { contact_id: None }
+    }
+}
+
+///
+/// 编辑、导入和导出会话脚本。
+///
+/// # 参数
+///
+/// - contact_id: 要编辑的会话，为空时导入为新的会话。
+///
+#[component]
+fn ScriptEditor(contact_id: Option<String>) -> Element {
+    let mut app_state = use_context::<Signal<AppState>>();
+    let navigator = navigator();
+
+    let contact = use_signal({
+        let contact_id = contact_id.clone();
+        move || {
+            let id = contact_id?;
+            app_state
+                .read()
+                .contacts
+                .iter()
+                .find(|c| c.id == id)
+                .cloned()
+        }
+    });
+    let mut script = use_signal(move || {
+        let state = app_state.read();
+        contact
+            .read()
+            .as_ref()
+            .and_then(|contact| state.messages.get(&contact.id))
+            .map(|messages| format_script(messages, &state.operators, &state.user_profile))
+            .unwrap_or_default()
+    });
+    let mut append = use_signal(|| false);
+    let mut as_group = use_signal(|| false);
+    let mut group_name = use_signal(String::new);
+    let mut file_input_token = use_signal(|| 0usize);
+    let mut success_message = use_signal(|| None::<String>);
+    // 导入后回到会话列表
+    let mut imported = use_signal(|| false);
+    let mut error_message = use_signal(|| None::<String>);
+
+    let parsed = use_memo(move || {
+        let state = app_state.read();
+        parse_script(&script.read(), &state.operators, &state.user_profile)
+    });
+    let contact_name = use_memo(move || {
+        contact
+            .read()
+            .as_ref()
+            .map(|contact| contact_display_name(contact, &app_state.read().operators))
+            .unwrap_or_default()
+    });
+    // 脚本中发言的干员
+    let speakers = use_memo(move || {
+        let parsed = parsed.read();
+        let Ok(messages) = &*parsed else {
+            return Vec::new();
+        };
+        let state = app_state.read();
+        operator_ids(messages, &state.user_profile.id)
+            .into_iter()
+            .filter_map(|id| state.operators.iter().find(|op| op.id == id).cloned())
+            .collect::<Vec<_>>()
+    });
+
+    let save_script = move |_| {
+        let name = if contact.read().is_some() {
+            contact_name()
+        } else {
+            "script".to_string()
+        };
+        let now = chrono::Local::now().naive_local();
+        let filename = capture_filename(DEFAULT_FILENAME_TEMPLATE, &name, now, None, "txt");
+        let bytes = script().into_bytes();
+        spawn(async move {
+            match save_files(vec![(filename, bytes)], "text/plain;charset=utf-8").await {
+                Ok(outcome) => success_message.set(outcome.message()),
+                Err(err) => {
+                    error!("save script failed: {err:?}");
+                    error_message.set(Some(err.to_string()));
+                }
+            }
+        });
+    };
+
+    let import = move |_| {
+        let Ok(mut messages) = parsed() else {
+            return;
+        };
+        if messages.is_empty() {
+            error_message.set(Some("脚本中没有消息".to_string()));
+            return;
+        }
+        if let Err(err) = load_local_images(&mut messages) {
+            error_message.set(Some(err.to_string()));
+            return;
+        }
+        let speakers = speakers();
+        let mut state = app_state.write();
+
+        let contact_id = match contact() {
+            Some(contact) => {
+                let strangers = speakers
+                    .iter()
+                    .filter(|op| !contact.participant_ids.contains(&op.id))
+                    .collect::<Vec<_>>();
+                if !contact.is_group
+                    && let Some(stranger) = strangers.first()
+                {
+                    error_message.set(Some(format!(
+                        "单聊中只能有对方和自己发言，脚本中有“{}”",
+                        stranger.name
+                    )));
+                    return;
+                }
+                // 群聊中新出现的干员加入群聊
+                if let Some(group) = state.contacts.iter_mut().find(|c| c.id == contact.id) {
+                    group
+                        .participant_ids
+                        .extend(strangers.iter().map(|op| op.id.clone()));
+                }
+                contact.id
+            }
+            None => {
+                let Some(first) = speakers.first() else {
+                    error_message.set(Some("脚本中没有干员发言".to_string()));
+                    return;
+                };
+                if speakers.len() == 1 && !as_group() {
+                    if state.contacts.iter().any(|c| c.id == first.id) {
+                        error_message.set(Some(format!(
+                            "已经有和“{}”的会话，请在该会话的菜单中编辑脚本",
+                            first.name
+                        )));
+                        return;
+                    }
+                    state.contacts.push(Contact {
+                        id: first.id.clone(),
+                        unread_count: 0,
+                        chat_head_style: ChatHeadStyle::Default,
+                        name: first.name.clone(),
+                        avatar_url: first.avatar_url.clone(),
+                        participant_ids: vec![first.id.clone()],
+                        participants_selves_ids: vec![],
+                        is_group: false,
+                    });
+                    first.id.clone()
+                } else {
+                    let name = match group_name().trim() {
+                        "" => speakers
+                            .iter()
+                            .map(|op| op.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join("、"),
+                        name => name.to_string(),
+                    };
+                    let group_id = Uuid::new_v4().to_string();
+                    state.contacts.push(Contact {
+                        id: group_id.clone(),
+                        unread_count: 0,
+                        chat_head_style: ChatHeadStyle::Default,
+                        name,
+                        avatar_url: String::new(),
+                        participant_ids: speakers.iter().map(|op| op.id.clone()).collect(),
+                        participants_selves_ids: vec![],
+                        is_group: true,
+                    });
+                    group_id
+                }
+            }
+        };
+
+        let count = messages.len();
+        let list = state.messages.entry(contact_id).or_default();
+        if append() || contact.read().is_none() {
+            list.extend(messages);
+        } else {
+            *list = messages;
+        }
+        drop(state);
+        imported.set(true);
+        success_message.set(Some(format!("已导入 {count} 条消息。")));
+    };
+
+    let close_success = move |_| {
+        success_message.set(None);
+        if imported() {
+            navigator.push(Route::BakerLayout {});
+        }
+    };
+
+    let is_new = contact.read().is_none();
+    let missing = contact_id.is_some() && is_new;
+    let input_class = "w-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm focus:outline-none focus:border-black/30";
+    let speaker_names = speakers
+        .read()
+        .iter()
+        .map(|op| op.name.as_str())
+        .collect::<Vec<_>>()
+        .join("、");
+    let title = if is_new {
+        "导入脚本为新会话".to_string()
+    } else {
+        format!("编辑脚本：{}", contact_name())
+    };
+
+    rsx! {
+        if let Some(message) = success_message() {
+            Modal {
+                title: "操作成功",
+                content_confirmation_button: "好",
+                on_close: close_success,
+                on_confirm: close_success,
+
+                {
+                    rsx! {
+                        p { class: "text-black break-all", "{message}" }
+                    }
+                }
+            }
+        }
+        if let Some(err) = error_message() {
+            Modal {
+                title: "操作失败",
+                content_confirmation_button: "好",
+                on_close: move |_| error_message.set(None),
+                on_confirm: move |_| error_message.set(None),
+
+                {
+                    rsx! {
+                        p { class: "text-black break-all", "{err}" }
+                    }
+                }
+            }
+        }
+        div {
+            width: "100%",
+            height: "100vh",
+            display: "flex",
+            flex_direction: "column",
+            position: "relative",
+            overflow: "hidden",
+            div { class: "h-14 shrink-0 flex items-center gap-3 px-6 border-b border-gray-600 bg-[#1f1f1f]/80 backdrop-blur-sm",
+                button {
+                    class: "text-gray-300 hover:text-white text-lg px-2 py-1 rounded-lg hover:bg-white/5 transition-colors",
+                    onclick: move |_| {
+                        navigator.push(Route::BakerLayout {});
+                    },
+                    "←"
+                }
+                h1 { class: "text-white text-lg font-bold", "{title}" }
+            }
+            if missing {
+                p { class: "text-white p-6", "找不到这个会话。" }
+            } else {
+                div {
+                    width: "100%",
+                    flex: "1",
+                    display: "flex",
+                    gap: "16px",
+                    padding: "16px",
+                    box_sizing: "border-box",
+                    overflow: "hidden",
+                    textarea {
+                        class: "flex-1 min-w-[200px] h-full bg-[#e9e9e9] border border-black/10 rounded p-3 text-black text-sm font-mono focus:outline-none focus:border-black/30 resize-none",
+                        spellcheck: false,
+                        placeholder: SCRIPT_PLACEHOLDER,
+                        value: "{script}",
+                        oninput: move |e| script.set(e.value()),
+                    }
+                    div {
+                        flex: "0 1 35%",
+                        min_width: "320px",
+                        overflow_y: "auto",
+                        class: "space-y-4",
+                        div { class: "flex gap-3",
+                            label { class: "flex-1 relative overflow-hidden text-center bg-[#3a3a3a] hover:bg-[#4a4a4a] text-white py-2 rounded text-sm font-medium transition-colors cursor-pointer",
+                                "打开脚本文件"
+                                input {
+                                    key: "{file_input_token()}",
+                                    r#type: "file",
+                                    accept: ".txt,text/plain",
+                                    class: "absolute inset-0 opacity-0 cursor-pointer",
+                                    onchange: move |evt| {
+                                        let files: Vec<FileData> = evt.files();
+                                        if let Some(file) = files.first().cloned() {
+                                            spawn(async move {
+                                                match file.read_bytes().await {
+                                                    Ok(bytes) => {
+                                                        let text = String::from_utf8_lossy(&bytes);
+                                                        script.set(text.trim_start_matches('\u{feff}').to_string());
+                                                    }
+                                                    Err(err) => error_message.set(Some(err.to_string())),
+                                                }
+                                                file_input_token.set(file_input_token() + 1);
+                                            });
+                                        }
+                                    },
+                                }
+                            }
+                            button {
+                                class: "flex-1 bg-[#3a3a3a] hover:bg-[#4a4a4a] text-white py-2 rounded text-sm font-medium transition-colors",
+                                onclick: save_script,
+                                "保存为文件"
+                            }
+                        }
+                        if is_new {
+                            label { class: "flex items-center gap-2 text-white text-sm cursor-pointer",
+                                input {
+                                    r#type: "checkbox",
+                                    class: "w-4 h-4 accent-black cursor-pointer",
+                                    checked: as_group() || speakers.read().len() > 1,
+                                    disabled: speakers.read().len() > 1,
+                                    onchange: move |e| as_group.set(e.checked()),
+                                }
+                                "导入为群聊"
+                            }
+                            if as_group() || speakers.read().len() > 1 {
+                                div { class: "space-y-1",
+                                    label { class: "block text-white text-sm", "群聊名称" }
+                                    input {
+                                        class: input_class,
+                                        placeholder: "留空时使用成员的名字",
+                                        value: "{group_name}",
+                                        oninput: move |e| group_name.set(e.value()),
+                                    }
+                                }
+                            }
+                        } else {
+                            label { class: "flex items-center gap-2 text-white text-sm cursor-pointer",
+                                input {
+                                    r#type: "checkbox",
+                                    class: "w-4 h-4 accent-black cursor-pointer",
+                                    checked: append(),
+                                    onchange: move |e| append.set(e.checked()),
+                                }
+                                "追加到会话末尾，而不是替换全部消息"
+                            }
+                        }
+                        match &*parsed.read() {
+                            Ok(messages) => rsx! {
+                                p { class: "text-gray-300 text-sm",
+                                    "共 {messages.len()} 条消息"
+                                    if !speaker_names.is_empty() {
+                                        "，发言的干员：{speaker_names}"
+                                    }
+                                }
+                            },
+                            Err(errors) => rsx! {
+                                div { class: "space-y-1",
+                                    for err in errors.iter() {
+                                        p { class: "text-red-300 text-xs break-all", "{err}" }
+                                    }
+                                }
+                            },
+                        }
+                        button {
+                            class: "w-full bg-blue-600 hover:bg-blue-500 disabled:opacity-50 text-white py-2 rounded text-sm font-medium transition-colors",
+                            disabled: parsed.read().is_err(),
+                            onclick: import,
+                            if is_new {
+                                "导入为新会话"
+                            } else if append() {
+                                "追加到会话"
+                            } else {
+                                "保存到会话"
+                            }
+                        }
+                        div { class: "text-gray-400 text-xs space-y-1",
+                            p { "每行一条消息，写作“名字: 内容”，名字为干员或自己的名字。" }
+                            p { "以 | 开头的行接在上一条消息后面换行；以 + 开头的行是对上一条消息的回应。" }
+                            p { "> 开头为自己发送的状态，--- 开头为话题结束；其他成员的状态写作“名字: [状态] 内容”。" }
+                            p {
+                                "图片和表情写作“名字: [图片] 路径”和“名字: [表情] 路径”，"
+                                if cfg!(target_arch = "wasm32") {
+                                    "网页端只能使用网址。"
+                                } else {
+                                    "可以使用网址或本地文件的完整路径。"
+                                }
+                            }
+                            p { "重名或带冒号的名字在导出时写作 @编号；以 = 开头的行是上一条消息的回放设置。" }
+                            p { "文字本身以 [图片] 这样的标记开头时，在前面加上 \\ 。" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::components::baker::layout::load_repo_config;
-use crate::components::baker::storage::v2::{BackgroundMode, Operator};
+use crate::components::baker::sound::preview_sound;
+use crate::components::baker::storage::v2::{BackgroundMode, Operator, SoundEvent};
 use crate::components::baker::{data_url_from_bytes, mime_from_filename, use_synced_field, Route};
 use crate::dioxus_elements::FileData;
 use dioxus::prelude::*;
@@ -17,6 +18,7 @@ pub fn SettingsPage() -> Element {
 
     let mut operators = use_synced_field(app_state, |s| s.operators.clone(), |s, v| s.operators = v);
     let mut background = use_synced_field(app_state, |s| s.background.clone(), |s, v| s.background = v);
+    let mut sound = use_synced_field(app_state, |s| s.sound.clone(), |s, v| s.sound = v);
 
     let mut new_name = use_signal(|| "".to_string());
     let mut new_avatar_preview = use_signal(|| "".to_string());
@@ -28,6 +30,7 @@ pub fn SettingsPage() -> Element {
     enum SettingsSection {
         Operators,
         Background,
+        Sound,
         About,
     }
 
@@ -76,6 +79,8 @@ pub fn SettingsPage() -> Element {
 
     let ops_list = operators.read().clone();
     let current_background = background.read().clone();
+    let current_sound = sound.read().clone();
+    let volume_percent = (current_sound.volume.clamp(0.0, 1.0) * 100.0).round() as u32;
     let background_mode_value = match current_background.mode {
         BackgroundMode::DotDark => "dot_dark",
         BackgroundMode::DotLight => "dot_light",
@@ -92,6 +97,11 @@ pub fn SettingsPage() -> Element {
     } else {
         "text-gray-400 hover:text-white hover:bg-white/5"
     };
+    let sound_tab_class = if matches!(section(), SettingsSection::Sound) {
+        "bg-[#2b2b2b] text-white"
+    } else {
+        "text-gray-400 hover:text-white hover:bg-white/5"
+    };
     let about_tab_class = if matches!(section(), SettingsSection::About) {
         "bg-[#2b2b2b] text-white"
     } else {
@@ -147,6 +157,11 @@ pub fn SettingsPage() -> Element {
                             onclick: move |_| section.set(SettingsSection::Background),
                             "背景设置"
                         }
+                        button {
+                            class: "w-full text-left px-3 py-2 rounded-lg text-sm transition-colors cursor-pointer {sound_tab_class}",
+                            onclick: move |_| section.set(SettingsSection::Sound),
+                            "声音设置"
+                        }
                         button {
                             class: "w-full text-left px-3 py-2 rounded-lg text-sm transition-colors cursor-pointer {about_tab_class}",
                             onclick: move |_| section.set(SettingsSection::About),
@@ -361,6 +376,112 @@ pub fn SettingsPage() -> Element {
                                 }
                             }
                         }
+                    } else if matches!(section(), SettingsSection::Sound) {
+                        div { class: "max-w-[820px] space-y-6",
+                            h2 { class: "text-white text-base font-bold", "声音设置" }
+                            div { class: "space-y-3",
+                                div { class: "flex items-center gap-3",
+                                    span { class: "text-gray-300 text-sm w-20", "主音量" }
+                                    input {
+                                        class: "flex-1 accent-blue-600 cursor-pointer",
+                                        r#type: "range",
+                                        min: "0",
+                                        max: "100",
+                                        value: "{volume_percent}",
+                                        oninput: move |e| {
+                                            if let Ok(value) = e.value().parse::<f64>() {
+                                                sound.write().volume = (value / 100.0).clamp(0.0, 1.0);
+                                            }
+                                        },
+                                    }
+                                    span { class: "text-gray-300 text-xs tabular-nums w-10 text-right", "{volume_percent}%" }
+                                }
+                                label { class: "flex items-center gap-2 text-gray-300 text-sm cursor-pointer",
+                                    input {
+                                        r#type: "checkbox",
+                                        class: "w-4 h-4 accent-blue-600 cursor-pointer",
+                                        checked: current_sound.muted,
+                                        onchange: move |e| sound.write().muted = e.checked(),
+                                    }
+                                    "全部静音"
+                                }
+                                label { class: "flex items-center gap-2 text-gray-300 text-sm cursor-pointer",
+                                    input {
+                                        r#type: "checkbox",
+                                        class: "w-4 h-4 accent-blue-600 cursor-pointer",
+                                        checked: current_sound.replay_muted,
+                                        onchange: move |e| sound.write().replay_muted = e.checked(),
+                                    }
+                                    "回放时静音（也可以在回放控制条上切换）"
+                                }
+                            }
+                            div { class: "space-y-2",
+                                for event in SoundEvent::ALL {
+                                    {
+                                        let event_sound = current_sound.event(event).clone();
+                                        let has_custom = !event_sound.custom_src.is_empty();
+                                        rsx! {
+                                            div {
+                                                key: "{event.label()}",
+                                                class: "flex items-center gap-3 p-3 bg-[#2b2b2b] rounded-xl border border-gray-600",
+                                                label { class: "flex items-center gap-2 text-white text-sm w-32 cursor-pointer",
+                                                    input {
+                                                        r#type: "checkbox",
+                                                        class: "w-4 h-4 accent-blue-600 cursor-pointer",
+                                                        checked: event_sound.enabled,
+                                                        onchange: move |e| sound.write().event_mut(event).enabled = e.checked(),
+                                                    }
+                                                    {event.label()}
+                                                }
+                                                span { class: "flex-1 text-gray-400 text-xs",
+                                                    if has_custom {
+                                                        "自定义声音"
+                                                    } else {
+                                                        "内置声音"
+                                                    }
+                                                }
+                                                input {
+                                                    class: "w-56 text-gray-300 text-xs cursor-pointer",
+                                                    r#type: "file",
+                                                    accept: "audio/*",
+                                                    onchange: move |evt| {
+                                                        let files: Vec<FileData> = evt.files();
+                                                        if let Some(file) = files.first().cloned() {
+                                                            let file_name: String = file.name();
+                                                            let mime = file
+                                                                .content_type()
+                                                                .unwrap_or_else(|| mime_from_filename(&file_name).to_string());
+                                                            let mut sound = sound;
+                                                            spawn(async move {
+                                                                if let Ok(bytes) = file.read_bytes().await {
+                                                                    let data_url = data_url_from_bytes(&mime, bytes.to_vec());
+                                                                    sound.write().event_mut(event).custom_src = data_url;
+                                                                }
+                                                            });
+                                                        }
+                                                    },
+                                                }
+                                                if has_custom {
+                                                    button {
+                                                        class: "text-gray-300 hover:text-white text-sm px-2 py-1 cursor-pointer",
+                                                        onclick: move |_| sound.write().event_mut(event).custom_src.clear(),
+                                                        "恢复内置"
+                                                    }
+                                                }
+                                                button {
+                                                    class: "text-blue-400 hover:text-blue-300 text-sm px-2 py-1 cursor-pointer",
+                                                    onclick: move |_| preview_sound(&sound.read(), event),
+                                                    "试听"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            p { class: "text-gray-400 text-xs",
+                                "自定义的声音和头像一样保存在本地数据中，请尽量使用较小的音频文件。"
+                            }
+                        }
                     } else if matches!(section(), SettingsSection::About) {
                         div {
                             h1 { class: "text-4xl font-bold", "Baker" }
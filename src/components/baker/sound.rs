@@ -0,0 +1,65 @@
+use crate::components::baker::storage::v2::{AppState, SoundEvent, SoundSettings};
+use dioxus::prelude::*;
+
+const MESSAGE_SOUND: Asset = asset!("/assets/sound/message.mp3");
+const MESSAGE_SELF_SOUND: Asset = asset!("/assets/sound/message-self.mp3");
+
+/// 事件内置的声音
+fn builtin_src(event: SoundEvent) -> String {
+    match event {
+        SoundEvent::Outgoing | SoundEvent::Status => MESSAGE_SELF_SOUND.to_string(),
+        SoundEvent::Incoming | SoundEvent::Reaction | SoundEvent::TopicEnded => {
+            MESSAGE_SOUND.to_string()
+        }
+    }
+}
+
+fn event_src(settings: &SoundSettings, event: SoundEvent) -> String {
+    let custom = &settings.event(event).custom_src;
+    if custom.is_empty() {
+        builtin_src(event)
+    } else {
+        custom.clone()
+    }
+}
+
+fn play_src(src: String, volume: f64) {
+    spawn(async move {
+        // 自定义的声音是 data URL, 通过消息传入而不是拼进脚本
+        let eval = document::eval(
+            r#"
+            const src = await dioxus.recv();
+            const volume = await dioxus.recv();
+            const audio = new Audio(src);
+            audio.volume = volume;
+            audio.play();
+        "#,
+        );
+        if eval.send(src).is_err() || eval.send(volume).is_err() {
+            return;
+        }
+        let _ = eval.await;
+    });
+}
+
+///
+/// 按声音设置播放一个事件的提示音。
+///
+/// # 参数
+///
+/// - event: 发生的事件。
+/// - in_replay: 是否在回放中，回放静音时不播放。
+///
+pub(super) fn play_sound(event: SoundEvent, in_replay: bool) {
+    let settings = try_consume_context::<Signal<AppState>>()
+        .map(|app_state| app_state.read().sound.clone())
+        .unwrap_or_default();
+    if let Some(volume) = settings.volume_for(event, in_replay) {
+        play_src(event_src(&settings, event), volume);
+    }
+}
+
+/// 在设置页面试听，忽略静音和开关
+pub(super) fn preview_sound(settings: &SoundSettings, event: SoundEvent) {
+    play_src(event_src(settings, event), settings.volume.clamp(0.0, 1.0));
+}
@@ -12,8 +12,8 @@ use v1::{
     MessageKind as V1MessageKind, Operator as V1Operator, UserProfile as V1UserProfile,
 };
 use v2::{
-    AppState, BackgroundMode, BackgroundSettings, ChatHeadStyle, Contact, Message, MessageKind,
-    MessageReaction, Operator, UserProfile,
+    AppState, BackgroundMode, BackgroundSettings, ChatHeadStyle, Contact, ExportSettings, Message,
+    MessageKind, MessageReaction, Operator, ReplayPlaylist, SoundSettings, UserProfile,
 };
 
 pub(crate) mod legacy;
@@ -599,6 +599,8 @@ fn migrate_v1_state_to_v2(state: V1AppState) -> AppState {
                                     sender_id: reaction.sender_id,
                                 })
                                 .collect(),
+                            replay: None,
+                            mission: None,
                         })
                         .collect(),
                 )
@@ -628,6 +630,10 @@ fn migrate_v1_state_to_v2(state: V1AppState) -> AppState {
         hide_tutorial: state.hide_tutorial,
         show_tip_saving_image_problem_on_web: state.show_tip_saving_image_problem_on_web,
         showed_notice: false,
+        replay_settings: HashMap::new(),
+        replay_playlist: ReplayPlaylist::default(),
+        sound: SoundSettings::default(),
+        export: ExportSettings::default(),
     }
 }
 
@@ -639,6 +645,25 @@ fn parse_legacy_state_from_str(raw: &str) -> Option<LegacyAppState> {
     serde_json::from_str::<LegacyAppState>(raw).ok()
 }
 
+///
+/// 解析保存为 JSON 的状态文件，可以是当前的状态结构，也可以是旧版本的 `baker_dx_state.json`。
+///
+/// # 参数
+///
+/// - raw: 文件内容。
+///
+#[cfg(not(target_arch = "wasm32"))]
+pub fn parse_state_file(raw: &str) -> Option<AppState> {
+    if let Ok(state) = serde_json::from_str::<AppState>(raw) {
+        return Some(state);
+    }
+    if let Some(v1_state) = parse_v1_state_from_str(raw) {
+        return Some(migrate_v1_state_to_v2(v1_state));
+    }
+    parse_legacy_state_from_str(raw)
+        .map(|legacy_state| migrate_v1_state_to_v2(migrate_legacy_state_to_v1(legacy_state)))
+}
+
 fn default_loaded_state() -> LoadedState {
     if let Some(v1_state) = parse_v1_state_from_str(DEFAULT_STATE_JSON) {
         return LoadedState {
@@ -749,6 +774,8 @@ fn decode_state(meta: PersistedMeta, snapshot: PersistedDbSnapshot) -> Option<Ap
                         animate: false,
                         animate_reactions: false,
                         reactions: message.reactions,
+                        replay: None,
+                        mission: None,
                     })
                 })
                 .collect::<Option<Vec<_>>>()?;
@@ -806,6 +833,10 @@ fn decode_state(meta: PersistedMeta, snapshot: PersistedDbSnapshot) -> Option<Ap
         hide_tutorial: meta.hide_tutorial,
         show_tip_saving_image_problem_on_web: meta.show_tip_saving_image_problem_on_web,
         showed_notice: meta.show_notice,
+        replay_settings: HashMap::new(),
+        replay_playlist: ReplayPlaylist::default(),
+        sound: SoundSettings::default(),
+        export: ExportSettings::default(),
     })
 }
 
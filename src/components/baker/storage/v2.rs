@@ -32,6 +32,8 @@ pub enum MessageKind {
     TopicEnded,
     Image,
     Sticker,
+    /// 任务卡片，内容在 [`Message::mission`] 中
+    Mission,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize)]
@@ -62,6 +64,88 @@ impl<'de> Deserialize<'de> for MessageReaction {
     }
 }
 
+/// 单条消息的回放设置，覆盖全局的回放设置
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct MessageReplayOverrides {
+    /// 输入时长，为空时按全局设置计算
+    #[serde(default)]
+    pub typing_ms: Option<u64>,
+    /// 在这条消息之前额外停顿的时间
+    #[serde(default)]
+    pub pause_before_ms: u64,
+    /// 在这条消息之后额外停顿的时间
+    #[serde(default)]
+    pub pause_after_ms: u64,
+    /// 不显示“输入中”，直接显示消息
+    #[serde(default)]
+    pub skip_typing: bool,
+    /// 消息显示后多久显示回应，为空时使用全局的发送后间隔
+    #[serde(default)]
+    pub reactions_after_ms: Option<u64>,
+}
+
+/// 任务的状态
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum MissionStatus {
+    #[default]
+    Accepted,
+    Completed,
+    Failed,
+}
+
+impl MissionStatus {
+    pub const ALL: [MissionStatus; 3] = [
+        MissionStatus::Accepted,
+        MissionStatus::Completed,
+        MissionStatus::Failed,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MissionStatus::Accepted => "已接取",
+            MissionStatus::Completed => "已完成",
+            MissionStatus::Failed => "已失败",
+        }
+    }
+}
+
+/// 任务中的一项目标
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct MissionObjective {
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// 任务消息的内容
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Mission {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub objectives: Vec<MissionObjective>,
+    #[serde(default)]
+    pub status: MissionStatus,
+}
+
+impl Mission {
+    /// 只能显示文字时的写法：标题和状态、说明、每项目标各占一行
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![format!("[任务] {}（{}）", self.title, self.status.label())];
+        if !self.description.is_empty() {
+            lines.push(self.description.clone());
+        }
+        for objective in &self.objectives {
+            let mark = if objective.done { "[x]" } else { "[ ]" };
+            lines.push(format!("{mark} {}", objective.text));
+        }
+        lines.join("\n")
+    }
+}
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Message {
     pub id: String,
@@ -75,6 +159,21 @@ pub struct Message {
     pub animate_reactions: bool,
     #[serde(default)]
     pub reactions: Vec<MessageReaction>,
+    #[serde(default)]
+    pub replay: Option<MessageReplayOverrides>,
+    /// 任务消息的内容，其他类型的消息为空
+    #[serde(default)]
+    pub mission: Option<Mission>,
+}
+
+impl Message {
+    /// 任务消息的文字写法，其他类型的消息为空
+    pub fn mission_text(&self) -> String {
+        self.mission
+            .as_ref()
+            .map(Mission::to_text)
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -134,6 +233,243 @@ impl Default for UserProfile {
     }
 }
 
+///
+/// 回放间隔模式。
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplayIntervalMode {
+    /// 固定间隔
+    Fixed,
+    /// 按字数：当前消息字数 * 每个字的间隔。请注意，当消息为表情包和图片时仍按照固定间隔处理
+    PerChar,
+}
+
+///
+/// 回放时消息的回应何时出现。
+///
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum ReplayReactionTiming {
+    /// 消息显示后再等待一个发送后间隔
+    #[default]
+    AfterMessage,
+    /// 与消息同时出现
+    WithMessage,
+}
+
+fn default_topic_ended_text() -> String {
+    crate::components::baker::replay::TOPIC_ENDED_TEXT.to_string()
+}
+
+///
+/// 回放设置。
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplaySettings {
+    /// 回放间隔模式
+    pub mode: ReplayIntervalMode,
+    /// 当设为固定间隔时的间隔
+    pub fixed_ms: u64,
+    /// 当设为按字数时，每个字的间隔
+    pub per_char_ms: u64,
+    /// 发送后的间隔
+    pub gap_ms: u64,
+    /// 自己的消息也先显示“输入中”
+    #[serde(default)]
+    pub self_typing: bool,
+    /// 状态消息显示后额外停顿的时间，为 0 时不停顿
+    #[serde(default)]
+    pub status_pause_ms: u64,
+    /// 回应何时出现
+    #[serde(default)]
+    pub reaction_timing: ReplayReactionTiming,
+    /// 回放结束时追加的提示，为空时不追加
+    #[serde(default = "default_topic_ended_text")]
+    pub topic_ended_text: String,
+}
+
+impl Default for ReplaySettings {
+    fn default() -> Self {
+        Self {
+            mode: ReplayIntervalMode::Fixed,
+            fixed_ms: 800,
+            per_char_ms: 40,
+            gap_ms: 200,
+            self_typing: false,
+            status_pause_ms: 0,
+            reaction_timing: ReplayReactionTiming::AfterMessage,
+            topic_ended_text: default_topic_ended_text(),
+        }
+    }
+}
+
+///
+/// 按顺序连续回放多个会话的播放列表。
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplayPlaylist {
+    /// 依次回放的会话
+    pub contact_ids: Vec<String>,
+    /// 一个会话回放结束后切换到下一个会话之前的停顿
+    pub pause_ms: u64,
+}
+
+impl Default for ReplayPlaylist {
+    fn default() -> Self {
+        Self {
+            contact_ids: Vec::new(),
+            pause_ms: 1500,
+        }
+    }
+}
+
+///
+/// 会播放提示音的事件。
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SoundEvent {
+    /// 收到对方的消息
+    Incoming,
+    /// 自己发出消息
+    Outgoing,
+    /// 状态消息
+    Status,
+    /// 消息收到回应
+    Reaction,
+    /// 回放结束时的话题结束提示
+    TopicEnded,
+}
+
+impl SoundEvent {
+    pub const ALL: [SoundEvent; 5] = [
+        SoundEvent::Incoming,
+        SoundEvent::Outgoing,
+        SoundEvent::Status,
+        SoundEvent::Reaction,
+        SoundEvent::TopicEnded,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SoundEvent::Incoming => "收到消息",
+            SoundEvent::Outgoing => "发出消息",
+            SoundEvent::Status => "状态消息",
+            SoundEvent::Reaction => "回应",
+            SoundEvent::TopicEnded => "话题结束",
+        }
+    }
+}
+
+///
+/// 一个事件的提示音。
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventSound {
+    pub enabled: bool,
+    /// 自定义的音频 (data URL)，为空时使用内置的声音
+    pub custom_src: String,
+}
+
+impl EventSound {
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            custom_src: String::new(),
+        }
+    }
+}
+
+impl Default for EventSound {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+///
+/// 声音设置。
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundSettings {
+    /// 主音量，0 到 1
+    pub volume: f64,
+    /// 关闭所有提示音
+    pub muted: bool,
+    /// 回放时不播放提示音
+    pub replay_muted: bool,
+    pub incoming: EventSound,
+    pub outgoing: EventSound,
+    pub status: EventSound,
+    pub reaction: EventSound,
+    pub topic_ended: EventSound,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            volume: 0.5,
+            muted: false,
+            replay_muted: false,
+            incoming: EventSound::new(true),
+            outgoing: EventSound::new(true),
+            status: EventSound::new(true),
+            reaction: EventSound::new(false),
+            topic_ended: EventSound::new(false),
+        }
+    }
+}
+
+impl SoundSettings {
+    pub fn event(&self, event: SoundEvent) -> &EventSound {
+        match event {
+            SoundEvent::Incoming => &self.incoming,
+            SoundEvent::Outgoing => &self.outgoing,
+            SoundEvent::Status => &self.status,
+            SoundEvent::Reaction => &self.reaction,
+            SoundEvent::TopicEnded => &self.topic_ended,
+        }
+    }
+
+    pub fn event_mut(&mut self, event: SoundEvent) -> &mut EventSound {
+        match event {
+            SoundEvent::Incoming => &mut self.incoming,
+            SoundEvent::Outgoing => &mut self.outgoing,
+            SoundEvent::Status => &mut self.status,
+            SoundEvent::Reaction => &mut self.reaction,
+            SoundEvent::TopicEnded => &mut self.topic_ended,
+        }
+    }
+
+    /// 播放这个事件的提示音时使用的音量；不需要播放时为 `None`
+    pub fn volume_for(&self, event: SoundEvent, in_replay: bool) -> Option<f64> {
+        let silent = self.muted || (in_replay && self.replay_muted) || !self.event(event).enabled;
+        let volume = self.volume.clamp(0.0, 1.0);
+        (!silent && volume > 0.0).then_some(volume)
+    }
+}
+
+///
+/// 导出文件的保存设置，只在桌面端使用，网页端由浏览器决定保存位置。
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    /// 导出的文件夹，为空时每次都询问；选择保存位置后会记住所在的文件夹
+    pub dir: String,
+    /// 每次导出都弹出保存对话框，否则直接写入导出的文件夹
+    pub ask: bool,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            dir: String::new(),
+            ask: true,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Default)]
 pub struct AppState {
     pub user_profile: UserProfile,
@@ -153,4 +489,14 @@ pub struct AppState {
     /// 暂缓开发的提醒
     #[serde(default)]
     pub showed_notice: bool,
+    /// 每个会话上次使用的回放设置
+    #[serde(default)]
+    pub replay_settings: HashMap<String, ReplaySettings>,
+    /// 上次使用的播放列表
+    #[serde(default)]
+    pub replay_playlist: ReplayPlaylist,
+    #[serde(default)]
+    pub sound: SoundSettings,
+    #[serde(default)]
+    pub export: ExportSettings,
 }
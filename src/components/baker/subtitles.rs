@@ -0,0 +1,189 @@
+use crate::components::baker::replay::{ReplayAction, ReplayPlayer};
+use crate::components::baker::storage::v2::MessageKind;
+
+///
+/// 字幕导出的格式。
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SubtitleFormat {
+    Srt,
+    WebVtt,
+    /// 每行一条消息、带时间的纯文本
+    Transcript,
+}
+
+impl SubtitleFormat {
+    pub const ALL: [SubtitleFormat; 3] = [
+        SubtitleFormat::Srt,
+        SubtitleFormat::WebVtt,
+        SubtitleFormat::Transcript,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "SRT",
+            SubtitleFormat::WebVtt => "WebVTT",
+            SubtitleFormat::Transcript => "文本",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::WebVtt => "vtt",
+            SubtitleFormat::Transcript => "txt",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "application/x-subrip",
+            SubtitleFormat::WebVtt => "text/vtt",
+            SubtitleFormat::Transcript => "text/plain",
+        }
+    }
+}
+
+///
+/// 一条字幕：从消息显示内容开始，到下一条消息显示内容为止。
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubtitleCue {
+    /// 相对回放开始的时间，已按速度换算
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// 发送者的名字，状态消息和话题结束提示没有
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+///
+/// 按回放时间线生成字幕。
+///
+/// 字幕在消息显示内容的时刻出现（对方的消息为“输入中”结束的时刻），与相同回放设置下的录屏对齐。
+///
+/// # 参数
+///
+/// - player: 回放的播放状态，只使用其中的时间线、消息和速度。
+/// - hold_ms: 最后一条字幕停留的时间。
+/// - sender_name: 根据发送者编号取得显示的名字。
+///
+pub fn subtitle_cues(
+    player: &ReplayPlayer,
+    hold_ms: u64,
+    sender_name: impl Fn(&str) -> String,
+) -> Vec<SubtitleCue> {
+    let speed = player.speed.max(0.01);
+    let scaled = |ms: u64| (ms as f64 / speed).round() as u64;
+
+    let mut cues: Vec<SubtitleCue> = Vec::new();
+    for event in &player.timeline.events {
+        let (speaker, text) = match &event.action {
+            ReplayAction::Reveal { index } | ReplayAction::Show { index } => {
+                let msg = &player.source[*index];
+                let text = match msg.kind {
+                    MessageKind::Image => "[图片]".to_string(),
+                    MessageKind::Sticker => "[表情]".to_string(),
+                    MessageKind::Mission => msg.mission_text(),
+                    _ => msg.content.clone(),
+                };
+                let speaker = match msg.kind {
+                    MessageKind::Status | MessageKind::TopicEnded => None,
+                    _ => Some(sender_name(&msg.sender_id)).filter(|name| !name.is_empty()),
+                };
+                (speaker, text)
+            }
+            ReplayAction::TopicEnded { text } => (None, text.clone()),
+            _ => continue,
+        };
+        let start_ms = scaled(event.at_ms);
+        if let Some(last) = cues.last_mut() {
+            last.end_ms = start_ms;
+        }
+        cues.push(SubtitleCue {
+            start_ms,
+            end_ms: start_ms + hold_ms,
+            speaker,
+            text,
+        });
+    }
+    cues
+}
+
+/// `HH:MM:SS{separator}mmm`
+fn timestamp(ms: u64, separator: char) -> String {
+    let (hours, rest) = (ms / 3_600_000, ms % 3_600_000);
+    let (minutes, rest) = (rest / 60_000, rest % 60_000);
+    let (seconds, millis) = (rest / 1000, rest % 1000);
+    format!("{hours:02}:{minutes:02}:{seconds:02}{separator}{millis:03}")
+}
+
+impl SubtitleCue {
+    /// 字幕的文字，空行会让字幕提前结束，这里去掉
+    fn lines(&self) -> Vec<String> {
+        let mut lines = self
+            .text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        if let Some(speaker) = &self.speaker {
+            lines[0] = format!("{speaker}：{}", lines[0]);
+        }
+        lines
+    }
+}
+
+///
+/// 把字幕写成指定的格式。
+///
+pub fn render_subtitles(cues: &[SubtitleCue], format: SubtitleFormat) -> String {
+    let mut out = String::new();
+    match format {
+        SubtitleFormat::Srt => {
+            for (index, cue) in cues.iter().enumerate() {
+                out.push_str(&format!(
+                    "{}\n{} --> {}\n{}\n\n",
+                    index + 1,
+                    timestamp(cue.start_ms, ','),
+                    timestamp(cue.end_ms, ','),
+                    cue.lines().join("\n"),
+                ));
+            }
+        }
+        SubtitleFormat::WebVtt => {
+            out.push_str("WEBVTT\n\n");
+            for cue in cues {
+                let text = cue
+                    .lines()
+                    .join("\n")
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;");
+                out.push_str(&format!(
+                    "{} --> {}\n{text}\n\n",
+                    timestamp(cue.start_ms, '.'),
+                    timestamp(cue.end_ms, '.'),
+                ));
+            }
+        }
+        SubtitleFormat::Transcript => {
+            for cue in cues {
+                let stamp = timestamp(cue.start_ms, '.');
+                for (index, line) in cue.lines().into_iter().enumerate() {
+                    // 多行消息的后续行与第一行对齐
+                    if index == 0 {
+                        out.push_str(&format!("[{stamp}] {line}\n"));
+                    } else {
+                        out.push_str(&format!("{:width$}{line}\n", "", width = stamp.len() + 3));
+                    }
+                }
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,430 @@
+use crate::components::baker::chat_render::{ChatSource, reaction_labels};
+use crate::components::baker::storage::v2::{Message, MessageKind, Mission};
+use base64::Engine;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+///
+/// 文字导出的格式。
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextFormat {
+    /// 图片另存为文件，在文中引用
+    Markdown,
+    /// 单个网页，图片直接嵌入
+    Html,
+    PlainText,
+}
+
+impl TextFormat {
+    pub const ALL: [TextFormat; 3] = [
+        TextFormat::Markdown,
+        TextFormat::Html,
+        TextFormat::PlainText,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TextFormat::Markdown => "Markdown",
+            TextFormat::Html => "HTML 网页",
+            TextFormat::PlainText => "纯文本",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            TextFormat::Markdown => "md",
+            TextFormat::Html => "html",
+            TextFormat::PlainText => "txt",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            TextFormat::Markdown => "text/markdown",
+            TextFormat::Html => "text/html",
+            TextFormat::PlainText => "text/plain",
+        }
+    }
+}
+
+///
+/// 导出的文字，以及 Markdown 中引用的图片文件。
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextExport {
+    pub text: String,
+    /// 相对文字文件的路径和内容
+    pub files: Vec<(String, Vec<u8>)>,
+}
+
+/// Markdown 引用的图片所在的文件夹
+pub const MARKDOWN_IMAGE_DIR: &str = "images";
+
+///
+/// 把会话导出为文字。
+///
+/// 每条消息都标明发送者；群聊中按 `Operator` 显示名字。
+///
+/// # 参数
+///
+/// - source: 要导出的会话和消息。
+/// - format: 导出的格式。
+/// - images: 不是 data URL 的图片地址对应的 data URL，用于嵌入或另存网络和应用内的图片；
+///   找不到时保留原来的地址。
+///
+pub fn export_text(
+    source: &ChatSource,
+    format: TextFormat,
+    images: &HashMap<String, String>,
+) -> TextExport {
+    match format {
+        TextFormat::Markdown => export_markdown(source, images),
+        TextFormat::Html => TextExport {
+            text: export_html(source, images),
+            files: Vec::new(),
+        },
+        TextFormat::PlainText => TextExport {
+            text: export_plain_text(source),
+            files: Vec::new(),
+        },
+    }
+}
+
+/// 发送者的名字，找不到时显示为未知成员
+fn sender_name(source: &ChatSource, sender_id: &str) -> String {
+    let (name, _) = source.sender(sender_id);
+    if name.is_empty() {
+        "未知成员".to_string()
+    } else {
+        name
+    }
+}
+
+/// 图片对应的 data URL，没有时为原来的地址
+fn embedded<'a>(src: &'a str, images: &'a HashMap<String, String>) -> &'a str {
+    images.get(src).map(String::as_str).unwrap_or(src)
+}
+
+/// 取出 base64 data URL 中的类型和数据
+fn split_data_url(url: &str) -> Option<(&str, Vec<u8>)> {
+    let (mime, encoded) = url.strip_prefix("data:")?.split_once(";base64,")?;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()?;
+    Some((mime, bytes))
+}
+
+fn image_extension(mime: &str) -> &str {
+    match mime {
+        "image/jpeg" => "jpg",
+        "image/svg+xml" => "svg",
+        _ => mime.strip_prefix("image/").unwrap_or("bin"),
+    }
+}
+
+fn reactions_text(msg: &Message) -> Option<String> {
+    let labels = reaction_labels(&msg.reactions);
+    (!labels.is_empty()).then(|| labels.join("、"))
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (index, line) in text.lines().enumerate() {
+        if index > 0 {
+            // 行尾两个空格表示换行
+            out.push_str("  \n");
+        }
+        for (position, c) in line.chars().enumerate() {
+            let special = matches!(
+                c,
+                '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~'
+            );
+            // 行首的 - 和 + 会变成列表
+            if special || (position == 0 && matches!(c, '-' | '+')) {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn export_markdown(source: &ChatSource, images: &HashMap<String, String>) -> TextExport {
+    let mut text = format!("# {}\n", escape_markdown(&source.header_name()));
+    let mut files = Vec::new();
+    // 同一张图片只保存一次
+    let mut saved = HashMap::<String, String>::new();
+    for msg in source.messages {
+        text.push('\n');
+        match msg.kind {
+            MessageKind::Status => {
+                let _ = writeln!(text, "*{}*", escape_markdown(&msg.content));
+                continue;
+            }
+            MessageKind::TopicEnded => {
+                let _ = writeln!(text, "---\n\n*{}*", escape_markdown(&msg.content));
+                continue;
+            }
+            _ => {}
+        }
+
+        let name = escape_markdown(&sender_name(source, &msg.sender_id));
+        let _ = write!(text, "**{name}**：");
+        match msg.kind {
+            MessageKind::Image | MessageKind::Sticker => {
+                let alt = if msg.kind == MessageKind::Image {
+                    "图片"
+                } else {
+                    "表情"
+                };
+                let path = match saved.get(&msg.content) {
+                    Some(path) => path.clone(),
+                    None => {
+                        let path = match split_data_url(embedded(&msg.content, images)) {
+                            Some((mime, bytes)) => {
+                                let path = format!(
+                                    "{MARKDOWN_IMAGE_DIR}/{:03}.{}",
+                                    files.len() + 1,
+                                    image_extension(mime)
+                                );
+                                files.push((path.clone(), bytes));
+                                path
+                            }
+                            None => msg.content.clone(),
+                        };
+                        saved.insert(msg.content.clone(), path.clone());
+                        path
+                    }
+                };
+                let _ = write!(text, "![{alt}](<{path}>)");
+            }
+            MessageKind::Mission => text.push_str(&escape_markdown(&msg.mission_text())),
+            _ => text.push_str(&escape_markdown(&msg.content)),
+        }
+        if let Some(reactions) = reactions_text(msg) {
+            let _ = write!(text, "  \n*回应：{}*", escape_markdown(&reactions));
+        }
+        text.push('\n');
+    }
+    TextExport { text, files }
+}
+
+fn export_plain_text(source: &ChatSource) -> String {
+    let mut text = format!("{}\n\n", source.header_name());
+    for msg in source.messages {
+        match msg.kind {
+            MessageKind::Status => {
+                let _ = writeln!(text, "（{}）", msg.content);
+                continue;
+            }
+            MessageKind::TopicEnded => {
+                let _ = writeln!(text, "\n—— {} ——\n", msg.content);
+                continue;
+            }
+            _ => {}
+        }
+        let mission_text;
+        let content = match msg.kind {
+            MessageKind::Image => "[图片]",
+            MessageKind::Sticker => "[表情]",
+            MessageKind::Mission => {
+                mission_text = msg.mission_text();
+                mission_text.as_str()
+            }
+            _ => msg.content.as_str(),
+        };
+        let _ = writeln!(text, "{}：{content}", sender_name(source, &msg.sender_id));
+        if let Some(reactions) = reactions_text(msg) {
+            let _ = writeln!(text, "  回应：{reactions}");
+        }
+    }
+    text
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '\n' => out.push_str("<br>"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// 网页的样式，颜色和尺寸与聊天区域一致
+const HTML_STYLE: &str = r#"
+* { box-sizing: border-box; }
+body { margin: 0; min-height: 100vh; background-color: #1a1a1a; background-image: radial-gradient(#2a2a2a 1px, transparent 1px); background-size: 20px 20px; font-family: "Source Han Sans SC", "PingFang SC", "Microsoft YaHei", sans-serif; }
+.chat { max-width: 800px; margin: 0 auto; padding: 24px 12px; }
+.head { height: 56px; display: flex; align-items: center; padding: 0 32px; margin-bottom: 4px; background: #282828; border-bottom: 2px solid #cac9c9; color: #fff; font-size: 18px; font-weight: bold; }
+.status { margin: 8px 0; color: #6b7280; font-size: 12px; line-height: 16px; text-align: center; }
+.topic-ended { margin: 24px 0; color: #919191; font-size: 14px; line-height: 20px; text-align: center; }
+.row { display: flex; gap: 20px; margin-top: 4px; align-items: flex-start; }
+.row.first { margin-top: 16px; }
+.row.right { flex-direction: row-reverse; }
+.avatar { width: 56px; height: 56px; flex-shrink: 0; border: 2px solid rgba(255, 255, 255, 0.75); background: #4b5563; color: #d1d5db; object-fit: cover; display: flex; align-items: center; justify-content: center; font-weight: bold; }
+.avatar.hidden { visibility: hidden; }
+.body { display: flex; flex-direction: column; max-width: 60%; }
+.right .body { align-items: flex-end; }
+.name { color: #a7a7a7; font-size: 14px; line-height: 20px; margin-bottom: 4px; }
+.bubble { padding: 8px 12px; border-radius: 16px; font-size: 16px; line-height: 26px; white-space: normal; word-break: break-word; background: #454545; color: #fff; }
+.right .bubble { background: #f3f2f2; color: #000; }
+.left .bubble { border-top-left-radius: 0; }
+.right .bubble { border-top-right-radius: 0; }
+.media { max-width: 320px; border-radius: 16px; }
+.media.sticker { max-width: 200px; }
+.mission { min-width: 240px; border-left: 4px solid #f59e0b; }
+.mission-head { display: flex; align-items: center; gap: 8px; font-weight: bold; }
+.mission-badge { padding: 0 6px; border-radius: 4px; background: #f59e0b; color: #000; font-size: 12px; line-height: 20px; }
+.mission-status { margin-left: auto; font-size: 12px; font-weight: normal; opacity: 0.8; }
+.mission-description { margin-top: 4px; font-size: 14px; line-height: 22px; opacity: 0.85; }
+.mission ul { margin: 4px 0 0; padding: 0; list-style: none; font-size: 14px; line-height: 22px; }
+.mission li::before { content: "☐ "; }
+.mission li.done { text-decoration: line-through; opacity: 0.6; }
+.mission li.done::before { content: "☑ "; }
+.reactions { display: flex; flex-wrap: wrap; gap: 4px; margin-top: 4px; }
+.reactions span { padding: 2px 8px; border-radius: 12px; background: #3c3c3c; color: #e5e7eb; font-size: 14px; line-height: 20px; }
+"#;
+
+fn mission_html(mission: &Mission) -> String {
+    let mut html = format!(
+        "<div class=\"bubble mission\"><div class=\"mission-head\">\
+         <span class=\"mission-badge\">任务</span><span>{}</span>\
+         <span class=\"mission-status\">{}</span></div>",
+        escape_html(&mission.title),
+        mission.status.label()
+    );
+    if !mission.description.is_empty() {
+        let _ = write!(
+            html,
+            "<div class=\"mission-description\">{}</div>",
+            escape_html(&mission.description)
+        );
+    }
+    if !mission.objectives.is_empty() {
+        html.push_str("<ul>");
+        for objective in &mission.objectives {
+            let class = if objective.done {
+                " class=\"done\""
+            } else {
+                ""
+            };
+            let _ = write!(html, "<li{class}>{}</li>", escape_html(&objective.text));
+        }
+        html.push_str("</ul>");
+    }
+    html.push_str("</div>");
+    html
+}
+
+fn export_html(source: &ChatSource, images: &HashMap<String, String>) -> String {
+    let title = escape_html(&source.header_name());
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n\
+         <main class=\"chat\">\n<header class=\"head\">{title}</header>\n"
+    );
+    let mut last_sender_id = None;
+    for msg in source.messages {
+        match msg.kind {
+            MessageKind::Status => {
+                let _ = writeln!(
+                    html,
+                    "<p class=\"status\">{}</p>",
+                    escape_html(&msg.content)
+                );
+                last_sender_id = None;
+                continue;
+            }
+            MessageKind::TopicEnded => {
+                let _ = writeln!(
+                    html,
+                    "<p class=\"topic-ended\">{}</p>",
+                    escape_html(&msg.content)
+                );
+                last_sender_id = None;
+                continue;
+            }
+            _ => {}
+        }
+
+        let first = last_sender_id != Some(msg.sender_id.as_str());
+        last_sender_id = Some(msg.sender_id.as_str());
+        let side = if source.on_right(&msg.sender_id) {
+            "right"
+        } else {
+            "left"
+        };
+        let name = sender_name(source, &msg.sender_id);
+        let (_, avatar) = source.sender(&msg.sender_id);
+        let _ = write!(
+            html,
+            "<div class=\"row {side}{}\">",
+            if first { " first" } else { "" }
+        );
+        let hidden = if first { "" } else { " hidden" };
+        if avatar.is_empty() {
+            let initial = name.chars().next().map(String::from).unwrap_or_default();
+            let _ = write!(
+                html,
+                "<div class=\"avatar{hidden}\">{}</div>",
+                escape_html(&initial)
+            );
+        } else {
+            let _ = write!(
+                html,
+                "<img class=\"avatar{hidden}\" src=\"{}\" alt=\"\">",
+                escape_html(embedded(&avatar, images))
+            );
+        }
+        html.push_str("<div class=\"body\">");
+        // 群聊中每条消息都标明发送者
+        if source.contact.is_group {
+            let _ = write!(html, "<div class=\"name\">{}</div>", escape_html(&name));
+        }
+        match msg.kind {
+            MessageKind::Image | MessageKind::Sticker => {
+                let class = if msg.kind == MessageKind::Image {
+                    "media"
+                } else {
+                    "media sticker"
+                };
+                let _ = write!(
+                    html,
+                    "<img class=\"{class}\" src=\"{}\" alt=\"\">",
+                    escape_html(embedded(&msg.content, images))
+                );
+            }
+            MessageKind::Mission => {
+                let mission = msg.mission.clone().unwrap_or_default();
+                html.push_str(&mission_html(&mission));
+            }
+            _ => {
+                let _ = write!(
+                    html,
+                    "<div class=\"bubble\">{}</div>",
+                    escape_html(&msg.content)
+                );
+            }
+        }
+        let reactions = reaction_labels(&msg.reactions);
+        if !reactions.is_empty() {
+            html.push_str("<div class=\"reactions\">");
+            for label in reactions {
+                let _ = write!(html, "<span>{}</span>", escape_html(&label));
+            }
+            html.push_str("</div>");
+        }
+        html.push_str("</div></div>\n");
+    }
+    html.push_str("</main>\n</body>\n</html>\n");
+    html
+}
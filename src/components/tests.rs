@@ -10,3 +10,1650 @@ fn test_is_remote_newer() {
     assert!(!is_remote_newer("1.0.2", "1.0.2").unwrap());
     assert!(!is_remote_newer("1.0.2", "1.0.1").unwrap());
 }
+
+mod replay {
+    use crate::components::baker::chat_area::{PendingTyping, ReplayTypingPhase};
+    use crate::components::baker::replay::{
+        REVEAL_MS, ReplayAction::*, ReplayEnd, ReplayEvent, ReplayIntervalMode, ReplayPlayer,
+        ReplayReactionTiming, ReplaySettings, TOPIC_ENDED_TEXT, build_timeline, replay_range,
+    };
+    use crate::components::baker::storage::v2::{
+        AppState, Message, MessageKind, MessageReaction, MessageReplayOverrides, SoundEvent,
+    };
+
+    pub(super) const USER: &str = "user";
+    pub(super) const OTHER: &str = "perlica";
+
+    pub(super) fn message(id: &str, sender_id: &str, content: &str, kind: MessageKind) -> Message {
+        Message {
+            id: id.to_string(),
+            sender_id: sender_id.to_string(),
+            content: content.to_string(),
+            kind,
+            animate: false,
+            animate_reactions: false,
+            reactions: Vec::new(),
+            replay: None,
+            mission: None,
+        }
+    }
+
+    pub(super) fn settings(mode: ReplayIntervalMode) -> ReplaySettings {
+        ReplaySettings {
+            mode,
+            fixed_ms: 800,
+            per_char_ms: 40,
+            gap_ms: 100,
+            ..Default::default()
+        }
+    }
+
+    fn at(at_ms: u64, action: crate::components::baker::replay::ReplayAction) -> ReplayEvent {
+        ReplayEvent { at_ms, action }
+    }
+
+    fn sound(at_ms: u64, event: SoundEvent) -> ReplayEvent {
+        at(at_ms, PlaySound { event })
+    }
+
+    fn topic_ended() -> crate::components::baker::replay::ReplayAction {
+        TopicEnded {
+            text: TOPIC_ENDED_TEXT.to_string(),
+        }
+    }
+
+    pub(super) fn mixed_messages() -> Vec<Message> {
+        let mut reacted = message("2", USER, "收到", MessageKind::Normal);
+        reacted.reactions.push(MessageReaction {
+            content: "👍".to_string(),
+            sender_id: OTHER.to_string(),
+        });
+        vec![
+            message("0", OTHER, "管理员，你好", MessageKind::Normal),
+            message("1", USER, "佩丽卡加入了群聊", MessageKind::Status),
+            reacted,
+            message("3", OTHER, "data:image/png;base64,", MessageKind::Image),
+        ]
+    }
+
+    #[test]
+    fn test_timeline_fixed_interval() {
+        let timeline = build_timeline(
+            &mixed_messages(),
+            USER,
+            &settings(ReplayIntervalMode::Fixed),
+        );
+        assert_eq!(
+            timeline.events,
+            vec![
+                at(100, ShowTyping { index: 0 }),
+                at(900, Reveal { index: 0 }),
+                sound(900, SoundEvent::Incoming),
+                at(900 + REVEAL_MS, ClearPending),
+                // 状态消息不显示输入中, 也不计算输入时长
+                at(1200, Show { index: 1 }),
+                sound(1200, SoundEvent::Status),
+                at(2100, Show { index: 2 }),
+                sound(2100, SoundEvent::Outgoing),
+                at(2200, ApplyReactions { index: 2 }),
+                at(2300, ShowTyping { index: 3 }),
+                at(3100, Reveal { index: 3 }),
+                sound(3100, SoundEvent::Incoming),
+                at(3300, ClearPending),
+                at(3300, topic_ended()),
+            ]
+        );
+        assert_eq!(timeline.duration_ms(), 3300);
+    }
+
+    #[test]
+    fn test_timeline_per_char_interval() {
+        let timeline = build_timeline(
+            &mixed_messages(),
+            USER,
+            &settings(ReplayIntervalMode::PerChar),
+        );
+        let reveals = timeline
+            .events
+            .iter()
+            .filter(|event| matches!(event.action, Reveal { .. } | Show { .. }))
+            .map(|event| event.at_ms)
+            .collect::<Vec<_>>();
+        // 6 个字 * 40ms; 图片仍按固定间隔
+        assert_eq!(reveals, vec![340, 640, 820, 1820]);
+    }
+
+    #[test]
+    fn test_timeline_honours_message_overrides() {
+        let mut messages = mixed_messages();
+        messages[0].replay = Some(MessageReplayOverrides {
+            typing_ms: Some(2000),
+            pause_after_ms: 500,
+            ..Default::default()
+        });
+        messages[2].replay = Some(MessageReplayOverrides {
+            pause_before_ms: 1000,
+            reactions_after_ms: Some(1500),
+            ..Default::default()
+        });
+        messages[3].replay = Some(MessageReplayOverrides {
+            skip_typing: true,
+            ..Default::default()
+        });
+        let timeline = build_timeline(&messages, USER, &settings(ReplayIntervalMode::Fixed));
+        assert_eq!(
+            timeline.events,
+            vec![
+                at(100, ShowTyping { index: 0 }),
+                at(2100, Reveal { index: 0 }),
+                sound(2100, SoundEvent::Incoming),
+                at(2100 + REVEAL_MS, ClearPending),
+                at(2900, Show { index: 1 }),
+                sound(2900, SoundEvent::Status),
+                at(4800, Show { index: 2 }),
+                sound(4800, SoundEvent::Outgoing),
+                at(6300, ApplyReactions { index: 2 }),
+                // 跳过输入中: 等待输入时长后直接显示
+                at(7200, Show { index: 3 }),
+                sound(7200, SoundEvent::Incoming),
+                at(7200, topic_ended()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timeline_options() {
+        let timeline = build_timeline(
+            &mixed_messages(),
+            USER,
+            &ReplaySettings {
+                self_typing: true,
+                status_pause_ms: 500,
+                reaction_timing: ReplayReactionTiming::WithMessage,
+                topic_ended_text: String::new(),
+                ..settings(ReplayIntervalMode::Fixed)
+            },
+        );
+        assert_eq!(
+            timeline.events,
+            vec![
+                at(100, ShowTyping { index: 0 }),
+                at(900, Reveal { index: 0 }),
+                sound(900, SoundEvent::Incoming),
+                at(1100, ClearPending),
+                at(1200, Show { index: 1 }),
+                sound(1200, SoundEvent::Status),
+                // 状态消息之后停顿 500ms, 自己的消息也显示输入中
+                at(1800, ShowTyping { index: 2 }),
+                at(2600, Reveal { index: 2 }),
+                sound(2600, SoundEvent::Outgoing),
+                at(2600, ApplyReactions { index: 2 }),
+                at(2800, ClearPending),
+                at(2900, ShowTyping { index: 3 }),
+                at(3700, Reveal { index: 3 }),
+                sound(3700, SoundEvent::Incoming),
+                at(3900, ClearPending),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replay_range() {
+        let mut messages = mixed_messages();
+        messages.push(message("4", USER, "话题结束", MessageKind::TopicEnded));
+        messages.push(message("5", OTHER, "还在吗", MessageKind::Normal));
+
+        assert_eq!(replay_range(&messages, "0", &ReplayEnd::Last), 0..6);
+        assert_eq!(
+            replay_range(&messages, "2", &ReplayEnd::Message("3".to_string())),
+            2..4
+        );
+        assert_eq!(
+            replay_range(&messages, "2", &ReplayEnd::Message("2".to_string())),
+            2..3
+        );
+        // 结束消息在开始之前时播放到最后
+        assert_eq!(
+            replay_range(&messages, "3", &ReplayEnd::Message("0".to_string())),
+            3..6
+        );
+        // 停在标记之前, 开始的消息本身是标记时不算
+        assert_eq!(replay_range(&messages, "0", &ReplayEnd::NextMarker), 0..1);
+        assert_eq!(replay_range(&messages, "1", &ReplayEnd::NextMarker), 1..4);
+        assert_eq!(replay_range(&messages, "5", &ReplayEnd::NextMarker), 5..6);
+        assert_eq!(replay_range(&messages, "missing", &ReplayEnd::Last), 0..6);
+
+        for end in [
+            ReplayEnd::Last,
+            ReplayEnd::NextMarker,
+            ReplayEnd::Message("message:3".to_string()),
+        ] {
+            assert_eq!(ReplayEnd::from_option_value(&end.option_value()), end);
+        }
+    }
+
+    #[test]
+    fn test_replay_settings_are_persisted_per_contact() {
+        let mut state = AppState::default();
+        state.replay_settings.insert(
+            "contact".to_string(),
+            ReplaySettings {
+                mode: ReplayIntervalMode::PerChar,
+                fixed_ms: 600,
+                per_char_ms: 55,
+                gap_ms: 300,
+                self_typing: true,
+                status_pause_ms: 400,
+                reaction_timing: ReplayReactionTiming::WithMessage,
+                topic_ended_text: String::new(),
+            },
+        );
+        let json = serde_json::to_string(&state).unwrap();
+        let restored = serde_json::from_str::<AppState>(&json).unwrap();
+        assert_eq!(restored.replay_settings, state.replay_settings);
+
+        // 旧的存档没有这个字段
+        let mut value = serde_json::to_value(&state).unwrap();
+        value.as_object_mut().unwrap().remove("replay_settings");
+        let restored = serde_json::from_value::<AppState>(value).unwrap();
+        assert!(restored.replay_settings.is_empty());
+
+        // 更早保存的设置没有后来加入的选项
+        let restored = serde_json::from_str::<ReplaySettings>(
+            r#"{"mode":"Fixed","fixed_ms":800,"per_char_ms":40,"gap_ms":200}"#,
+        )
+        .unwrap();
+        assert_eq!(restored, ReplaySettings::default());
+    }
+
+    #[test]
+    fn test_timeline_of_nothing_only_ends_the_topic() {
+        let timeline = build_timeline(&[], USER, &ReplaySettings::default());
+        assert_eq!(timeline.events, vec![at(0, topic_ended()),]);
+    }
+
+    #[test]
+    fn test_applying_timeline_reproduces_messages() {
+        let source = mixed_messages();
+        let timeline = build_timeline(&source, USER, &ReplaySettings::default());
+        let mut messages = Vec::new();
+        let mut pending = None;
+        for event in &timeline.events {
+            event.action.apply_to_messages(&source, USER, &mut messages);
+            pending = event.action.pending_after(&source, pending);
+            if event.action == (ShowTyping { index: 3 }) {
+                assert_eq!(
+                    pending,
+                    Some(PendingTyping {
+                        id: "3".to_string(),
+                        phase: ReplayTypingPhase::Typing,
+                    })
+                );
+            }
+        }
+        assert_eq!(pending, None);
+        assert_eq!(messages.len(), source.len() + 1);
+        for (shown, original) in messages.iter().zip(&source) {
+            assert_eq!(shown.id, original.id);
+            assert_eq!(shown.reactions, original.reactions);
+        }
+        let last = messages.last().unwrap();
+        assert_eq!(last.kind, MessageKind::TopicEnded);
+        assert_eq!(last.content, TOPIC_ENDED_TEXT);
+        assert_eq!(last.sender_id, USER);
+    }
+
+    #[test]
+    fn test_player_delays_follow_speed() {
+        let mut player = ReplayPlayer::new(
+            mixed_messages(),
+            USER.to_string(),
+            &settings(ReplayIntervalMode::Fixed),
+        );
+        assert_eq!(player.next_event().unwrap().0, 100);
+        player.cursor = 1;
+        assert_eq!(player.next_event().unwrap().0, 800);
+        player.speed = 2.0;
+        assert_eq!(player.next_event().unwrap().0, 400);
+        player.speed = 0.5;
+        assert_eq!(player.next_event().unwrap().0, 1600);
+        player.cursor = player.timeline.events.len();
+        assert!(player.is_finished());
+        assert!(player.next_event().is_none());
+    }
+
+    #[test]
+    fn test_player_resumes_interrupted_wait() {
+        let mut player = ReplayPlayer::new(
+            mixed_messages(),
+            USER.to_string(),
+            &settings(ReplayIntervalMode::Fixed),
+        );
+        player.cursor = 1;
+        player.begin_wait(1000);
+        player.interrupt_wait(1300);
+        assert_eq!(player.next_event().unwrap().0, 500);
+        // 变速之后剩下的部分按新速度等待
+        player.speed = 2.0;
+        assert_eq!(player.next_event().unwrap().0, 250);
+        player.begin_wait(2000);
+        player.interrupt_wait(2100);
+        assert_eq!(player.next_event().unwrap().0, 150);
+        // 其他动作不受影响，跳转之后重新等待
+        player.cursor = 0;
+        assert_eq!(player.next_event().unwrap().0, 50);
+        player.seek(1);
+        assert_eq!(player.next_event().unwrap().0, 400);
+    }
+
+    #[test]
+    fn test_player_steps_by_message() {
+        let mut player = ReplayPlayer::new(
+            mixed_messages(),
+            USER.to_string(),
+            &settings(ReplayIntervalMode::Fixed),
+        );
+        let mut stops = vec![player.cursor];
+        while !player.is_finished() {
+            player.cursor = player.next_boundary();
+            stops.push(player.cursor);
+        }
+        // 每条消息播放完之后停一次, 最后是话题结束
+        assert_eq!(stops, vec![0, 4, 6, 9, 13, 14]);
+        assert_eq!(
+            (0..=4)
+                .map(|count| player.position_after(count))
+                .collect::<Vec<_>>(),
+            vec![0, 4, 6, 9, 13]
+        );
+
+        while player.cursor > 0 {
+            player.cursor = player.previous_boundary();
+            stops.pop();
+            assert_eq!(Some(&player.cursor), stops.last());
+        }
+
+        // 停在消息中间时, 后退回到这条消息开始之前
+        player.cursor = 10;
+        assert_eq!(player.previous_boundary(), 9);
+        assert_eq!(player.next_boundary(), 13);
+    }
+
+    #[test]
+    fn test_player_snapshot() {
+        let mut player = ReplayPlayer::new(
+            mixed_messages(),
+            USER.to_string(),
+            &settings(ReplayIntervalMode::Fixed),
+        );
+        player.cursor = 10;
+        let (messages, pending) = player.snapshot();
+        assert_eq!(player.shown_count(), 4);
+        assert_eq!(
+            messages.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(),
+            vec!["0", "1", "2", "3"]
+        );
+        assert!(messages.iter().all(|m| !m.animate && !m.animate_reactions));
+        assert_eq!(messages[2].reactions.len(), 1);
+        assert_eq!(
+            pending,
+            Some(PendingTyping {
+                id: "3".to_string(),
+                phase: ReplayTypingPhase::Typing,
+            })
+        );
+
+        player.cursor = player.timeline.events.len();
+        let (messages, pending) = player.snapshot();
+        assert_eq!(messages.len(), 5);
+        assert_eq!(pending, None);
+    }
+}
+
+mod replay_export {
+    use super::replay::{USER, mixed_messages, settings};
+    use crate::components::baker::replay::{ReplayIntervalMode, ReplayPlayer};
+    use crate::components::baker::replay_export::{
+        AnimationFormat, ExportFrame, RenderedFrame, encode_animation, encode_png_sequence,
+        export_frames, frame_ticks, normalize_frames,
+    };
+    use image::{AnimationDecoder, Rgba, RgbaImage};
+    use std::io::Cursor;
+
+    fn player() -> ReplayPlayer {
+        ReplayPlayer::new(
+            mixed_messages(),
+            USER.to_string(),
+            &settings(ReplayIntervalMode::Fixed),
+        )
+    }
+
+    fn frame(cursor: usize, start_ms: u64, duration_ms: u64) -> ExportFrame {
+        ExportFrame {
+            cursor,
+            start_ms,
+            duration_ms,
+        }
+    }
+
+    fn solid(width: u32, height: u32, color: [u8; 4], ticks: u32) -> RenderedFrame {
+        RenderedFrame {
+            image: RgbaImage::from_pixel(width, height, Rgba(color)),
+            ticks,
+        }
+    }
+
+    fn sample_frames() -> Vec<RenderedFrame> {
+        vec![
+            solid(4, 3, [255, 0, 0, 255], 3),
+            // 短于一帧的画面被跳过
+            solid(4, 3, [0, 255, 0, 255], 0),
+            solid(4, 3, [0, 0, 255, 255], 2),
+        ]
+    }
+
+    fn assert_close(actual: &Rgba<u8>, expected: [u8; 4]) {
+        for (a, e) in actual.0.iter().zip(expected) {
+            assert!(a.abs_diff(e) <= 8, "{actual:?} != {expected:?}");
+        }
+    }
+
+    fn check_decoded(frames: Vec<image::Frame>) {
+        let delays = frames
+            .iter()
+            .map(|frame| frame.delay().numer_denom_ms())
+            .map(|(numer, denom)| numer / denom)
+            .collect::<Vec<_>>();
+        assert_eq!(delays, vec![300, 200]);
+        assert_close(frames[0].buffer().get_pixel(1, 1), [255, 0, 0, 255]);
+        assert_close(frames[1].buffer().get_pixel(3, 2), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_export_frames_merge_simultaneous_actions() {
+        let frames = export_frames(&player(), 2000);
+        assert_eq!(
+            frames,
+            vec![
+                frame(0, 0, 100),
+                frame(1, 100, 800),
+                // 显示内容和提示音合并为一帧
+                frame(3, 900, 200),
+                frame(4, 1100, 100),
+                frame(6, 1200, 900),
+                frame(8, 2100, 100),
+                frame(9, 2200, 100),
+                frame(10, 2300, 800),
+                frame(12, 3100, 200),
+                frame(14, 3300, 2000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_frames_follow_speed() {
+        let mut player = player();
+        player.speed = 2.0;
+        let frames = export_frames(&player, 1000);
+        assert_eq!(frames[1], frame(1, 50, 400));
+        assert_eq!(frames.last(), Some(&frame(14, 1650, 1000)));
+    }
+
+    #[test]
+    fn test_frame_ticks_do_not_drift() {
+        let frames = vec![frame(0, 0, 100), frame(1, 100, 20), frame(2, 120, 880)];
+        // 15 帧每秒: 1.5, 0.3, 13.2 帧
+        assert_eq!(frame_ticks(&frames, 15), vec![2, 0, 13]);
+        assert_eq!(frame_ticks(&frames, 10), vec![1, 0, 9]);
+    }
+
+    #[test]
+    fn test_normalize_frames_pads_to_largest() {
+        let mut frames = vec![
+            solid(4, 3, [255, 0, 0, 255], 1),
+            solid(3, 5, [0, 0, 255, 255], 1),
+        ];
+        normalize_frames(&mut frames);
+        assert!(frames.iter().all(|f| f.image.dimensions() == (4, 5)));
+        assert_eq!(frames[0].image.get_pixel(0, 4).0, [0, 0, 0, 0]);
+        assert_eq!(frames[1].image.get_pixel(2, 4).0, [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_encode_gif() {
+        let bytes = encode_animation(&sample_frames(), 10, AnimationFormat::Gif).unwrap();
+        let decoder = image::codecs::gif::GifDecoder::new(Cursor::new(bytes)).unwrap();
+        check_decoded(decoder.into_frames().collect_frames().unwrap());
+    }
+
+    #[test]
+    fn test_encode_apng() {
+        let bytes = encode_animation(&sample_frames(), 10, AnimationFormat::Apng).unwrap();
+        let decoder = image::codecs::png::PngDecoder::new(Cursor::new(bytes)).unwrap();
+        assert!(decoder.is_apng().unwrap());
+        check_decoded(
+            decoder
+                .apng()
+                .unwrap()
+                .into_frames()
+                .collect_frames()
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_encode_webp() {
+        let bytes = encode_animation(&sample_frames(), 10, AnimationFormat::WebP).unwrap();
+        let decoder = image::codecs::webp::WebPDecoder::new(Cursor::new(bytes)).unwrap();
+        assert!(decoder.has_animation());
+        check_decoded(decoder.into_frames().collect_frames().unwrap());
+    }
+
+    #[test]
+    fn test_encode_png_sequence_repeats_frames() {
+        let sequence = encode_png_sequence(&sample_frames()).unwrap();
+        let names = sequence
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "replay-1.png",
+                "replay-2.png",
+                "replay-3.png",
+                "replay-4.png",
+                "replay-5.png"
+            ]
+        );
+        // 持续多帧的画面只编码一次
+        assert_eq!(sequence[0].1, sequence[2].1);
+        let last = image::load_from_memory(&sequence[4].1).unwrap().to_rgba8();
+        assert_eq!(last.get_pixel(0, 0).0, [0, 0, 255, 255]);
+        assert!(encode_animation(&[], 10, AnimationFormat::Gif).is_err());
+    }
+}
+
+mod subtitles {
+    use super::replay::{OTHER, USER, mixed_messages, settings};
+    use crate::components::baker::replay::{ReplayIntervalMode, ReplayPlayer, TOPIC_ENDED_TEXT};
+    use crate::components::baker::subtitles::{
+        SubtitleCue, SubtitleFormat, render_subtitles, subtitle_cues,
+    };
+
+    fn sender_name(sender_id: &str) -> String {
+        match sender_id {
+            USER => "管理员".to_string(),
+            OTHER => "佩丽卡".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn cue(start_ms: u64, end_ms: u64, speaker: Option<&str>, text: &str) -> SubtitleCue {
+        SubtitleCue {
+            start_ms,
+            end_ms,
+            speaker: speaker.map(str::to_string),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cues_follow_reveal_moments() {
+        let mut player = ReplayPlayer::new(
+            mixed_messages(),
+            USER.to_string(),
+            &settings(ReplayIntervalMode::Fixed),
+        );
+        let cues = subtitle_cues(&player, 1000, sender_name);
+        assert_eq!(
+            cues,
+            vec![
+                cue(900, 1200, Some("佩丽卡"), "管理员，你好"),
+                cue(1200, 2100, None, "佩丽卡加入了群聊"),
+                cue(2100, 3100, Some("管理员"), "收到"),
+                cue(3100, 3300, Some("佩丽卡"), "[图片]"),
+                cue(3300, 4300, None, TOPIC_ENDED_TEXT),
+            ]
+        );
+
+        player.speed = 2.0;
+        let starts = subtitle_cues(&player, 1000, sender_name)
+            .iter()
+            .map(|cue| cue.start_ms)
+            .collect::<Vec<_>>();
+        assert_eq!(starts, vec![450, 600, 1050, 1550, 1650]);
+    }
+
+    #[test]
+    fn test_render_subtitles() {
+        let cues = vec![
+            cue(900, 1200, Some("佩丽卡"), "你好\n\n<在吗>"),
+            cue(3_723_004, 3_725_000, None, "话题结束"),
+        ];
+        assert_eq!(
+            render_subtitles(&cues, SubtitleFormat::Srt),
+            "1\n00:00:00,900 --> 00:00:01,200\n佩丽卡：你好\n<在吗>\n\n\
+             2\n01:02:03,004 --> 01:02:05,000\n话题结束\n\n"
+        );
+        assert_eq!(
+            render_subtitles(&cues, SubtitleFormat::WebVtt),
+            "WEBVTT\n\n\
+             00:00:00.900 --> 00:00:01.200\n佩丽卡：你好\n&lt;在吗&gt;\n\n\
+             01:02:03.004 --> 01:02:05.000\n话题结束\n\n"
+        );
+        assert_eq!(
+            render_subtitles(&cues, SubtitleFormat::Transcript),
+            "[00:00:00.900] 佩丽卡：你好\n               <在吗>\n\
+             [01:02:03.004] 话题结束\n"
+        );
+    }
+}
+
+mod playlist {
+    use crate::components::baker::replay::{PlaylistSession, ReplayPlaylist};
+    use crate::components::baker::storage::v2::AppState;
+
+    #[test]
+    fn test_playlist_session_skips_missing_contacts() {
+        let playlist = ReplayPlaylist {
+            contact_ids: vec!["dm".to_string(), "gone".to_string(), "group".to_string()],
+            pause_ms: 800,
+        };
+        let mut session = PlaylistSession::new(&playlist, |id| id != "gone").unwrap();
+        assert_eq!(session.contact_ids, vec!["dm", "group"]);
+        assert_eq!(session.pause_ms, 800);
+        assert_eq!(session.current(), Some("dm"));
+        assert_eq!(session.advance(), Some("group"));
+        assert_eq!(session.advance(), None);
+        assert_eq!(session.advance(), None);
+
+        assert_eq!(PlaylistSession::new(&playlist, |_| false), None);
+    }
+
+    #[test]
+    fn test_playlist_is_persisted() {
+        let mut value = serde_json::to_value(AppState::default()).unwrap();
+        value.as_object_mut().unwrap().remove("replay_playlist");
+        let restored = serde_json::from_value::<AppState>(value).unwrap();
+        assert_eq!(restored.replay_playlist, ReplayPlaylist::default());
+
+        // 缺少的字段使用默认值
+        let restored = serde_json::from_str::<ReplayPlaylist>(r#"{"contact_ids":["dm"]}"#).unwrap();
+        assert_eq!(restored.pause_ms, ReplayPlaylist::default().pause_ms);
+    }
+}
+
+mod sound {
+    use crate::components::baker::storage::v2::{AppState, SoundEvent, SoundSettings};
+
+    #[test]
+    fn test_sound_volume_follows_settings() {
+        let mut settings = SoundSettings::default();
+        assert_eq!(settings.volume_for(SoundEvent::Incoming, false), Some(0.5));
+        assert_eq!(settings.volume_for(SoundEvent::Incoming, true), Some(0.5));
+        // 回应和话题结束默认不播放
+        assert_eq!(settings.volume_for(SoundEvent::Reaction, false), None);
+        assert_eq!(settings.volume_for(SoundEvent::TopicEnded, false), None);
+
+        settings.replay_muted = true;
+        assert_eq!(settings.volume_for(SoundEvent::Outgoing, true), None);
+        assert_eq!(settings.volume_for(SoundEvent::Outgoing, false), Some(0.5));
+
+        settings.volume = 3.0;
+        assert_eq!(settings.volume_for(SoundEvent::Status, false), Some(1.0));
+        settings.volume = 0.0;
+        assert_eq!(settings.volume_for(SoundEvent::Status, false), None);
+
+        settings.volume = 0.8;
+        settings.muted = true;
+        for event in SoundEvent::ALL {
+            assert_eq!(settings.volume_for(event, false), None);
+        }
+    }
+
+    #[test]
+    fn test_sound_settings_default_for_old_saves() {
+        let mut value = serde_json::to_value(AppState::default()).unwrap();
+        value.as_object_mut().unwrap().remove("sound");
+        let restored = serde_json::from_value::<AppState>(value).unwrap();
+        assert_eq!(restored.sound, SoundSettings::default());
+
+        let restored = serde_json::from_str::<SoundSettings>(
+            r#"{"volume":0.3,"incoming":{"custom_src":"data:audio/mpeg;base64,"}}"#,
+        )
+        .unwrap();
+        assert_eq!(restored.volume, 0.3);
+        assert!(restored.incoming.enabled);
+        assert_eq!(restored.incoming.custom_src, "data:audio/mpeg;base64,");
+        assert!(!restored.reaction.enabled);
+    }
+}
+
+mod chat_render {
+    use super::replay::{OTHER, USER, message};
+    use crate::components::baker::chat_render::{
+        BubbleContent, ChatSource, RenderAssets, RenderOptions, RowLayout, layout_chat,
+        reaction_labels, render_chat, render_chat_png,
+    };
+    use crate::components::baker::storage::v2::{
+        ChatHeadStyle, Contact, Message, MessageKind, MessageReaction, Operator, UserProfile,
+    };
+    use ab_glyph::FontArc;
+    use image::{Rgba, RgbaImage};
+    use std::collections::HashMap;
+
+    pub(super) const ALLY: &str = "ally";
+    pub(super) const IMAGE_SRC: &str = "data:image/png;base64,wide";
+
+    fn assets() -> RenderAssets {
+        let font = FontArc::try_from_slice(include_bytes!("../../assets/bender.otf")).unwrap();
+        RenderAssets::from_fonts(vec![font])
+    }
+
+    pub(super) fn contact() -> Contact {
+        Contact {
+            id: OTHER.to_string(),
+            unread_count: 0,
+            chat_head_style: ChatHeadStyle::Default,
+            name: String::new(),
+            avatar_url: String::new(),
+            participant_ids: vec![OTHER.to_string(), ALLY.to_string()],
+            participants_selves_ids: vec![ALLY.to_string()],
+            is_group: true,
+        }
+    }
+
+    pub(super) fn operators() -> Vec<Operator> {
+        [(OTHER, "Perlica"), (ALLY, "Chen")]
+            .into_iter()
+            .map(|(id, name)| Operator {
+                id: id.to_string(),
+                name: name.to_string(),
+                avatar_url: String::new(),
+            })
+            .collect()
+    }
+
+    pub(super) fn user_profile() -> UserProfile {
+        UserProfile {
+            id: USER.to_string(),
+            name: "Me".to_string(),
+            avatar_url: String::new(),
+        }
+    }
+
+    pub(super) fn messages() -> Vec<Message> {
+        let mut reacted = message("3", USER, "OK", MessageKind::Normal);
+        for _ in 0..2 {
+            reacted.reactions.push(MessageReaction {
+                content: "+1".to_string(),
+                sender_id: OTHER.to_string(),
+            });
+        }
+        vec![
+            message("0", OTHER, "Hello", MessageKind::Normal),
+            message("1", OTHER, "Are you there", MessageKind::Normal),
+            message("2", USER, "Joined", MessageKind::Status),
+            reacted,
+            message("4", ALLY, "Me too", MessageKind::Normal),
+            message("5", OTHER, IMAGE_SRC, MessageKind::Image),
+            message("6", OTHER, &"word ".repeat(60), MessageKind::Normal),
+        ]
+    }
+
+    fn message_layouts(
+        rows: &[RowLayout],
+    ) -> HashMap<String, &crate::components::baker::chat_render::MessageLayout> {
+        rows.iter()
+            .filter_map(|row| match row {
+                RowLayout::Message(msg) => Some((msg.message_id.clone(), msg)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reaction_labels_merge_duplicates() {
+        let reactions = ["+1", "ok", "+1", "+1"]
+            .into_iter()
+            .map(|content| MessageReaction {
+                content: content.to_string(),
+                sender_id: String::new(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(reaction_labels(&reactions), vec!["+1 x3", "ok"]);
+    }
+
+    #[test]
+    fn test_layout_follows_chat_area() {
+        let (contact, operators, user_profile, messages) =
+            (contact(), operators(), user_profile(), messages());
+        let source = ChatSource {
+            contact: &contact,
+            messages: &messages,
+            operators: &operators,
+            user_profile: &user_profile,
+        };
+        let images = HashMap::from([(IMAGE_SRC.to_string(), RgbaImage::new(640, 320))]);
+        let layout = layout_chat(&source, &assets(), 800.0, &images);
+        assert_eq!(layout.header_name, "Perlica");
+
+        let rows = message_layouts(&layout.rows);
+        // 自己和 participants_selves_ids 中的成员在右侧
+        assert!(!rows["0"].on_right);
+        assert!(rows["3"].on_right);
+        assert!(rows["4"].on_right);
+        // 连续消息只有第一条显示头像和名字
+        assert!(rows["0"].avatar.is_some() && rows["0"].sender_name.is_some());
+        assert!(rows["1"].avatar.is_none() && rows["1"].sender_name.is_none());
+        assert!(rows["4"].avatar.is_some());
+
+        // 气泡与头像之间留出 76px
+        assert_eq!(rows["0"].bubble.x, 24.0 + 76.0);
+        assert!((rows["4"].bubble.right() - (800.0 - 12.0 - 24.0 - 76.0)).abs() < 0.01);
+
+        let RowLayout::Status { rect, .. } = &layout.rows[2] else {
+            panic!("expected a status row");
+        };
+        assert!(rect.y > rows["1"].bubble.bottom());
+
+        // 图片不画气泡，按 320px 的宽度上限等比缩小
+        let image = rows["5"];
+        assert!(!image.has_bubble);
+        match &image.content {
+            BubbleContent::Media { rect, .. } => {
+                assert_eq!((rect.width, rect.height), (320.0, 160.0))
+            }
+            other => panic!("unexpected content {other:?}"),
+        }
+
+        let labels = rows["3"].reactions.iter().map(|(label, _)| label.as_str());
+        assert_eq!(labels.collect::<Vec<_>>(), vec!["+1 x2"]);
+
+        // 长消息按气泡的最大宽度折行
+        let long = rows["6"];
+        let BubbleContent::Text(lines) = &long.content else {
+            panic!("expected text");
+        };
+        assert!(lines.len() > 1);
+        assert!(long.bubble.width <= (800.0 - 12.0 - 48.0) * 0.6 + 0.01);
+        assert!(layout.height > long.bubble.bottom());
+
+        // 没有读取到的图片显示为文字
+        let layout = layout_chat(&source, &assets(), 800.0, &HashMap::new());
+        let rows = message_layouts(&layout.rows);
+        assert_eq!(
+            rows["5"].content,
+            BubbleContent::Text(vec!["[图片]".to_string()])
+        );
+        assert!(rows["5"].has_bubble);
+    }
+
+    #[test]
+    fn test_render_chat() {
+        let (contact, operators, user_profile, messages) =
+            (contact(), operators(), user_profile(), messages());
+        let source = ChatSource {
+            contact: &contact,
+            messages: &messages,
+            operators: &operators,
+            user_profile: &user_profile,
+        };
+        let assets = assets();
+        let options = RenderOptions {
+            width: 600,
+            scale: 2.0,
+            background: Some(Rgba([26, 26, 26, 255])),
+        };
+        let load_image = |src: &str| (src == IMAGE_SRC).then(|| RgbaImage::new(64, 32));
+        let image = render_chat(&source, &assets, &options, load_image);
+        let images = HashMap::from([(IMAGE_SRC.to_string(), RgbaImage::new(64, 32))]);
+        let layout = layout_chat(&source, &assets, 600.0, &images);
+        assert_eq!(image.width(), 1200);
+        assert_eq!(image.height(), (layout.height * 2.0).ceil() as u32);
+
+        let rows = message_layouts(&layout.rows);
+        let pixel = |x: f32, y: f32| *image.get_pixel((x * 2.0) as u32, (y * 2.0) as u32);
+        let left = rows["0"].bubble;
+        assert_eq!(
+            pixel(left.x + 4.0, left.y + left.height / 2.0),
+            Rgba([69, 69, 69, 255])
+        );
+        let right = rows["3"].bubble;
+        let color = pixel(right.x + 6.0, right.y + 6.0);
+        assert!(
+            color == Rgba([243, 242, 242, 255]) || color == Rgba([239, 237, 237, 255]),
+            "unexpected bubble color {color:?}"
+        );
+        // 背景保留设置的颜色
+        assert_eq!(pixel(300.0, layout.height - 8.0), Rgba([26, 26, 26, 255]));
+
+        let png = render_chat_png(&source, &assets, &options, load_image).unwrap();
+        let decoded = image::load_from_memory(&png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), image.dimensions());
+    }
+}
+
+mod render_cli {
+    use super::chat_render::{contact, operators};
+    use crate::components::baker::chat_render::RenderOptions;
+    use crate::components::baker::render_cli::{find_contact, parse_render_chat_args};
+    use crate::components::baker::storage::v2::AppState;
+    use image::Rgba;
+    use std::path::PathBuf;
+
+    fn args(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_render_chat_args() {
+        let parsed = parse_render_chat_args(args(&[
+            "state.json",
+            "--width",
+            "600",
+            "Perlica",
+            "out.png",
+            "--scale",
+            "2",
+            "--background",
+            "#1a1a1a",
+            "--assets",
+            "res",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.state, PathBuf::from("state.json"));
+        assert_eq!(parsed.contact, "Perlica");
+        assert_eq!(parsed.output, PathBuf::from("out.png"));
+        assert_eq!(parsed.assets_dir, PathBuf::from("res"));
+        assert_eq!(
+            parsed.options,
+            RenderOptions {
+                width: 600,
+                scale: 2.0,
+                background: Some(Rgba([26, 26, 26, 255])),
+            }
+        );
+
+        let defaults = parse_render_chat_args(args(&["a.json", "c", "o.png"])).unwrap();
+        assert_eq!(defaults.options, RenderOptions::default());
+        assert_eq!(defaults.assets_dir, PathBuf::from("assets"));
+
+        for bad in [
+            &["a.json", "c"][..],
+            &["a.json", "c", "o.png", "extra"],
+            &["a.json", "c", "o.png", "--width"],
+            &["a.json", "c", "o.png", "--width", "0"],
+            &["a.json", "c", "o.png", "--scale", "-1"],
+            &["a.json", "c", "o.png", "--background", "red"],
+            &["a.json", "c", "o.png", "--quality", "90"],
+        ] {
+            assert!(parse_render_chat_args(args(bad)).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_find_contact() {
+        let mut named = contact();
+        named.id = "named".to_string();
+        named.name = "Squad".to_string();
+        let state = AppState {
+            contacts: vec![contact(), named],
+            operators: operators(),
+            ..AppState::default()
+        };
+        assert_eq!(find_contact(&state, "named").unwrap().id, "named");
+        assert_eq!(find_contact(&state, "Squad").unwrap().id, "named");
+        assert!(find_contact(&state, "nobody").is_err());
+
+        let mut twin = state.contacts[1].clone();
+        twin.id = "twin".to_string();
+        let state = AppState {
+            contacts: vec![state.contacts[1].clone(), twin],
+            ..state
+        };
+        assert!(find_contact(&state, "Squad").is_err());
+        assert_eq!(find_contact(&state, "twin").unwrap().id, "twin");
+    }
+}
+
+mod capture_pages {
+    use super::replay::{USER, message};
+    use crate::components::baker::capture_pages::{
+        CaptureFormat, JPEG_MATTE, capture_filename, capture_range, encode_capture, page_filename,
+        split_pages, unique_filenames, unique_filenames_besides, zip_store,
+    };
+    use crate::components::baker::replay_export::encode_png;
+    use crate::components::baker::storage::v2::{AppState, ExportSettings, MessageKind};
+    use chrono::NaiveDate;
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn test_capture_range() {
+        let messages = (0..5)
+            .map(|i| message(&i.to_string(), USER, "hi", MessageKind::Normal))
+            .collect::<Vec<_>>();
+        assert_eq!(capture_range(&messages, None, None), 0..5);
+        assert_eq!(capture_range(&messages, Some("1"), Some("3")), 1..4);
+        assert_eq!(capture_range(&messages, Some("2"), None), 2..5);
+        // 结束消息在起始消息之前时交换
+        assert_eq!(capture_range(&messages, Some("3"), Some("1")), 1..4);
+        assert_eq!(capture_range(&messages, Some("missing"), Some("0")), 0..1);
+        assert_eq!(capture_range(&[], Some("0"), None), 0..0);
+    }
+
+    #[test]
+    fn test_split_pages_at_message_boundaries() {
+        let boundaries = [24.0, 100.0, 180.0, 400.0, 460.0];
+        assert_eq!(split_pages(&boundaries, 200.0), vec![0..2, 2..3, 3..4]);
+        assert_eq!(split_pages(&boundaries, 1000.0), vec![0..4]);
+        // 比一页还高的消息单独成为一页
+        assert_eq!(split_pages(&[0.0, 500.0, 520.0], 100.0), vec![0..1, 1..2]);
+        assert!(split_pages(&[24.0], 100.0).is_empty());
+    }
+
+    #[test]
+    fn test_page_filename() {
+        assert_eq!(page_filename("download", 0, 3, "png"), "download-1.png");
+        assert_eq!(page_filename("download", 8, 12, "png"), "download-09.png");
+    }
+
+    #[test]
+    fn test_capture_filename_template() {
+        let now = NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        assert_eq!(
+            capture_filename("{name}-{date}", "阿米娅", now, None, "png"),
+            "阿米娅-2024-05-01.png"
+        );
+        assert_eq!(
+            capture_filename("{date}_{time}", "", now, None, "jpg"),
+            "2024-05-01_093000.jpg"
+        );
+        // 模板中没有编号时加在末尾
+        assert_eq!(
+            capture_filename("{name}", "chat", now, Some((1, 12)), "webp"),
+            "chat-02.webp"
+        );
+        assert_eq!(
+            capture_filename("p{page}-{name}", "chat", now, Some((0, 3)), "png"),
+            "p1-chat.png"
+        );
+        // 不允许的字符被替换，空的文件名使用默认值
+        assert_eq!(
+            capture_filename("{name}", "a/b:c?", now, None, "png"),
+            "a_b_c_.png"
+        );
+        assert_eq!(
+            capture_filename(" {name} ", "", now, None, "zip"),
+            "download.zip"
+        );
+        assert_eq!(
+            capture_filename("{name}", "", now, Some((0, 2)), "png"),
+            "download-1.png"
+        );
+    }
+
+    #[test]
+    fn test_unique_filenames() {
+        let names = [
+            "阿米娅.png",
+            "凯尔希.png",
+            "阿米娅.png",
+            "Chat.png",
+            "chat.png",
+            "阿米娅.png",
+        ]
+        .map(String::from)
+        .to_vec();
+        assert_eq!(
+            unique_filenames(names),
+            vec![
+                "阿米娅.png",
+                "凯尔希.png",
+                "阿米娅 (2).png",
+                "Chat.png",
+                "chat (2).png",
+                "阿米娅 (3).png"
+            ]
+        );
+        assert_eq!(
+            unique_filenames(vec!["a".into(), "a".into()]),
+            vec!["a", "a (2)"]
+        );
+    }
+
+    #[test]
+    fn test_unique_filenames_besides_existing() {
+        let existing = ["Chat-1.png", "chat-1 (2).png", "notes.txt"].map(String::from);
+        let names = ["chat-1.png", "chat-2.png", "chat-1.png"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(
+            unique_filenames_besides(names, existing),
+            vec!["chat-1 (3).png", "chat-2.png", "chat-1 (4).png"]
+        );
+    }
+
+    #[test]
+    fn test_encode_capture_formats() {
+        let mut image = RgbaImage::from_pixel(8, 4, Rgba([255, 255, 255, 255]));
+        image.put_pixel(0, 0, Rgba([0, 0, 0, 0]));
+        let png = encode_png(&image).unwrap();
+
+        assert_eq!(encode_capture(&png, CaptureFormat::Png, 90).unwrap(), png);
+
+        let jpeg = encode_capture(&png, CaptureFormat::Jpeg, 90).unwrap();
+        assert_eq!(&jpeg[..2], &[0xff, 0xd8]);
+        // JPEG 不支持透明，透明的部分铺上底色
+        let decoded = image::load_from_memory(&jpeg).unwrap().to_rgb8();
+        assert_eq!(decoded.dimensions(), (8, 4));
+        let corner = decoded.get_pixel(0, 0);
+        assert!(corner[0].abs_diff(JPEG_MATTE[0]) < 48);
+
+        let webp = encode_capture(&png, CaptureFormat::WebP, 90).unwrap();
+        assert_eq!(&webp[8..12], b"WEBP");
+        let decoded = image::load_from_memory(&webp).unwrap().to_rgba8();
+        assert_eq!(decoded, image);
+
+        let avif = encode_capture(&png, CaptureFormat::Avif, 60).unwrap();
+        assert_eq!(&avif[4..8], b"ftyp");
+    }
+
+    #[test]
+    fn test_zip_store() {
+        let files = vec![
+            ("download-1.png".to_string(), b"first".to_vec()),
+            ("下载-2.png".to_string(), b"second page".to_vec()),
+        ];
+        let zip = zip_store(&files).unwrap();
+        assert_eq!(&zip[..4], &[0x50, 0x4b, 0x03, 0x04]);
+
+        // 结尾的目录记录指向中央目录
+        let end = &zip[zip.len() - 22..];
+        assert_eq!(&end[..4], &[0x50, 0x4b, 0x05, 0x06]);
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+        let central_size = u32::from_le_bytes(end[12..16].try_into().unwrap()) as usize;
+        let central_offset = u32::from_le_bytes(end[16..20].try_into().unwrap()) as usize;
+        assert_eq!(central_offset + central_size, zip.len() - 22);
+        assert_eq!(
+            &zip[central_offset..central_offset + 4],
+            &[0x50, 0x4b, 0x01, 0x02]
+        );
+
+        // 不压缩，内容原样保存
+        let first = &zip[30 + files[0].0.len()..][..5];
+        assert_eq!(first, b"first");
+
+        // 超出格式上限时报错，而不是写入截断的长度
+        let long_name = vec![("a".repeat(70_000), Vec::new())];
+        assert!(zip_store(&long_name).is_err());
+    }
+
+    #[test]
+    fn test_export_settings_default_for_old_saves() {
+        let mut value = serde_json::to_value(AppState::default()).unwrap();
+        value.as_object_mut().unwrap().remove("export");
+        let restored = serde_json::from_value::<AppState>(value).unwrap();
+        assert_eq!(restored.export, ExportSettings::default());
+        assert!(restored.export.ask);
+
+        let restored = serde_json::from_str::<ExportSettings>(r#"{"dir":"/tmp/baker"}"#).unwrap();
+        assert_eq!(restored.dir, "/tmp/baker");
+        assert!(restored.ask);
+    }
+}
+
+mod text_export {
+    use super::chat_render::{ALLY, IMAGE_SRC, contact, messages, operators, user_profile};
+    use super::mission::{mission, mission_message};
+    use super::replay::{OTHER, USER, message};
+    use crate::components::baker::chat_render::ChatSource;
+    use crate::components::baker::storage::v2::MessageKind;
+    use crate::components::baker::text_export::{TextFormat, export_text};
+    use std::collections::HashMap;
+
+    const STICKER_SRC: &str = "/assets/stickers/wave.png";
+    const STICKER_DATA: &str = "data:image/webp;base64,d2F2ZQ==";
+
+    fn export(format: TextFormat) -> crate::components::baker::text_export::TextExport {
+        let (contact, operators, user_profile) = (contact(), operators(), user_profile());
+        let mut messages = messages();
+        messages.push(message("7", ALLY, "<b>1 * 2</b>", MessageKind::Normal));
+        messages.push(message("8", USER, STICKER_SRC, MessageKind::Sticker));
+        messages.push(message("9", OTHER, IMAGE_SRC, MessageKind::Image));
+        messages.push(message("10", USER, "话题结束", MessageKind::TopicEnded));
+        messages.push(mission_message("11", USER, mission()));
+        let source = ChatSource {
+            contact: &contact,
+            messages: &messages,
+            operators: &operators,
+            user_profile: &user_profile,
+        };
+        let images = HashMap::from([(STICKER_SRC.to_string(), STICKER_DATA.to_string())]);
+        export_text(&source, format, &images)
+    }
+
+    #[test]
+    fn test_markdown_export() {
+        let export = export(TextFormat::Markdown);
+        let text = &export.text;
+        assert!(text.starts_with("# Perlica\n\n**Perlica**：Hello\n"));
+        assert!(text.contains("\n*Joined*\n"));
+        assert!(text.contains("**Me**：OK  \n*回应：\\+1 x2*\n"));
+        assert!(text.contains("**Chen**：\\<b\\>1 \\* 2\\</b\\>\n"));
+        assert!(text.contains("---\n\n*话题结束*\n"));
+        assert!(text.contains("**Me**：\\[任务\\] 侦察（已接取）  \n前往四号谷地  \n"));
+
+        // 图片另存为文件，同一张图片只保存一次
+        assert!(text.contains("**Perlica**：![图片](<images/001.png>)"));
+        assert!(text.contains("**Me**：![表情](<images/002.webp>)"));
+        assert_eq!(text.matches("images/001.png").count(), 2);
+        let names = export
+            .files
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["images/001.png", "images/002.webp"]);
+        assert_eq!(export.files[1].1, b"wave");
+    }
+
+    #[test]
+    fn test_plain_text_export() {
+        let export = export(TextFormat::PlainText);
+        assert!(export.files.is_empty());
+        let lines = export.text.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[..4],
+            ["Perlica", "", "Perlica：Hello", "Perlica：Are you there"]
+        );
+        assert_eq!(lines[4], "（Joined）");
+        assert_eq!(lines[5..7], ["Me：OK", "  回应：+1 x2"]);
+        assert!(lines.contains(&"Chen：Me too"));
+        assert!(lines.contains(&"Perlica：[图片]"));
+        assert!(lines.contains(&"Me：[表情]"));
+        assert!(lines.contains(&"—— 话题结束 ——"));
+        assert!(lines.ends_with(&[
+            "Me：[任务] 侦察（已接取）",
+            "前往四号谷地",
+            "注意安全",
+            "[x] 抵达营地",
+            "[ ] 带回样本"
+        ]));
+    }
+
+    #[test]
+    fn test_html_export_is_self_contained() {
+        let html = export(TextFormat::Html).text;
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Perlica</title>"));
+        // 群聊中每条消息都标明发送者
+        assert_eq!(html.matches("<div class=\"name\">Perlica</div>").count(), 5);
+        assert!(html.contains("<div class=\"name\">Chen</div>"));
+        // 自己和 participants_selves_ids 中的成员在右侧
+        assert!(html.contains("<div class=\"row right first\"><div class=\"avatar\">C</div>"));
+        assert!(html.contains("&lt;b&gt;1 * 2&lt;/b&gt;"));
+        assert!(html.contains(&format!("<img class=\"media\" src=\"{IMAGE_SRC}\"")));
+        // 应用内的图片换成 data URL 嵌入
+        assert!(html.contains(&format!(
+            "<img class=\"media sticker\" src=\"{STICKER_DATA}\""
+        )));
+        assert!(!html.contains(STICKER_SRC));
+        assert!(html.contains("<span>+1 x2</span>"));
+        assert!(html.contains("<span class=\"mission-badge\">任务</span><span>侦察</span>"));
+        assert!(html.contains("<li class=\"done\">抵达营地</li><li>带回样本</li>"));
+    }
+}
+
+mod script {
+    use super::chat_render::{ALLY, IMAGE_SRC, messages, operators, user_profile};
+    use super::mission::{mission, mission_message};
+    use super::replay::{OTHER, USER, message};
+    use crate::components::baker::script::{format_script, operator_ids, parse_script};
+    use crate::components::baker::storage::v2::{
+        Message, MessageKind, MessageReaction, MessageReplayOverrides, Operator,
+    };
+
+    /// 去掉编号后比较
+    fn without_ids(messages: &[Message]) -> Vec<Message> {
+        messages
+            .iter()
+            .map(|msg| Message {
+                id: String::new(),
+                ..msg.clone()
+            })
+            .collect()
+    }
+
+    fn tricky_messages() -> Vec<Message> {
+        let mut messages = messages();
+        let mut multiline = message("7", ALLY, "first\n\n  indented\n", MessageKind::Normal);
+        multiline.reactions.push(MessageReaction {
+            content: "two\nlines".to_string(),
+            sender_id: USER.to_string(),
+        });
+        multiline.reactions.push(MessageReaction {
+            content: "anonymous: yes".to_string(),
+            sender_id: String::new(),
+        });
+        multiline.replay = Some(MessageReplayOverrides {
+            typing_ms: Some(1200),
+            pause_before_ms: 300,
+            skip_typing: true,
+            ..Default::default()
+        });
+        messages.extend([
+            multiline,
+            message("8", OTHER, "[图片] not an image", MessageKind::Normal),
+            message("9", OTHER, "\\backslash", MessageKind::Normal),
+            message("10", OTHER, "> not a status", MessageKind::Normal),
+            message("11", OTHER, "left", MessageKind::Status),
+            message(
+                "12",
+                USER,
+                "/assets/stickers/wave.png",
+                MessageKind::Sticker,
+            ),
+            message("13", ALLY, "done", MessageKind::TopicEnded),
+            message("14", USER, "", MessageKind::TopicEnded),
+            message("15", OTHER, "[任务] not a mission", MessageKind::Normal),
+            mission_message("16", ALLY, mission()),
+        ]);
+        messages
+    }
+
+    #[test]
+    fn test_script_round_trip() {
+        let (operators, user_profile) = (operators(), user_profile());
+        let messages = tricky_messages();
+        let script = format_script(&messages, &operators, &user_profile);
+        let parsed = parse_script(&script, &operators, &user_profile).unwrap();
+        assert_eq!(without_ids(&parsed), without_ids(&messages));
+        assert_eq!(format_script(&parsed, &operators, &user_profile), script);
+        assert!(parsed.iter().all(|msg| !msg.id.is_empty()));
+    }
+
+    #[test]
+    fn test_script_syntax() {
+        let (operators, user_profile) = (operators(), user_profile());
+        let script = format!(
+            "# 开场\n\
+             Perlica: Hello\n\
+             \n\
+             Me：OK\n\
+             \x20 + Perlica: +1\n\
+             > Joined\n\
+             Chen: [图片] {IMAGE_SRC}\n\
+             | second line\n\
+             ---\n"
+        );
+        let parsed = parse_script(&script, &operators, &user_profile).unwrap();
+        let mut reacted = message("", USER, "OK", MessageKind::Normal);
+        reacted.reactions.push(MessageReaction {
+            content: "+1".to_string(),
+            sender_id: OTHER.to_string(),
+        });
+        let expected = vec![
+            message("", OTHER, "Hello", MessageKind::Normal),
+            reacted,
+            message("", USER, "Joined", MessageKind::Status),
+            message(
+                "",
+                ALLY,
+                &format!("{IMAGE_SRC}\nsecond line"),
+                MessageKind::Image,
+            ),
+            message("", USER, "", MessageKind::TopicEnded),
+        ];
+        assert_eq!(without_ids(&parsed), expected);
+        assert_eq!(operator_ids(&parsed, USER), vec![OTHER, ALLY]);
+    }
+
+    #[test]
+    fn test_script_names_that_need_ids() {
+        let mut operators = operators();
+        operators.push(Operator {
+            id: "twin".to_string(),
+            name: "Chen".to_string(),
+            avatar_url: String::new(),
+        });
+        operators.push(Operator {
+            id: "colon".to_string(),
+            name: "A: B".to_string(),
+            avatar_url: String::new(),
+        });
+        let user_profile = user_profile();
+        let messages = vec![
+            message("0", ALLY, "one", MessageKind::Normal),
+            message("1", "twin", "two", MessageKind::Normal),
+            message("2", "colon", "three", MessageKind::Normal),
+        ];
+        let script = format_script(&messages, &operators, &user_profile);
+        assert_eq!(script, "@ally: one\n@twin: two\n@colon: three\n");
+        let parsed = parse_script(&script, &operators, &user_profile).unwrap();
+        assert_eq!(without_ids(&parsed), without_ids(&messages));
+
+        let errors =
+            parse_script("Chen: hi\nNobody: hi\n| more\n", &operators, &user_profile).unwrap_err();
+        assert_eq!(
+            errors.iter().map(|err| err.line).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!(errors[0].to_string().starts_with("第 1 行："));
+        let errors = parse_script("| orphan\nno colon\n", &operators, &user_profile).unwrap_err();
+        assert_eq!(
+            errors.iter().map(|err| err.line).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        let errors =
+            parse_script("Perlica: [任务] {oops\n", &operators, &user_profile).unwrap_err();
+        assert!(errors[0].message.starts_with("无法读取任务"));
+    }
+
+    #[test]
+    fn test_script_round_trip_unknown_sender_and_crlf() {
+        let (operators, user_profile) = (operators(), user_profile());
+        let mut removed = message(
+            "0",
+            "removed",
+            "line one\r\nline two\r",
+            MessageKind::Normal,
+        );
+        removed.reactions.push(MessageReaction {
+            content: "ok\r".to_string(),
+            sender_id: "gone".to_string(),
+        });
+        let messages = vec![removed, message("1", OTHER, "a\rb", MessageKind::Normal)];
+        let script = format_script(&messages, &operators, &user_profile);
+        assert!(script.starts_with("@removed: line one\r\n"));
+        let parsed = parse_script(&script, &operators, &user_profile).unwrap();
+        assert_eq!(without_ids(&parsed), without_ids(&messages));
+        assert_eq!(operator_ids(&parsed, USER), vec!["removed", "gone", OTHER]);
+
+        // 只有以 `\r` 结尾的内容时仍能读回
+        let messages = vec![message("0", OTHER, "end\r", MessageKind::Normal)];
+        let script = format_script(&messages, &operators, &user_profile);
+        let parsed = parse_script(&script, &operators, &user_profile).unwrap();
+        assert_eq!(without_ids(&parsed), without_ids(&messages));
+
+        // Windows 的换行
+        let parsed = parse_script(
+            "Perlica: Hello\r\n| again\r\n+ Chen: +1\r\n",
+            &operators,
+            &user_profile,
+        )
+        .unwrap();
+        assert_eq!(parsed[0].content, "Hello\nagain");
+        assert_eq!(parsed[0].reactions[0].content, "+1");
+    }
+}
+
+mod bulk_edit {
+    use super::replay::{OTHER, USER, message};
+    use crate::components::baker::bulk_edit::{
+        change_kind, move_selected, range_ids, set_sender, shift_selected,
+    };
+    use crate::components::baker::storage::v2::{Message, MessageKind};
+    use std::collections::HashSet;
+
+    fn messages() -> Vec<Message> {
+        ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|id| message(id, OTHER, id, MessageKind::Normal))
+            .collect()
+    }
+
+    fn ids(messages: &[Message]) -> String {
+        messages.iter().map(|msg| msg.id.as_str()).collect()
+    }
+
+    fn selection(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_move_selected_keeps_order() {
+        let mut list = messages();
+        move_selected(&mut list, &selection(&["d", "b"]), 0);
+        assert_eq!(ids(&list), "bdace");
+
+        let mut list = messages();
+        move_selected(&mut list, &selection(&["a", "c"]), 4);
+        assert_eq!(ids(&list), "bdace");
+
+        let mut list = messages();
+        move_selected(&mut list, &selection(&["a", "b"]), 99);
+        assert_eq!(ids(&list), "cdeab");
+
+        // 放到选中的消息自己之间时不变
+        let mut list = messages();
+        move_selected(&mut list, &selection(&["b", "c"]), 2);
+        assert_eq!(ids(&list), "abcde");
+    }
+
+    #[test]
+    fn test_shift_selected() {
+        let mut list = messages();
+        shift_selected(&mut list, &selection(&["a", "c"]), true);
+        assert_eq!(ids(&list), "acbde");
+        shift_selected(&mut list, &selection(&["a", "c"]), true);
+        assert_eq!(ids(&list), "acbde");
+
+        let mut list = messages();
+        shift_selected(&mut list, &selection(&["d", "e"]), false);
+        assert_eq!(ids(&list), "abcde");
+        shift_selected(&mut list, &selection(&["b", "d"]), false);
+        assert_eq!(ids(&list), "acbed");
+    }
+
+    #[test]
+    fn test_set_sender_and_range() {
+        let mut list = messages();
+        set_sender(&mut list, &selection(&["b", "e"]), USER);
+        let senders = list
+            .iter()
+            .map(|msg| msg.sender_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(senders, vec![OTHER, USER, OTHER, OTHER, USER]);
+
+        assert_eq!(range_ids(&list, 3, 1), vec!["b", "c", "d"]);
+        assert_eq!(range_ids(&list, 2, 2), vec!["c"]);
+    }
+
+    #[test]
+    fn test_change_kind_keeps_mission_title() {
+        let mut msg = message("a", OTHER, "侦察", MessageKind::Normal);
+        change_kind(&mut msg, MessageKind::Mission);
+        assert_eq!(msg.kind, MessageKind::Mission);
+        assert!(msg.content.is_empty());
+        assert_eq!(msg.mission.as_ref().unwrap().title, "侦察");
+
+        change_kind(&mut msg, MessageKind::Status);
+        assert_eq!(msg.kind, MessageKind::Status);
+        assert_eq!(msg.content, "侦察");
+        assert!(msg.mission.is_none());
+    }
+}
+
+mod mission {
+    use super::replay::{USER, message};
+    use crate::components::baker::storage::v2::{
+        AppState, Message, MessageKind, Mission, MissionObjective, MissionStatus,
+    };
+
+    pub(super) fn mission() -> Mission {
+        Mission {
+            title: "侦察".to_string(),
+            description: "前往四号谷地\n注意安全".to_string(),
+            objectives: vec![
+                MissionObjective {
+                    text: "抵达营地".to_string(),
+                    done: true,
+                },
+                MissionObjective {
+                    text: "带回样本".to_string(),
+                    done: false,
+                },
+            ],
+            status: MissionStatus::Accepted,
+        }
+    }
+
+    pub(super) fn mission_message(id: &str, sender_id: &str, mission: Mission) -> Message {
+        Message {
+            mission: Some(mission),
+            ..message(id, sender_id, "", MessageKind::Mission)
+        }
+    }
+
+    #[test]
+    fn test_mission_text() {
+        let msg = mission_message("m", USER, mission());
+        assert_eq!(
+            msg.mission_text(),
+            "[任务] 侦察（已接取）\n前往四号谷地\n注意安全\n[x] 抵达营地\n[ ] 带回样本"
+        );
+        let done = Mission {
+            title: "撤离".to_string(),
+            status: MissionStatus::Completed,
+            ..Default::default()
+        };
+        assert_eq!(done.to_text(), "[任务] 撤离（已完成）");
+        assert!(
+            message("n", USER, "hi", MessageKind::Normal)
+                .mission_text()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_mission_persists() {
+        let mut state = AppState::default();
+        state.messages.insert(
+            "dm".to_string(),
+            vec![
+                mission_message("m", USER, mission()),
+                message("n", USER, "hi", MessageKind::Normal),
+            ],
+        );
+        let json = serde_json::to_string(&state).unwrap();
+        let restored = serde_json::from_str::<AppState>(&json).unwrap();
+        assert_eq!(restored.messages, state.messages);
+
+        // 旧存档中的消息没有任务
+        let mut value = serde_json::to_value(&state).unwrap();
+        value["messages"]["dm"][1]
+            .as_object_mut()
+            .unwrap()
+            .remove("mission");
+        let restored = serde_json::from_value::<AppState>(value).unwrap();
+        assert!(restored.messages["dm"][1].mission.is_none());
+
+        let partial = serde_json::from_str::<Mission>(r#"{"title":"撤离"}"#).unwrap();
+        assert_eq!(partial.status, MissionStatus::Accepted);
+        assert!(partial.objectives.is_empty());
+    }
+}
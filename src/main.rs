@@ -19,11 +19,19 @@ const FAVICON: Asset = asset!("/assets/favicon.ico");
 const MAIN_CSS: Asset = asset!("/assets/styling/main.css");
 const MODAL_CSS: Asset = asset!("/assets/styling/modal.css");
 const TAILWIND_CSS: Asset = asset!("/assets/tailwind.css");
+// 截图脚本随应用打包，离线也可以截图
+const CAPTURE_JS: Asset = asset!("/assets/js/capture.js");
 
 const FONT: Asset = asset!("/assets/SourceHanSansSC-Regular.otf");
 const FONT_BENDER: Asset = asset!("/assets/bender.otf");
 
 fn main() {
+    // `baker-dx render-chat ...` 只在命令行渲染会话，不打开界面
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(code) = components::baker::render_cli::run(std::env::args().skip(1)) {
+        std::process::exit(code);
+    }
+
     #[cfg(all(not(target_arch = "wasm32"), feature = "desktop"))]
     {
         let icon = load_window_icon();
@@ -207,7 +215,7 @@ fn App() -> Element {
         document::Link { rel: "stylesheet", href: MAIN_CSS }
         document::Link { rel: "stylesheet", href: TAILWIND_CSS }
         document::Link { rel: "stylesheet", href: MODAL_CSS }
-        document::Script { src: "https://unpkg.com/@zumer/snapdom/dist/snapdom.js" }
+        document::Script { src: CAPTURE_JS }
         document::Style { {font_face} }
         document::Style { {font_face_bender.clone()} }
         document::Title { "Baker" }